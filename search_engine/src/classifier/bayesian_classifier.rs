@@ -1,86 +1,192 @@
-use std::time::{Duration, Instant};
-use std::collections::HashSet;
+use std::time::Instant;
+use std::collections::HashMap;
 use std::collections::BinaryHeap;
 use index::disk_inverted_index::DiskInvertedIndex;
 use index::disk_inverted_index::IndexReader;
+use classifier::classifier::ClassScore;
+use classifier::classifier::Classification;
 use classifier::classifier::Classifier;
-use classifier::classifier::DocumentClass;
 use classifier::classifier::TermClassScore;
+use classifier::training_set::TrainingSet;
+
+/*
+ * Number of discriminating terms a Bayesian classifier is trained on by default
+ */
+pub const DISCRIMINATING_TERMS: u32 = 50;
+
+/*
+ * The discriminating terms a Bayesian classifier was trained on and their probability in every
+ * class, which can be saved and loaded again instead of selecting the terms every time
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BayesianModel {
+    /*
+     * The sorted terms of every class
+     */
+    vocab: Vec<String>,
+
+    /*
+     * The discriminating terms, best first
+     */
+    features: Vec<TermClassScore>,
+    classes: Vec<BayesianClass>,
+}
 
-pub struct BayesianClassifier<'a> {
-    index_disputed: &'a DiskInvertedIndex<'a>,
-    index_hamilton: &'a DiskInvertedIndex<'a>,
-    index_jay: &'a DiskInvertedIndex<'a>,
-    index_madison: &'a DiskInvertedIndex<'a>,
+/*
+ * The number of training documents of a class and the log probability of every discriminating
+ * term in the class, log2((tf + 1) / (tf of every discriminating term + number of terms))
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BayesianClass {
+    label: String,
+    num_documents: u32,
+    log_probabilities: Vec<f64>,
 }
 
-impl<'a> BayesianClassifier<'a> {
-    pub fn new(index_disputed: &'a DiskInvertedIndex, index_hamilton: &'a DiskInvertedIndex, index_jay: &'a DiskInvertedIndex, index_madison: &'a DiskInvertedIndex) -> BayesianClassifier<'a> {
+impl BayesianModel {
+    pub fn get_labels(&self) -> Vec<&str> {
+        self.classes.iter().map(|class| class.label.as_str()).collect()
+    }
+
+    pub fn get_features(&self) -> &[TermClassScore] {
+        &self.features
+    }
+}
+
+/*
+ * Multinomial Naive Bayes classifier over the terms with the highest mutual information with a
+ * class
+ */
+pub struct BayesianClassifier {
+    documents: DiskInvertedIndex,
+    model: BayesianModel,
+
+    /*
+     * The position of every discriminating term, and the log prior probability of every class
+     */
+    feature_ids: HashMap<String, usize>,
+    log_priors: Vec<f64>,
+}
+
+impl BayesianClassifier {
+    /*
+     * Constructs a Bayesian classifier, selecting the discriminating terms of the training set
+     *
+     * # Arguments
+     *
+     * *`documents` - The Disk Inverted Index holding the documents to classify
+     * *`training` - The labelled classes documents are classified into
+     */
+    pub fn new(documents: &DiskInvertedIndex, training: &TrainingSet) -> BayesianClassifier {
+        BayesianClassifier::from_model(documents, BayesianClassifier::fit(training, DISCRIMINATING_TERMS))
+    }
+
+    /*
+     * Constructs a Bayesian classifier from a trained model
+     *
+     * # Arguments
+     *
+     * *`documents` - The Disk Inverted Index holding the documents to classify
+     * *`model` - The discriminating terms and their probabilities
+     */
+    pub fn from_model(documents: &DiskInvertedIndex, model: BayesianModel) -> BayesianClassifier {
+        let feature_ids = model
+            .features
+            .iter()
+            .enumerate()
+            .map(|(feature_id, feature)| (feature.term.clone(), feature_id))
+            .collect();
+        let total_num_docs: u32 = model.classes.iter().map(|class| class.num_documents).sum();
+        let log_priors = model
+            .classes
+            .iter()
+            .map(|class| (class.num_documents as f64 / total_num_docs.max(1) as f64).log2())
+            .collect();
         BayesianClassifier {
-            index_disputed: index_disputed,
-            index_hamilton: index_hamilton,
-            index_jay: index_jay,
-            index_madison: index_madison,
+            documents: documents.clone(),
+            model,
+            feature_ids,
+            log_priors,
+        }
+    }
+
+    /*
+     * Trains a Bayesian classifier by selecting the discriminating terms of a training set and
+     * computing their probability in every class
+     *
+     * # Arguments
+     *
+     * *`training` - The labelled classes documents are classified into
+     * *`k` - Number of discriminating terms
+     *
+     * # Returns
+     *
+     * The model of the classes, in the order of the classes
+     */
+    pub fn fit(training: &TrainingSet, k: u32) -> BayesianModel {
+        let features = BayesianClassifier::build_discriminating_vocab_set(training, k);
+        let classes = training
+            .get_classes()
+            .iter()
+            .map(|class| {
+                let term_frequencies: Vec<u32> = features.iter().map(|feature| class.get_term_frequency(&feature.term)).collect();
+                let discriminating_terms_score: u32 = term_frequencies.iter().sum();
+                let denominator = (discriminating_terms_score + features.len() as u32) as f64;
+                BayesianClass {
+                    label: class.get_label().to_string(),
+                    num_documents: class.get_num_documents(),
+                    log_probabilities: term_frequencies
+                        .iter()
+                        .map(|&term_frequency| ((term_frequency + 1) as f64 / denominator).log2())
+                        .collect(),
+                }
+            })
+            .collect();
+        BayesianModel {
+            vocab: training.get_vocab(),
+            features,
+            classes,
         }
     }
 
-    pub fn build_discriminating_vocab_set(&self, k: u32) -> Vec<TermClassScore> {
+    pub fn get_model(&self) -> &BayesianModel {
+        &self.model
+    }
+
+    /*
+     * Selects the terms telling the classes of a training set apart best
+     *
+     * # Arguments
+     *
+     * *`training` - The labelled classes
+     * *`k` - Number of terms selected
+     *
+     * # Returns
+     *
+     * The k distinct terms with the highest mutual information with any class, best first, each
+     * with the class it was scored for
+     */
+    pub fn build_discriminating_vocab_set(training: &TrainingSet, k: u32) -> Vec<TermClassScore> {
         let mut priority_queue: BinaryHeap<TermClassScore> = BinaryHeap::new();
 
         let time = Instant::now();
 
-        let hamilton_vocabulary = self.index_hamilton.get_vocab();
-        for term in &hamilton_vocabulary {
-            // println!("Hamilton: {}", term);
-            match self.calculate_mutual_information_score(term.clone(), DocumentClass::Hamilton) {
-                Ok(score) => {
-                    match TermClassScore::new(score, term.clone(), DocumentClass::Hamilton) {
-                        Some(score) => {
-                            priority_queue.push(score);
-                        },
-                        None => {
-                            // Do nothing.
-                        },
-                    };
-                },
-                Err(error) => panic!("There was an error calculating the score for term {}. The error is: {}", term, error),
-            };
-        }
-        let jay_vocabulary = self.index_jay.get_vocab();
-        for term in &jay_vocabulary {
-            match self.calculate_mutual_information_score(term.clone(), DocumentClass::Jay) {
-                Ok(score) => {
-                    // println!("Jay: {}", term);
-                    match TermClassScore::new(score, term.clone(), DocumentClass::Jay) {
-                        Some(score) => {
-                            priority_queue.push(score);
-                        },
-                        None => {
-                            // Do nothing.
-                        },
-                    };
-                },
-                Err(error) => panic!("There was an error calculating the score for term {}. The error is: {}", term, error),
-            };
-        }
-        let madison_vocabulary = self.index_madison.get_vocab();
-        for term in &madison_vocabulary {
-            // println!("Madison: {}", term);
-            match self.calculate_mutual_information_score(term.clone(), DocumentClass::Madison) {
-                Ok(score) => {
-                    match TermClassScore::new(score, term.clone(), DocumentClass::Madison) {
-                        Some(score) => {
-                            priority_queue.push(score);
-                        },
-                        None => {
-                            // Do nothing.
-                        },
-                    };
-                },
-                Err(error) => panic!("There was an error calculating the score for term {}. The error is: {}", term, error),
-            };
+        let classes = training.get_classes();
+        let class_sizes: Vec<u32> = classes.iter().map(|class| class.get_num_documents()).collect();
+        for term in training.get_vocab() {
+            let document_frequencies: Vec<u32> = classes.iter().map(|class| class.get_document_frequency(&term)).collect();
+            for (class_id, class) in classes.iter().enumerate() {
+                // Terms are scored for the classes whose documents contain them
+                if document_frequencies[class_id] == 0 {
+                    continue;
+                }
+                let score = mutual_information(&class_sizes, &document_frequencies, class_id);
+                if let Some(score) = TermClassScore::new(score, term.clone(), class.get_label().to_string()) {
+                    priority_queue.push(score);
+                }
+            }
         }
-        println!("Time taken to build discriminating vocab: {} seconds. Total number of things in priority_queue: {}", time.elapsed().as_secs(), priority_queue.len());
+        debug!("Time taken to build discriminating vocab: {} seconds. Total number of things in priority_queue: {}", time.elapsed().as_secs(), priority_queue.len());
         let mut discriminating_vocab : Vec<TermClassScore> =  Vec::new();
         let mut counter = 0;
         while let Some(from_priority_queue) = priority_queue.pop() {
@@ -95,388 +201,62 @@ impl<'a> BayesianClassifier<'a> {
 
         discriminating_vocab
     }
-
-    fn get_total_num_documents(&self) -> Result<u32, &'static str> { // Nt (or just N), total number of documents for training set.
-        let hamilton_total_num = self.index_hamilton.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-        let jay_total_num = self.index_jay.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-        let madison_total_num = self.index_madison.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-
-        let mut total_num = 0;
-        total_num += hamilton_total_num;
-        total_num += jay_total_num;
-        total_num += madison_total_num;
-
-        match total_num > 0 {
-            true => Ok(total_num),
-            false => Err("Error: No Documents Found in Index"),
-        }
-    }
-
-    fn get_n_00(&self, term: &str, class: &DocumentClass) -> Result<u32, &'static str> { // N00, total number of documents that DO NOT contain term t and NOT in class c.
-        match *class {
-            DocumentClass::Hamilton => {
-                let jay_total_num = self.index_jay.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-                let madison_total_num = self.index_madison.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-
-                let jay_doc_freq_for_term = self.index_jay.get_document_frequency(term);
-                let madison_doc_freq_for_term = self.index_madison.get_document_frequency(term);
-
-                let jay_num_without_term = jay_total_num - jay_doc_freq_for_term;
-                let madison_num_without_term = madison_total_num - madison_doc_freq_for_term;
-
-                let n_00_hamilton = jay_num_without_term + madison_num_without_term;
-
-                Ok(n_00_hamilton)
-            },
-            DocumentClass::Jay => {
-                let hamilton_total_num = self.index_hamilton.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-                let madison_total_num = self.index_madison.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-
-                let hamilton_doc_freq_for_term = self.index_hamilton.get_document_frequency(term);
-                let madison_doc_freq_for_term = self.index_madison.get_document_frequency(term);
-
-                let hamilton_num_without_term = hamilton_total_num - hamilton_doc_freq_for_term;
-                let madison_num_without_term = madison_total_num - madison_doc_freq_for_term;
-
-                let n_00_jay = hamilton_num_without_term + madison_num_without_term;
-
-                Ok(n_00_jay)
-            },
-            DocumentClass::Madison => {
-                let hamilton_total_num = self.index_hamilton.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-                let jay_total_num = self.index_jay.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-
-                let hamilton_doc_freq_for_term = self.index_hamilton.get_document_frequency(term);
-                let jay_doc_freq_for_term = self.index_jay.get_document_frequency(term);
-
-                let hamilton_num_without_term = hamilton_total_num - hamilton_doc_freq_for_term;
-                let jay_num_without_term = jay_total_num - jay_doc_freq_for_term;
-
-                let n_00_madison = hamilton_num_without_term + jay_num_without_term;
-
-                Ok(n_00_madison)
-            },
-        }
-    }
-
-    fn get_n_01(&self, term: &str, class: &DocumentClass) -> Result<u32, &'static str> { // N01, total number of documents that DO contain term t and NOT in class c.
-        match *class {
-            DocumentClass::Hamilton => {
-                let jay_doc_freq_for_term = self.index_jay.get_document_frequency(term);
-
-                let madison_doc_freq_for_term = self.index_madison.get_document_frequency(term);
-
-                let n_01_hamilton = jay_doc_freq_for_term + madison_doc_freq_for_term;
-
-                Ok(n_01_hamilton)
-            },
-            DocumentClass::Jay => {
-                let hamilton_doc_freq_for_term = self.index_hamilton.get_document_frequency(term);
-
-                let madison_doc_freq_for_term = self.index_madison.get_document_frequency(term);
-
-                let n_01_jay = hamilton_doc_freq_for_term + madison_doc_freq_for_term;
-
-                Ok(n_01_jay)
-            },
-            DocumentClass::Madison => {
-                let hamilton_doc_freq_for_term = self.index_hamilton.get_document_frequency(term);
-
-                let jay_doc_freq_for_term = self.index_jay.get_document_frequency(term);
-
-                let n_01_madison = hamilton_doc_freq_for_term + jay_doc_freq_for_term;
-
-                Ok(n_01_madison)
-            },
-        }
-    }
-
-    fn get_n_10(&self, term: &str, class: &DocumentClass) -> Result<u32, &'static str> { // N10, total number of documents that DO NOT contain term t but IS in class c.
-        match *class {
-            DocumentClass::Hamilton => {
-                let hamilton_total_num = self.index_hamilton.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-
-                let hamilton_doc_freq_for_term = self.index_hamilton.get_document_frequency(term);
-
-                let hamilton_num_without_term = hamilton_total_num - hamilton_doc_freq_for_term; 
-
-                let n_10_hamilton = hamilton_num_without_term;
-
-                Ok(n_10_hamilton)
-            },
-            DocumentClass::Jay => {
-                let jay_total_num = self.index_jay.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-
-                let jay_doc_freq_for_term = self.index_jay.get_document_frequency(term);
-
-                let jay_num_without_term = jay_total_num - jay_doc_freq_for_term;
-
-                let n_10_jay = jay_num_without_term;
-
-                Ok(n_10_jay)
-            },
-            DocumentClass::Madison => {
-                let madison_total_num = self.index_madison.get_num_documents().expect("No Documents found!"); // Nc, total number of documents for class. 
-
-                let madison_doc_freq_for_term = self.index_madison.get_document_frequency(term);
-
-                let madison_num_without_term = madison_total_num - madison_doc_freq_for_term;
-
-                let n_10_madison = madison_num_without_term;
-
-                Ok(n_10_madison)
-            },
-        }
-    }
-
-    fn get_n_11(&self, term: &str, class: &DocumentClass) -> Result<u32, &'static str> { // N11, total number of documents that DO contain term t and IS in class c.
-        match *class {
-            DocumentClass::Hamilton => {
-                let hamilton_doc_freq_for_term = self.index_hamilton.get_document_frequency(term);
-
-                let n_11_hamilton = hamilton_doc_freq_for_term;
-
-                Ok(n_11_hamilton)
-            },
-            DocumentClass::Jay => {
-                let jay_doc_freq_for_term = self.index_jay.get_document_frequency(term);
-
-                let n_11_jay = jay_doc_freq_for_term;
-
-                Ok(n_11_jay)
-            },
-            DocumentClass::Madison => {
-                let madison_doc_freq_for_term = self.index_madison.get_document_frequency(term);
-
-                let n_11_madison = madison_doc_freq_for_term;
-
-                Ok(n_11_madison)
-            },
-        }
-    }
-
-    fn get_n_0X(&self, term: &str, class: &DocumentClass) -> Result<(u32, u32, u32), &'static str> { // N0X, N00 + N01
-        let n_00 = match self.get_n_00(term, class) {
-            Ok(n_00) => n_00,
-            Err(_) => panic!("Something happened when calculating N00!"),
-        };
-        let n_01 = match self.get_n_01(term, class) {
-            Ok(n_01) => n_01,
-            Err(_) => panic!("Something happened when calculating N01!"),
-        };
-
-        let n_0X = n_00 + n_01;
-
-        Ok((n_0X, n_00, n_01))
-    }
-
-    fn get_n_X0(&self, term: &str, class: &DocumentClass) -> Result<(u32, u32, u32), &'static str> { // NX0, N00 + N10
-        let n_00 = match self.get_n_00(term, class) {
-            Ok(n_00) => n_00,
-            Err(_) => panic!("Something happened when calculating N00!"),
-        };
-        let n_10 = match self.get_n_10(term, class) {
-            Ok(n_10) => n_10,
-            Err(_) => panic!("Something happened when calculating N10!"),
-        };
-        
-        let n_X0 = n_00 + n_10;
-
-        Ok((n_X0, n_00, n_10))
-    }
-
-    fn get_n_1X(&self, term: &str, class: &DocumentClass) -> Result<(u32, u32, u32), &'static str> { // N1X, N10 + N11
-        let n_10 = match self.get_n_10(term, class) {
-            Ok(n_10) => n_10,
-            Err(_) => panic!("Something happened when calculating N10!"),
-        };
-        let n_11 = match self.get_n_11(term, class) {
-            Ok(n_11) => n_11,
-            Err(_) => panic!("Something happened when calculating N11!"),
-        };
-
-        let n_1X = n_10 + n_11;
-
-        Ok((n_1X, n_10, n_11))
-    }
-
-    fn get_n_X1(&self, term: &str, class: &DocumentClass) -> Result<(u32, u32, u32), &'static str> { // NX1, N01 + N11
-        let n_01 = match self.get_n_01(term, class) {
-            Ok(n_01) => n_01,
-            Err(_) => panic!("Something happened when calculating N01!"),
-        };
-        let n_11 = match self.get_n_11(term, class) {
-            Ok(n_11) => n_11,
-            Err(_) => panic!("Something happened when calculating N11!"),
-        };
-
-        let n_X1 = n_01 + n_11;
-
-        Ok((n_X1, n_01, n_11))
-    }
-
-    fn calculate_mutual_information_score(&self, term: String, class: DocumentClass) -> Result<f64, &'static str> {
-        let n_hamilton = self.index_hamilton.get_num_documents().expect("No Documents found!"); 
-        let n_jay = self.index_jay.get_num_documents().expect("No Documents found!"); 
-        let n_madison = self.index_madison.get_num_documents().expect("No Documents found!"); 
-        let n = n_hamilton + n_jay + n_madison;
-
-
-        let (n_0X, n_00, n_01) = match self.get_n_0X(&term, &class) {
-            Ok(value) => value,
-            Err(error) => panic!("Something happened when calculating N_0X! Error: {}", error),
-        };
-        let (n_X0, _, n_10) = match self.get_n_X0(&term, &class) {
-            Ok(value) => value,
-            Err(error) => panic!("Something happened when calculating N_X0! Error: {}", error),
-        };
-        let (n_1X, _, n_11) = match self.get_n_1X(&term, &class) {
-            Ok(value) => value,
-            Err(error) => panic!("Something happened when calculating N_1X! Error: {}", error),
-        };
-        let (n_X1, _, _) = match self.get_n_X1(&term, &class) {
-            Ok(value) => value,
-            Err(error) => panic!("Something happened when calculating N_X1! Error: {}", error),
-        };
-
-        let first_term_calculation = (n_11 as f64 / n as f64) * ((n * n_11) as f64 / (n_1X * n_X1) as f64).log2();
-        let second_term_calculation = (n_10 as f64 / n as f64) * ((n * n_10) as f64 / (n_1X * n_X0) as f64).log2();
-        let third_term_calculation = (n_01 as f64 / n as f64) * ((n * n_01) as f64 / (n_0X * n_X1) as f64).log2();
-        let fourth_term_calculation = (n_00 as f64 / n as f64) * ((n * n_00) as f64 / (n_0X * n_X0) as f64).log2();
-
-        let first_term = match first_term_calculation.is_nan() {
-            true => 0.0,
-            false => first_term_calculation,
-        };
-        let second_term = match second_term_calculation.is_nan() {
-            true => 0.0,
-            false => second_term_calculation,
-        };
-        let third_term = match third_term_calculation.is_nan() {
-            true => 0.0,
-            false => third_term_calculation,
-        };
-        let fourth_term = match fourth_term_calculation.is_nan() {
-            true => 0.0,
-            false => fourth_term_calculation,
-        };
-
-        let score = first_term + second_term + third_term + fourth_term;
-
-        Ok(score)
-    }
-
-    fn calculate_probability_term_given_class(&self, doc_id: u32, index_for_class: &DiskInvertedIndex, discriminating_vocab: &Vec<TermClassScore>) -> f64 {
-        let terms_for_doc = self.index_disputed.get_terms_for_document(doc_id);
-        let mut discriminating_vocab_hashset = HashSet::new();
-        for term_class_score in discriminating_vocab {
-            discriminating_vocab_hashset.insert(term_class_score.term.clone());
-        }
-
-        let doc_terms_intersected_discriminating_terms: HashSet<_> = terms_for_doc.intersection(&discriminating_vocab_hashset).collect();
-
-        let discriminating_vocab_len = discriminating_vocab.len() as u32;
-        
-        let mut discriminating_terms_score = 0;
-        let mut probability = 0.0;
-        
-        for discriminating_term in discriminating_vocab {
-            discriminating_terms_score += index_for_class.get_term_frequency(&discriminating_term.term);
-        }
-
-        for term in doc_terms_intersected_discriminating_terms {
-            // println!("Term: {}", term);
-            // println!("Term Freq: {}", index_for_class.get_term_frequency(&term) + 1);
-            probability += ((index_for_class.get_term_frequency(&term) + 1) as f64/(discriminating_terms_score + discriminating_vocab_len) as f64).log2();
-            // println!("Probability from now: {}", probability);
-        }
-
-        // println!("Discriminating Terms Score: {}", discriminating_terms_score);
-        // println!("Probability: {}", probability);
-        probability
-    }
-
 }
 
-impl<'a> Classifier<'a> for BayesianClassifier<'a> {
-    fn classify(&self, doc_id: u32) -> &'a str {
-        let k = 50;
-
-        println!("Using k={}", k);
-
-        let discriminating_vocab = self.build_discriminating_vocab_set(k);
-
-        let discriminating_vocab_len = discriminating_vocab.len() as u32;
-        
-        let num_docs_hamilton = self.index_hamilton.get_num_documents().unwrap();
-        let num_docs_madison = self.index_madison.get_num_documents().unwrap();
-        let num_docs_jay = self.index_jay.get_num_documents().unwrap();
-        
-        let total_num_docs = self.get_total_num_documents().unwrap();
-
-        let p_c_hamilton : f64 = num_docs_hamilton as f64/total_num_docs as f64;
-        let p_c_madison : f64 = num_docs_madison as f64/total_num_docs as f64;
-        let p_c_jay : f64 = num_docs_jay as f64/total_num_docs as f64;
-
-        let mut hamilton_score : f64 = 0.0;
-        let mut madison_score : f64 = 0.0;
-        let mut jay_score : f64 = 0.0;
-
-        let mut p_t_c_hamilton : f64 = 0.0;
-        let mut p_t_c_madison : f64 = 0.0;
-        let mut p_t_c_jay : f64 = 0.0;
-
-        p_t_c_hamilton = self.calculate_probability_term_given_class(doc_id, self.index_hamilton, &discriminating_vocab);
-        p_t_c_madison = self.calculate_probability_term_given_class(doc_id, self.index_madison, &discriminating_vocab);
-        p_t_c_jay = self.calculate_probability_term_given_class(doc_id, self.index_jay, &discriminating_vocab);
-
-        hamilton_score = (p_c_hamilton).log2() + p_t_c_hamilton;
-        madison_score = (p_c_madison).log2() + p_t_c_madison;
-        jay_score = (p_c_jay).log2() + p_t_c_jay;
-
-        println!("Hamilton Classification Score: {}", hamilton_score);
-        println!("Madison Classification Score: {}", madison_score);
-        println!("Jay Classification Score: {}\n", jay_score);
+/*
+ * Calculates the mutual information of a term and a class
+ *
+ * # Arguments
+ *
+ * *`class_sizes` - Number of documents of every class
+ * *`document_frequencies` - Number of documents of every class containing the term
+ * *`class_id` - Position of the class scored
+ *
+ * # Returns
+ *
+ * The mutual information, in bits
+ */
+fn mutual_information(class_sizes: &[u32], document_frequencies: &[u32], class_id: usize) -> f64 {
+    let n: u32 = class_sizes.iter().sum();
+    let n_11 = document_frequencies[class_id]; // Documents that DO contain term t and ARE in class c
+    let n_10 = class_sizes[class_id] - n_11; // Documents that DO NOT contain term t but ARE in class c
+    let n_01 = document_frequencies.iter().sum::<u32>() - n_11; // Documents that DO contain term t and are NOT in class c
+    let n_00 = n - n_11 - n_10 - n_01; // Documents that DO NOT contain term t and are NOT in class c
+
+    let n_1x = n_10 + n_11;
+    let n_x1 = n_01 + n_11;
+    let n_0x = n_00 + n_01;
+    let n_x0 = n_00 + n_10;
+
+    let term = |n_ij: u32, n_i: u32, n_j: u32| {
+        let calculation = (n_ij as f64 / n as f64) * ((n as f64 * n_ij as f64) / (n_i as f64 * n_j as f64)).log2();
+        if calculation.is_nan() { 0.0 } else { calculation }
+    };
+    term(n_11, n_1x, n_x1) + term(n_10, n_1x, n_x0) + term(n_01, n_0x, n_x1) + term(n_00, n_0x, n_x0)
+}
 
-        if hamilton_score > madison_score {
-            if hamilton_score > jay_score {
-                return "Hamilton" ;
-            } else {
-                return "Jay" ;
-            }
-        } else {
-            if madison_score > jay_score {
-                return "Madison" ;
-            }  else {
-                return "Jay";
-            }
+impl Classifier for BayesianClassifier {
+    fn classify(&self, doc_id: u32) -> Classification {
+        // Every discriminating term found in the document counts once
+        let feature_ids: Vec<usize> = self
+            .documents
+            .get_terms_for_document(doc_id)
+            .iter()
+            .filter_map(|term| self.feature_ids.get(term).cloned())
+            .collect();
+
+        let mut scores = Vec::new();
+        for (class, log_prior) in self.model.classes.iter().zip(self.log_priors.iter()) {
+            let p_t_c: f64 = feature_ids.iter().map(|&feature_id| class.log_probabilities[feature_id]).sum();
+            let score = log_prior + p_t_c;
+            debug!("{} Classification Score: {}", class.label, score);
+            scores.push(ClassScore { label: class.label.clone(), score });
         }
+        // The most likely class wins
+        Classification::best_of(scores, false)
     }
-    fn get_all_vocab(&self) -> Vec<String> {
-        let vocabulary_hamilton = self.index_hamilton.get_vocab();
-        let vocabulary_jay = self.index_jay.get_vocab();
-        let vocabulary_madison = self.index_madison.get_vocab();
 
-        let first_union: HashSet<_> = vocabulary_hamilton.union(&vocabulary_jay).collect();
-        let mut first_union_final: HashSet<String> = HashSet::new();
-        for vocab in first_union {
-            first_union_final.insert(vocab.clone());
-        }
-
-        let second_union: HashSet<_> = first_union_final.union(&vocabulary_madison).collect();
-        let mut final_union: HashSet<String> = HashSet::new();
-        for vocab in second_union {
-            final_union.insert(vocab.clone());
-        }
-
-        let mut vocab_list : Vec<String> = Vec::new();
-
-        for vocab in final_union {
-            vocab_list.push(vocab); 
-        }
-        vocab_list.sort();
-        
-        vocab_list
+    fn get_all_vocab(&self) -> Vec<String> {
+        self.model.vocab.clone()
     }
-
 }
@@ -3,29 +3,87 @@ use std::cmp::Ordering;
 use std::ops::Add;
 use std::ops::Div;
 
-pub trait Classifier<'a> {
-    fn classify(&self, doc_id: u32) -> &'a str;
+pub trait Classifier {
+    /*
+     * Classifies a document of the index being classified
+     *
+     * # Arguments
+     *
+     * *`doc_id` - The document
+     *
+     * # Returns
+     *
+     * The label of the class chosen for the document and the score of every class
+     */
+    fn classify(&self, doc_id: u32) -> Classification;
     fn get_all_vocab(&self) -> Vec<String>;
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
-pub enum DocumentClass {
-    Hamilton,
-    Jay,
-    Madison,
+/*
+ * The score a classifier gives a document for one class. What the score means depends on the
+ * classifier: the Rocchio classifier gives the distance to the centroid of the class, so the
+ * closest class wins, the Bayesian classifier gives the log probability of the class, so the
+ * most likely class wins, and the kNN classifier gives the votes of the nearest neighbours, so the
+ * class with the most votes wins
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClassScore {
+    pub label: String,
+    pub score: f64,
+}
+
+/*
+ * The class a classifier chose for a document and the score of every class
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Classification {
+    pub label: String,
+    pub scores: Vec<ClassScore>,
+}
+
+impl Classification {
+    /*
+     * Chooses the class with the best score, the class listed first on ties
+     *
+     * # Arguments
+     *
+     * *`scores` - The score of every class
+     * *`lowest_wins` - Whether the lowest score is the best, as for distances
+     *
+     * # Returns
+     *
+     * The classification, with an empty label if no classes were scored
+     */
+    pub fn best_of(scores: Vec<ClassScore>, lowest_wins: bool) -> Classification {
+        let mut best: Option<&ClassScore> = None;
+        for score in scores.iter() {
+            let better = match best {
+                Some(best) if lowest_wins => score.score < best.score,
+                Some(best) => score.score > best.score,
+                None => true,
+            };
+            if better {
+                best = Some(score);
+            }
+        }
+        Classification {
+            label: best.map(|best| best.label.clone()).unwrap_or_default(),
+            scores,
+        }
+    }
 }
 
-#[derive(Debug, PartialOrd)]
+#[derive(Debug, Clone, PartialOrd, Serialize, Deserialize)]
 pub struct TermClassScore {
     pub score: f64,
     pub term: String,
-    pub class: DocumentClass,
+    pub class: String,
 }
 
 impl TermClassScore {
-    pub fn new(score: f64, term: String, class: DocumentClass) -> Option<TermClassScore> {
+    pub fn new(score: f64, term: String, class: String) -> Option<TermClassScore> {
         if score.is_nan() {
-            println!("Looks like there was a NaN! Term is: {}. Class is: {:?}.", term, class);
+            warn!("Looks like there was a NaN! Term is: {}. Class is: {}.", term, class);
             None
         }
         else {
@@ -74,7 +132,7 @@ impl Scalar {
 }
 
 
-#[derive(Debug, Clone,  PartialOrd)]
+#[derive(Debug, Clone,  PartialOrd, Serialize)]
 pub struct TermComponentScore {
     pub score: f64,
     pub term: String,
@@ -83,7 +141,7 @@ pub struct TermComponentScore {
 impl TermComponentScore {
     pub fn new(score: f64, term: String) -> Option<TermComponentScore> {
         if score.is_nan() {
-            println!("Looks like there was a NaN! Term is: {}", term);
+            warn!("Looks like there was a NaN! Term is: {}", term);
             None
         }
         else {
@@ -0,0 +1,346 @@
+use index::disk_inverted_index::DiskInvertedIndex;
+use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Instant;
+use classifier::classifier::ClassScore;
+use classifier::classifier::Classification;
+use classifier::classifier::Classifier;
+use classifier::rocchio_classifier::normalized_document_components;
+use classifier::training_set::TrainingSet;
+
+/*
+ * Number of neighbours a kNN classifier is trained with by default
+ */
+pub const DEFAULT_NEIGHBOURS: u32 = 5;
+
+/*
+ * How the nearest training documents vote for the class of a document
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Voting {
+    /*
+     * Every neighbour counts once
+     */
+    Majority,
+
+    /*
+     * Every neighbour counts its cosine similarity to the document, so closer neighbours weigh
+     * more
+     */
+    Weighted,
+}
+
+impl FromStr for Voting {
+    type Err = &'static str;
+
+    fn from_str(name: &str) -> Result<Voting, &'static str> {
+        match name {
+            "majority" => Ok(Voting::Majority),
+            "weighted" => Ok(Voting::Weighted),
+            _ => Err("Unknown voting, use majority or weighted"),
+        }
+    }
+}
+
+/*
+ * The normalized vectors of the training documents of a kNN classifier, which can be saved and
+ * loaded again instead of reading the training documents every time
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnnModel {
+    k: u32,
+    voting: Voting,
+    labels: Vec<String>,
+
+    /*
+     * The sorted terms of every class, which the components of the vectors refer to by position
+     */
+    vocab: Vec<String>,
+    documents: Vec<TrainingVector>,
+
+    /*
+     * The directories of the indexes the training documents were read from
+     */
+    #[serde(default)]
+    indexes: Vec<PathBuf>,
+}
+
+/*
+ * The non-zero components of the normalized vector of a training document, the position of its
+ * class and where the document was read from
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TrainingVector {
+    class: usize,
+    components: Vec<(u32, f64)>,
+
+    /*
+     * The position of the document's index in KnnModel::indexes and its id in that index
+     */
+    #[serde(default)]
+    index: usize,
+    #[serde(default)]
+    doc_id: u32,
+}
+
+impl KnnModel {
+    pub fn get_labels(&self) -> Vec<&str> {
+        self.labels.iter().map(|label| label.as_str()).collect()
+    }
+
+    pub fn get_k(&self) -> u32 {
+        self.k
+    }
+
+    pub fn get_voting(&self) -> Voting {
+        self.voting
+    }
+
+    /*
+     * Changes how many neighbours vote and how, which needs no training again
+     *
+     * # Arguments
+     *
+     * *`k` - Number of nearest training documents voting, at least 1
+     * *`voting` - How the neighbours vote
+     */
+    pub fn set_neighbours(&mut self, k: u32, voting: Voting) {
+        self.k = k.max(1);
+        self.voting = voting;
+    }
+}
+
+/*
+ * k-Nearest-Neighbours classifier: the k training documents whose normalized vectors have the
+ * highest cosine similarity with the document vote for its class
+ */
+pub struct KnnClassifier {
+    documents: DiskInvertedIndex,
+    model: KnnModel,
+
+    /*
+     * The position of every term of the vocabulary, and the length of every training vector
+     */
+    term_ids: HashMap<String, u32>,
+    lengths: Vec<f64>,
+
+    /*
+     * The id of every training document in the index being classified, if it was read from it
+     */
+    own_doc_ids: Vec<Option<u32>>,
+}
+
+impl KnnClassifier {
+    /*
+     * Constructs a kNN classifier, reading the vectors of the training documents
+     *
+     * # Arguments
+     *
+     * *`documents` - The Disk Inverted Index holding the documents to classify
+     * *`training` - The labelled classes documents are classified into
+     * *`k` - Number of nearest training documents voting
+     * *`voting` - How the neighbours vote
+     */
+    pub fn new(documents: &DiskInvertedIndex, training: &TrainingSet, k: u32, voting: Voting) -> KnnClassifier {
+        KnnClassifier::from_model(documents, KnnClassifier::fit(training, k, voting))
+    }
+
+    /*
+     * Constructs a kNN classifier from a trained model
+     *
+     * # Arguments
+     *
+     * *`documents` - The Disk Inverted Index holding the documents to classify
+     * *`model` - The vectors of the training documents
+     */
+    pub fn from_model(documents: &DiskInvertedIndex, model: KnnModel) -> KnnClassifier {
+        let term_ids = model.vocab.iter().enumerate().map(|(term_id, term)| (term.clone(), term_id as u32)).collect();
+        let lengths = model.documents.iter().map(|document| vector_length(&document.components)).collect();
+        let own_indexes: Vec<bool> = model.indexes.iter().map(|index| same_directory(index, documents.get_directory())).collect();
+        let own_doc_ids = model
+            .documents
+            .iter()
+            .map(|document| match own_indexes.get(document.index) {
+                Some(true) => Some(document.doc_id),
+                _ => None,
+            })
+            .collect();
+        KnnClassifier {
+            documents: documents.clone(),
+            model,
+            term_ids,
+            lengths,
+            own_doc_ids,
+        }
+    }
+
+    /*
+     * Trains a kNN classifier by computing the normalized vector of every training document
+     *
+     * # Arguments
+     *
+     * *`training` - The labelled classes documents are classified into
+     * *`k` - Number of nearest training documents voting, at least 1
+     * *`voting` - How the neighbours vote
+     *
+     * # Returns
+     *
+     * The vectors of the training documents, class by class
+     */
+    pub fn fit(training: &TrainingSet, k: u32, voting: Voting) -> KnnModel {
+        let time = Instant::now();
+        let vocab = training.get_vocab();
+        let term_ids: HashMap<&str, u32> = vocab.iter().enumerate().map(|(term_id, term)| (term.as_str(), term_id as u32)).collect();
+        let mut documents = Vec::new();
+        let mut indexes: Vec<PathBuf> = Vec::new();
+        for (class_id, class) in training.get_classes().iter().enumerate() {
+            let directory = class.get_index().get_directory();
+            let index = match indexes.iter().position(|index| index == directory) {
+                Some(index) => index,
+                None => {
+                    indexes.push(directory.to_path_buf());
+                    indexes.len() - 1
+                },
+            };
+            for doc_id in class.get_doc_ids() {
+                let mut components: Vec<(u32, f64)> = normalized_document_components(doc_id, class.get_index())
+                    .into_iter()
+                    .filter_map(|(term, score)| term_ids.get(term.as_str()).map(|&term_id| (term_id, score)))
+                    .collect();
+                components.sort_by_key(|component| component.0);
+                documents.push(TrainingVector { class: class_id, components, index, doc_id });
+            }
+        }
+        debug!("Time taken to compute the training vectors: {} seconds", time.elapsed().as_secs());
+        KnnModel {
+            k: k.max(1),
+            voting,
+            labels: training.get_labels().iter().map(|label| label.to_string()).collect(),
+            vocab,
+            documents,
+            indexes,
+        }
+    }
+
+    pub fn get_model(&self) -> &KnnModel {
+        &self.model
+    }
+
+    /*
+     * Classifies a document with other neighbours than those of the model, so one trained
+     * classifier answers for any number of neighbours and voting
+     *
+     * # Arguments
+     *
+     * *`doc_id` - The document of the index being classified
+     * *`k` - Number of nearest training documents voting, at least 1
+     * *`voting` - How the neighbours vote
+     *
+     * # Returns
+     *
+     * The label of the class chosen for the document and the votes of every class
+     */
+    pub fn classify_with(&self, doc_id: u32, k: u32, voting: Voting) -> Classification {
+        let neighbours = self.nearest_neighbours(doc_id, k.max(1));
+        let mut votes = vec![0.0; self.model.labels.len()];
+        for &(position, similarity) in neighbours.iter() {
+            let class = self.model.documents[position].class;
+            debug!("Neighbour {} of {} with similarity {}", position, self.model.labels[class], similarity);
+            votes[class] += match voting {
+                Voting::Majority => 1.0,
+                Voting::Weighted => similarity,
+            };
+        }
+
+        // Ties between the classes with the most votes go to the class of the nearest neighbour
+        let most_votes = votes.iter().cloned().fold(0.0, f64::max);
+        let label = neighbours
+            .iter()
+            .map(|&(position, _)| self.model.documents[position].class)
+            .find(|&class| votes[class] == most_votes)
+            .map(|class| self.model.labels[class].clone())
+            .unwrap_or_default();
+        let scores = self
+            .model
+            .labels
+            .iter()
+            .zip(votes)
+            .map(|(label, score)| ClassScore { label: label.clone(), score })
+            .collect();
+        Classification { label, scores }
+    }
+
+    /*
+     * Finds the training documents most similar to a document
+     *
+     * # Arguments
+     *
+     * *`doc_id` - The document of the index being classified
+     * *`k` - Largest number of training documents returned
+     *
+     * # Returns
+     *
+     * Up to k positions of training documents sharing a term with the document, with their cosine
+     * similarity, most similar first and in training order on ties. A labelled document of the
+     * classified index is not its own neighbour
+     */
+    fn nearest_neighbours(&self, doc_id: u32, k: u32) -> Vec<(usize, f64)> {
+        // Terms outside the vocabulary of the classes never match a training document, but still
+        // count towards the length of the document vector
+        let components = normalized_document_components(doc_id, &self.documents);
+        let length = vector_length(&components);
+        let scores: HashMap<u32, f64> = components
+            .iter()
+            .filter_map(|&(ref term, score)| self.term_ids.get(term).map(|&term_id| (term_id, score)))
+            .collect();
+
+        let mut similarities: Vec<(usize, f64)> = Vec::new();
+        for (position, (training_document, training_length)) in self.model.documents.iter().zip(self.lengths.iter()).enumerate() {
+            if self.own_doc_ids[position] == Some(doc_id) {
+                continue;
+            }
+            let dot_product: f64 = training_document
+                .components
+                .iter()
+                .filter_map(|&(term_id, score)| scores.get(&term_id).map(|document_score| score * document_score))
+                .sum();
+            let similarity = if length > 0.0 && *training_length > 0.0 { dot_product / (length * training_length) } else { 0.0 };
+            if similarity > 0.0 {
+                similarities.push((position, similarity));
+            }
+        }
+        // The sort is stable, so equally similar documents keep their training order
+        similarities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+        similarities.truncate(k as usize);
+        similarities
+    }
+}
+
+/*
+ * Whether two paths name the same directory, resolving links and relative paths when they exist
+ */
+fn same_directory(first: &Path, second: &Path) -> bool {
+    match (fs::canonicalize(first), fs::canonicalize(second)) {
+        (Ok(first), Ok(second)) => first == second,
+        _ => first == second,
+    }
+}
+
+fn vector_length<T>(components: &[(T, f64)]) -> f64 {
+    components.iter().map(|component| component.1 * component.1).sum::<f64>().sqrt()
+}
+
+impl Classifier for KnnClassifier {
+    fn classify(&self, doc_id: u32) -> Classification {
+        self.classify_with(doc_id, self.model.k, self.model.voting)
+    }
+
+    fn get_all_vocab(&self) -> Vec<String> {
+        self.model.vocab.clone()
+    }
+}
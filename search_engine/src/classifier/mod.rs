@@ -1,3 +1,6 @@
 pub mod classifier;
 pub mod rocchio_classifier;
 pub mod bayesian_classifier;
+pub mod knn_classifier;
+pub mod training_set;
+pub mod model;
@@ -0,0 +1,131 @@
+extern crate serde_json;
+
+use classifier::bayesian_classifier::BayesianClassifier;
+use classifier::bayesian_classifier::BayesianModel;
+use classifier::bayesian_classifier::DISCRIMINATING_TERMS;
+use classifier::classifier::Classifier;
+use classifier::knn_classifier::KnnClassifier;
+use classifier::knn_classifier::KnnModel;
+use classifier::knn_classifier::Voting;
+use classifier::knn_classifier::DEFAULT_NEIGHBOURS;
+use classifier::rocchio_classifier::RocchioClassifier;
+use classifier::rocchio_classifier::RocchioModel;
+use classifier::training_set::TrainingSet;
+use index::disk_inverted_index::DiskInvertedIndex;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/*
+ * Version of the format of saved models, written in every model file. Models saved in another
+ * format are rejected instead of being misread, and must be trained again
+ */
+pub const MODEL_FORMAT_VERSION: u64 = 1;
+
+/*
+ * A trained classifier, which can be saved to a file and loaded again so the training set is only
+ * read once. The file is JSON tagged with the method of the classifier and the format version
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "lowercase")]
+pub enum ClassifierModel {
+    Rocchio(RocchioModel),
+    Bayes(BayesianModel),
+    Knn(KnnModel),
+}
+
+impl ClassifierModel {
+    /*
+     * Trains a classifier. A kNN classifier is trained with 5 neighbours and majority voting,
+     * which set_neighbours of its model changes
+     *
+     * # Arguments
+     *
+     * *`method` - The classifier trained, rocchio, bayes or knn
+     * *`training` - The labelled classes documents are classified into
+     *
+     * # Returns
+     *
+     * The trained model, or an error if the method is unknown
+     */
+    pub fn fit(method: &str, training: &TrainingSet) -> Result<ClassifierModel, &'static str> {
+        match method {
+            "rocchio" => Ok(ClassifierModel::Rocchio(RocchioClassifier::fit(training))),
+            "bayes" | "bayesian" => Ok(ClassifierModel::Bayes(BayesianClassifier::fit(training, DISCRIMINATING_TERMS))),
+            "knn" => Ok(ClassifierModel::Knn(KnnClassifier::fit(training, DEFAULT_NEIGHBOURS, Voting::Majority))),
+            _ => Err("Unknown method, use rocchio, bayes or knn"),
+        }
+    }
+
+    /*
+     * Reads a model saved by save
+     *
+     * # Arguments
+     *
+     * *`path` - The model file
+     *
+     * # Returns
+     *
+     * The model, or an error if the file cannot be read, does not hold a model or holds a model
+     * saved in another format version
+     */
+    pub fn load(path: &Path) -> Result<ClassifierModel, &'static str> {
+        let contents = fs::read_to_string(path).map_err(|_| "Failed to read the model file")?;
+        let mut serialized: Value = serde_json::from_str(&contents).map_err(|_| "The model file does not hold a classifier model")?;
+        let object = serialized.as_object_mut().ok_or("The model file does not hold a classifier model")?;
+        if object.remove("version").and_then(|version| version.as_u64()) != Some(MODEL_FORMAT_VERSION) {
+            return Err("The model was saved by another version of the search engine and must be trained again");
+        }
+        serde_json::from_value(serialized).map_err(|_| "The model file does not hold a classifier model")
+    }
+
+    /*
+     * Writes the model to a file, replacing it if it exists
+     *
+     * # Arguments
+     *
+     * *`path` - The model file
+     */
+    pub fn save(&self, path: &Path) -> Result<(), &'static str> {
+        let mut serialized = serde_json::to_value(self).map_err(|_| "Failed to serialize the model")?;
+        serialized["version"] = json!(MODEL_FORMAT_VERSION);
+        fs::write(path, serialized.to_string()).map_err(|_| "Failed to write the model file")
+    }
+
+    /*
+     * The method of the classifier, rocchio, bayes or knn
+     */
+    pub fn get_method(&self) -> &'static str {
+        match *self {
+            ClassifierModel::Rocchio(_) => "rocchio",
+            ClassifierModel::Bayes(_) => "bayes",
+            ClassifierModel::Knn(_) => "knn",
+        }
+    }
+
+    /*
+     * The labels of the classes, in the order of the scores of a classification
+     */
+    pub fn get_labels(&self) -> Vec<&str> {
+        match *self {
+            ClassifierModel::Rocchio(ref model) => model.get_labels(),
+            ClassifierModel::Bayes(ref model) => model.get_labels(),
+            ClassifierModel::Knn(ref model) => model.get_labels(),
+        }
+    }
+
+    /*
+     * Constructs the classifier of the model
+     *
+     * # Arguments
+     *
+     * *`documents` - The Disk Inverted Index holding the documents to classify
+     */
+    pub fn classifier(self, documents: &DiskInvertedIndex) -> Box<dyn Classifier + Send + Sync> {
+        match self {
+            ClassifierModel::Rocchio(model) => Box::new(RocchioClassifier::from_model(documents, model)),
+            ClassifierModel::Bayes(model) => Box::new(BayesianClassifier::from_model(documents, model)),
+            ClassifierModel::Knn(model) => Box::new(KnnClassifier::from_model(documents, model)),
+        }
+    }
+}
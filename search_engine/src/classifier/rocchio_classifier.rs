@@ -3,291 +3,285 @@ extern crate serde_json;
 
 use index::disk_inverted_index::DiskInvertedIndex;
 use index::disk_inverted_index::IndexReader;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
-use std::collections::HashSet;
-use std::fs::File;
-use std::io::prelude::*;
+use classifier::classifier::ClassScore;
+use classifier::classifier::Classification;
 use classifier::classifier::Classifier;
-use classifier::classifier::DocumentClass;
 use classifier::classifier::Scalar;
 use classifier::classifier::TermComponentScore;
+use classifier::training_set::TrainingClass;
+use classifier::training_set::TrainingSet;
+use std::time::Instant;
+
+/*
+ * The centroids of the classes a Rocchio classifier was trained on, which can be saved and loaded
+ * again instead of averaging the training documents every time
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RocchioModel {
+    /*
+     * The sorted terms of every class, making up the dimensions of the centroids
+     */
+    vocab: Vec<String>,
+    centroids: Vec<Centroid>,
+}
 
-pub struct RocchioClassifier<'a> {
-    index_disputed: &'a DiskInvertedIndex<'a>,
-    index_hamilton: &'a DiskInvertedIndex<'a>,
-    index_jay: &'a DiskInvertedIndex<'a>,
-    index_madison: &'a DiskInvertedIndex<'a>,
+/*
+ * The average of the normalized vectors of the training documents of a class, one component per
+ * term of the vocabulary
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Centroid {
+    label: String,
+    components: Vec<f64>,
 }
 
-impl<'a> RocchioClassifier<'a> {
-    pub fn new(index_disputed: &'a DiskInvertedIndex, index_hamilton: &'a DiskInvertedIndex, index_jay: &'a DiskInvertedIndex, index_madison: &'a DiskInvertedIndex) -> RocchioClassifier<'a> {
-        RocchioClassifier {
-            index_disputed: index_disputed,
-            index_hamilton: index_hamilton,
-            index_jay: index_jay,
-            index_madison: index_madison,
-        }
+impl RocchioModel {
+    pub fn get_labels(&self) -> Vec<&str> {
+        self.centroids.iter().map(|centroid| centroid.label.as_str()).collect()
     }
+}
 
-    fn calculate_centroid_for_document(&self, index: &DiskInvertedIndex) -> Vec<TermComponentScore> {
-        let doc_ids = self.retrieve_doc_ids(index);
-        let number_of_documents_in_class = doc_ids.len();
-        let mut sum_of_docs = self.calculate_normalized_vector_for_document(*doc_ids.get(0).expect("Error retrieving doc ID"), index);
-        
-        for i in 1..(number_of_documents_in_class - 1) {
-            let doc = *doc_ids.get(i).expect("Error Retrieving doc ID");
-            sum_of_docs = add_vector_components(self.calculate_normalized_vector_for_document(doc, index), sum_of_docs);
-        }
-        // println!("Sum of Docs: {:?}", sum_of_docs);
-        let num_docs_in_class_scalar : Scalar = Scalar::new(number_of_documents_in_class as f64);
-        sum_of_docs / num_docs_in_class_scalar
-    }
-
-    fn calculate_normalized_vector_for_document(&self,doc_id: u32, index: &DiskInvertedIndex) -> Vec<TermComponentScore> {
-        let document_weight = index.get_document_weights(doc_id).unwrap().1;
-
-        // Used for getting terms for disputed index only
-        // let vocab_set = self.index_disputed.get_vocab();
-        
-        // Used for getting terms in entire corpus
-        let vocab_set = self.get_all_vocab();
-
-        let mut vocab_list : Vec<String> =  Vec::new();
-
-        for vocab in vocab_set {
-            vocab_list.push(vocab);
-        }
-
-        vocab_list.sort();
-
-        let mut document_vector: Vec<TermComponentScore> = Vec::new();
-        for term in vocab_list {
-            let res = index.get_postings_no_positions(&term);
-            let term_exists = res.is_ok();
-            if !term_exists {
-                let new_tcs = TermComponentScore::new(0f64, term).expect("Error creating TermComponentScore");
-                document_vector.push(new_tcs);
-                continue;
-            }
-            let posting = res.unwrap();
-            for (id, _, term_score, _, _, _) in posting {
-                if id == doc_id {
-                    let new_tcs = TermComponentScore::new((term_score)/(document_weight), term).expect("Error creating TermComponentScore");
-                    document_vector.push(new_tcs);
-                    break;
-                }
-            }
-        }
+/*
+ * Classifies documents into the class whose centroid, the average of the normalized vectors of
+ * its training documents, is the closest
+ */
+pub struct RocchioClassifier {
+    documents: DiskInvertedIndex,
+    model: RocchioModel,
+
+    /*
+     * The position of every term of the vocabulary, and the squared length of every centroid
+     */
+    term_ids: HashMap<String, usize>,
+    squared_lengths: Vec<f64>,
+}
 
-        return document_vector;
+impl RocchioClassifier {
+    /*
+     * Constructs a Rocchio classifier, computing the centroids of the training set
+     *
+     * # Arguments
+     *
+     * *`documents` - The Disk Inverted Index holding the documents to classify
+     * *`training` - The labelled classes documents are classified into
+     */
+    pub fn new(documents: &DiskInvertedIndex, training: &TrainingSet) -> RocchioClassifier {
+        RocchioClassifier::from_model(documents, RocchioClassifier::fit(training))
     }
 
-    fn calculate_centroid_for_index(&self, index: &DiskInvertedIndex) -> Vec<TermComponentScore> {
-        let doc_ids = self.retrieve_doc_ids(index);
-        let number_of_documents_in_class = doc_ids.len();
-        let mut sum_of_docs = self.calculate_normalized_vector_for_index(*doc_ids.get(0).expect("Error retrieving doc ID"), index);
-        
-        for i in 1..(number_of_documents_in_class - 1) {
-            let doc = *doc_ids.get(i).expect("Error Retrieving doc ID");
-            sum_of_docs = add_vector_components(self.calculate_normalized_vector_for_index(doc, index), sum_of_docs);
+    /*
+     * Constructs a Rocchio classifier from a trained model
+     *
+     * # Arguments
+     *
+     * *`documents` - The Disk Inverted Index holding the documents to classify
+     * *`model` - The centroids documents are compared to
+     */
+    pub fn from_model(documents: &DiskInvertedIndex, model: RocchioModel) -> RocchioClassifier {
+        let term_ids = model.vocab.iter().enumerate().map(|(term_id, term)| (term.clone(), term_id)).collect();
+        let squared_lengths = model
+            .centroids
+            .iter()
+            .map(|centroid| centroid.components.iter().map(|component| component * component).sum())
+            .collect();
+        RocchioClassifier {
+            documents: documents.clone(),
+            model,
+            term_ids,
+            squared_lengths,
         }
-        // println!("Sum of Docs: {:?}", sum_of_docs);
-        let num_docs_in_class_scalar : Scalar = Scalar::new(number_of_documents_in_class as f64);
-        sum_of_docs / num_docs_in_class_scalar
     }
 
-    fn calculate_normalized_vector_for_index(&self,doc_id: u32, index: &DiskInvertedIndex) -> Vec<TermComponentScore> {
-        let document_weight = index.get_document_weights(doc_id).unwrap().1;
-
-        let vocab_set = index.get_vocab();
-        let mut vocab_list : Vec<String> =  Vec::new();
-
-        for vocab in vocab_set {
-            vocab_list.push(vocab);
-        }
-
-        vocab_list.sort();
-
-        let mut document_vector: Vec<TermComponentScore> = Vec::new();
-        for term in vocab_list {
-            let res = index.get_postings_no_positions(&term);
-            let term_exists = res.is_ok();
-            if !term_exists {
-                let new_tcs = TermComponentScore::new(0f64, term).expect("Error creating TermComponentScore");
-                document_vector.push(new_tcs);
-                continue;
-            }
-            let posting = res.unwrap();
-            for (id, _, term_score, _, _, _) in posting {
-                if id == doc_id {
-                    let new_tcs = TermComponentScore::new((term_score)/(document_weight), term).expect("Error creating TermComponentScore");
-                    document_vector.push(new_tcs);
-                    break;
-                }
-            }
-        }
-
-        return document_vector;
+    /*
+     * Trains a Rocchio classifier by averaging the normalized vectors of the documents of every
+     * class
+     *
+     * # Arguments
+     *
+     * *`training` - The labelled classes documents are classified into
+     *
+     * # Returns
+     *
+     * The centroid of every class, in the order of the classes
+     */
+    pub fn fit(training: &TrainingSet) -> RocchioModel {
+        let time = Instant::now();
+        let vocab = training.get_vocab();
+        let term_ids: HashMap<&str, usize> = vocab.iter().enumerate().map(|(term_id, term)| (term.as_str(), term_id)).collect();
+        let centroids = training
+            .get_classes()
+            .iter()
+            .map(|class| Centroid {
+                label: class.get_label().to_string(),
+                components: calculate_centroid(class, &term_ids),
+            })
+            .collect();
+        debug!("Time taken to compute the centroids: {} seconds", time.elapsed().as_secs());
+        RocchioModel { vocab, centroids }
     }
 
-    fn retrieve_id_file(&self, index: &DiskInvertedIndex) -> HashMap<u32, String> {
-        //read and deserialize the id file for an index
-        let id_file_filename = format!("{}/{}", index.get_path(), "id_file.bin");
-        let mut id_file_file = File::open(id_file_filename).unwrap();
-        let mut id_file_contents = String::new();
-        id_file_file
-            .read_to_string(&mut id_file_contents)
-            .expect("Failed to read id file");
-        let id_file: HashMap<u32, String> = serde_json::from_str(&id_file_contents).unwrap();
-        return id_file;
+    pub fn get_model(&self) -> &RocchioModel {
+        &self.model
     }
 
-    fn retrieve_doc_ids(&self, index: &DiskInvertedIndex) -> Vec<u32> {
-        let id_file = self.retrieve_id_file(index);
-        let mut ids: Vec<u32> = Vec::new();
-        //unsure if i retrieved the right ids needed for the index
-        for key in id_file.keys() {
-            ids.push(*key);
-        }
-        return ids;
-    }
-
-    pub fn get_hamilton_centroid(&self) -> Vec<TermComponentScore> {
-        let mut term_centroid : Vec<TermComponentScore> = Vec::new();
-        let components = self.calculate_centroid_for_index(self.index_hamilton);
-
-        let components_clone = components.clone();
-        for (i, component) in components_clone.iter().enumerate() {
-            if i == 30 {
-                break; 
-            }
-            println!("{}. Term: {}, Score: {}", i + 1, component.term, component.score); 
-        }
-        components
-
-    }
-
-    pub fn get_madison_centroid(&self) -> Vec<TermComponentScore> {
-        let components = self.calculate_centroid_for_index(self.index_madison);
-        let components_clone = components.clone();
-        for (i, component) in components_clone.iter().enumerate() {
-            if i == 30 {
-                break; 
-            }
-            println!("{}. Term: {}, Score: {}", i + 1, component.term, component.score); 
-        }
-        components
+    /*
+     * The centroid of every class over the vocabulary of every class, in the order of the classes
+     */
+    pub fn get_centroids(&self) -> Vec<(String, Vec<TermComponentScore>)> {
+        self.model
+            .centroids
+            .iter()
+            .map(|centroid| {
+                let components = self
+                    .model
+                    .vocab
+                    .iter()
+                    .zip(centroid.components.iter())
+                    .map(|(term, &score)| TermComponentScore { score, term: term.clone() })
+                    .collect();
+                (centroid.label.clone(), components)
+            })
+            .collect()
     }
+}
 
-    pub fn get_jay_centroid(&self) -> Vec<TermComponentScore> {
-        let components = self.calculate_centroid_for_index(self.index_jay);
-        let components_clone = components.clone();
-        for (i, component) in components_clone.iter().enumerate() {
-            if i == 30 {
-                break; 
+/*
+ * Averages the normalized vectors of the documents of a class
+ *
+ * # Arguments
+ *
+ * *`class` - The class
+ * *`term_ids` - The position of every term making up the dimensions of the vectors
+ *
+ * # Returns
+ *
+ * One component per term
+ */
+fn calculate_centroid(class: &TrainingClass, term_ids: &HashMap<&str, usize>) -> Vec<f64> {
+    let doc_ids = class.get_doc_ids();
+    let mut sum_of_docs = vec![0.0; term_ids.len()];
+    for &doc_id in doc_ids.iter() {
+        for (term, score) in normalized_document_components(doc_id, class.get_index()) {
+            if let Some(&term_id) = term_ids.get(term.as_str()) {
+                sum_of_docs[term_id] += score;
             }
-            println!("{}. Term: {}, Score: {}", i + 1, component.term, component.score); 
         }
-        components
     }
+    let num_documents = doc_ids.len().max(1) as f64;
+    sum_of_docs.into_iter().map(|sum| sum / num_documents).collect()
 }
 
-fn add_vector_components(vec_1: Vec<TermComponentScore>, vec_2: Vec<TermComponentScore>) -> Vec<TermComponentScore> {
-
-    let mut res = Vec::new();
-    // println!("Vec1: {:?}", vec_1);
-    // println!("Vec2: {:?}", vec_2);
-    for (x,y) in vec_1.iter().zip(vec_2.iter()) {
-        let x_copy = x.clone();
-        let y_copy = y.clone();
-
-        let answer = x_copy + y_copy;
-        
-        res.push(answer);
-    }
-
-    return res;
+/*
+ * Builds the non-zero components of the length normalized vector of a document from the forward
+ * index
+ *
+ * # Arguments
+ *
+ * *`doc_id` - The document
+ * *`index` - The Disk Inverted Index containing the document
+ *
+ * # Returns
+ *
+ * The terms of the document, each with (1 + ln(tf)) / document weight
+ */
+pub fn normalized_document_components(doc_id: u32, index: &DiskInvertedIndex) -> Vec<(String, f64)> {
+    let document_weight = index.get_document_weights(doc_id).unwrap().1;
+    index
+        .get_document_vector(doc_id)
+        .expect("Error retrieving document vector")
+        .into_iter()
+        .map(|(term_id, term_frequency)| {
+            let term = index.get_term(term_id).expect("Error retrieving term");
+            (term, (1.0 + (term_frequency as f64).ln()) / document_weight)
+        })
+        .collect()
 }
 
-fn calculate_euclidian_distance(vec_1: &Vec<TermComponentScore>, vec_2: &Vec<TermComponentScore>) ->f64 {
-    let mut distance = 0f64;
-    for (x,y) in vec_1.iter().zip(vec_2.iter()) {
-       distance += (y.score - x.score).powi(2);
-    }
-    return distance.sqrt();
+/*
+ * Builds the length normalized vector of a document from the forward index
+ *
+ * # Arguments
+ *
+ * *`doc_id` - The document
+ * *`index` - The Disk Inverted Index containing the document
+ * *`vocab_list` - The sorted terms making up the dimensions of the vector
+ *
+ * # Returns
+ *
+ * One component per term of vocab_list: (1 + ln(tf)) / document weight, or 0 when the document
+ * does not contain the term
+ */
+fn normalized_document_vector(doc_id: u32, index: &DiskInvertedIndex, vocab_list: Vec<String>) -> Vec<TermComponentScore> {
+    let term_scores: HashMap<String, f64> = normalized_document_components(doc_id, index).into_iter().collect();
+    vocab_list
+        .into_iter()
+        .map(|term| {
+            let score = term_scores.get(&term).cloned().unwrap_or(0.0);
+            TermComponentScore::new(score, term).expect("Error creating TermComponentScore")
+        })
+        .collect()
 }
 
-impl<'a> Classifier<'a> for RocchioClassifier<'a> {
-    fn classify(&self, doc_id: u32) -> &'a str {
-        
-        let hamilton_centroid = self.calculate_centroid_for_document(self.index_hamilton);
-        let jay_centroid = self.calculate_centroid_for_document(self.index_jay);
-        let madison_centroid = self.calculate_centroid_for_document(self.index_madison);
-
-        // println!("Hamilton Centroid: {:?}\n", hamilton_centroid);
-        // println!("Jay Centroid: {:?}\n", jay_centroid);
-        // println!("Madison Centroid: {:?}\n", madison_centroid);
-
-        let components = self.calculate_normalized_vector_for_document(doc_id, self.index_disputed);
-
-        let components_clone = components.clone();
-        for (i, component) in components_clone.iter().enumerate() {
-            if i == 30 {
-                break; 
-            }
-            println!("{}. Term: {}, Score: {}", i + 1, component.term, component.score); 
+/*
+ * Computes the centroid of the normalized vectors of a set of documents, keeping only the terms
+ * that occur in at least one of them
+ *
+ * # Arguments
+ *
+ * *`doc_ids` - The documents averaged
+ * *`index` - The Disk Inverted Index holding the documents
+ *
+ * # Returns
+ *
+ * The non-zero components of the centroid, sorted by term, or an empty vector if no documents are
+ * given
+ */
+pub fn document_centroid(doc_ids: &[u32], index: &DiskInvertedIndex) -> Vec<TermComponentScore> {
+    let mut components: BTreeMap<String, TermComponentScore> = BTreeMap::new();
+    for &doc_id in doc_ids {
+        let vocab_list: Vec<String> = index.get_terms_for_document(doc_id).into_iter().collect();
+        for component in normalized_document_vector(doc_id, index, vocab_list) {
+            let sum = match components.remove(&component.term) {
+                Some(sum) => sum + component,
+                None => component,
+            };
+            components.insert(sum.term.clone(), sum);
         }
-
-        // println!("Normalized Vector {:?}\n", components);
-
-        let distance_disputed_hamilton = calculate_euclidian_distance(&components,&hamilton_centroid);
-        let distance_disputed_jay = calculate_euclidian_distance(&components,&jay_centroid);
-        let distance_disputed_madison = calculate_euclidian_distance(&components,&madison_centroid);
-
-        // println!("Hamilton Euclidian Distance: {:?}\n", distance_disputed_hamilton);
-        // println!("Jay Euclidian Distance: {:?}\n", distance_disputed_jay);
-        // println!("Madison Euclidian Distance: {:?}\n", distance_disputed_madison);
-
-        let min = distance_disputed_hamilton.min(distance_disputed_jay.min(distance_disputed_madison));
-        if min == distance_disputed_hamilton {
-            return "Hamilton";
-        } else if min == distance_disputed_jay {
-            return "Jay";
-        } else if min == distance_disputed_madison {
-            return "Madison";
-        } else {
-            return "Error";
-        }
-        
     }
-    fn get_all_vocab(&self) -> Vec<String> {
-
-        let vocabulary_hamilton = self.index_hamilton.get_vocab();
-        let vocabulary_jay = self.index_jay.get_vocab();
-        let vocabulary_madison = self.index_madison.get_vocab();
+    if doc_ids.is_empty() {
+        return Vec::new();
+    }
+    components.into_values().collect::<Vec<TermComponentScore>>() / Scalar::new(doc_ids.len() as f64)
+}
 
-        let first_union: HashSet<_> = vocabulary_hamilton.union(&vocabulary_madison).collect();
-        let mut first_union_final: HashSet<String> = HashSet::new();
-        for vocab in first_union {
-            first_union_final.insert(vocab.clone());
+impl Classifier for RocchioClassifier {
+    fn classify(&self, doc_id: u32) -> Classification {
+        let components = normalized_document_components(doc_id, &self.documents);
+        for (i, component) in components.iter().enumerate().take(30) {
+            debug!("{}. Term: {}, Score: {}", i + 1, component.0, component.1);
         }
 
-        let second_union: HashSet<_> = first_union_final.union(&vocabulary_jay).collect();
-        let mut final_union: HashSet<String> = HashSet::new();
-        for vocab in second_union {
-            final_union.insert(vocab.clone());
+        let mut scores = Vec::new();
+        for (centroid, squared_length) in self.model.centroids.iter().zip(self.squared_lengths.iter()) {
+            // |d - c|² = |c|² + the sum of d² - 2dc over the terms of the document, as the centroid
+            // is the only vector with components elsewhere. Terms outside the vocabulary are ignored
+            let mut squared_distance = *squared_length;
+            for &(ref term, score) in components.iter() {
+                if let Some(&term_id) = self.term_ids.get(term) {
+                    squared_distance += score * score - 2.0 * score * centroid.components[term_id];
+                }
+            }
+            let distance = squared_distance.max(0.0).sqrt();
+            debug!("{} Euclidian Distance: {:?}", centroid.label, distance);
+            scores.push(ClassScore { label: centroid.label.clone(), score: distance });
         }
+        // The closest centroid wins
+        Classification::best_of(scores, true)
+    }
 
-        let mut vocab_list : Vec<String> = Vec::new();
-
-        for vocab in final_union {
-            vocab_list.push(vocab); 
-        }
-        vocab_list.sort();
-        
-        vocab_list
+    fn get_all_vocab(&self) -> Vec<String> {
+        self.model.vocab.clone()
     }
 }
-    
-
-
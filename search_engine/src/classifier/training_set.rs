@@ -0,0 +1,269 @@
+use index::disk_inverted_index::DiskInvertedIndex;
+use index::disk_inverted_index::IndexReader;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/*
+ * A labelled class of training documents: every document of an index, or the documents of an
+ * index a label file gives the label
+ */
+#[derive(Clone)]
+pub struct TrainingClass {
+    label: String,
+    index: DiskInvertedIndex,
+
+    /*
+     * The documents of the class in increasing order, or None when the whole index belongs to it
+     */
+    doc_ids: Option<Vec<u32>>,
+}
+
+impl TrainingClass {
+    /*
+     * Constructs a class holding every document of an index
+     *
+     * # Arguments
+     *
+     * *`label` - The label documents of the class are given
+     * *`index` - The Disk Inverted Index of the class
+     */
+    pub fn new(label: &str, index: &DiskInvertedIndex) -> TrainingClass {
+        TrainingClass {
+            label: label.to_string(),
+            index: index.clone(),
+            doc_ids: None,
+        }
+    }
+
+    /*
+     * Constructs a class holding some of the documents of an index
+     *
+     * # Arguments
+     *
+     * *`label` - The label documents of the class are given
+     * *`index` - The Disk Inverted Index holding the documents
+     * *`doc_ids` - The documents of the class
+     */
+    pub fn with_documents(label: &str, index: &DiskInvertedIndex, mut doc_ids: Vec<u32>) -> TrainingClass {
+        doc_ids.sort();
+        doc_ids.dedup();
+        TrainingClass {
+            label: label.to_string(),
+            index: index.clone(),
+            doc_ids: Some(doc_ids),
+        }
+    }
+
+    pub fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn get_index(&self) -> &DiskInvertedIndex {
+        &self.index
+    }
+
+    /*
+     * Whether the class holds every document of its index
+     */
+    pub fn is_whole_index(&self) -> bool {
+        self.doc_ids.is_none()
+    }
+
+    /*
+     * The documents of the class, in increasing order
+     */
+    pub fn get_doc_ids(&self) -> Vec<u32> {
+        match self.doc_ids {
+            Some(ref doc_ids) => doc_ids.clone(),
+            None => (0..self.index.get_num_documents().unwrap_or(0)).collect(),
+        }
+    }
+
+    /*
+     * Number of documents in the class (Nc)
+     */
+    pub fn get_num_documents(&self) -> u32 {
+        match self.doc_ids {
+            Some(ref doc_ids) => doc_ids.len() as u32,
+            None => self.index.get_num_documents().unwrap_or(0),
+        }
+    }
+
+    /*
+     * Number of documents of the class containing a term
+     */
+    pub fn get_document_frequency(&self, term: &str) -> u32 {
+        match self.doc_ids {
+            Some(ref doc_ids) => self.class_postings(term, doc_ids).len() as u32,
+            None => self.index.get_document_frequency(term),
+        }
+    }
+
+    /*
+     * Number of occurrences of a term in the documents of the class
+     */
+    pub fn get_term_frequency(&self, term: &str) -> u32 {
+        match self.doc_ids {
+            Some(ref doc_ids) => self.class_postings(term, doc_ids).iter().map(|posting| posting.1).sum(),
+            None => self.index.get_term_frequency(term),
+        }
+    }
+
+    /*
+     * The terms found in the documents of the class
+     */
+    pub fn get_vocab(&self) -> HashSet<String> {
+        match self.doc_ids {
+            Some(ref doc_ids) => doc_ids.iter().flat_map(|&doc_id| self.index.get_terms_for_document(doc_id)).collect(),
+            None => self.index.get_vocab(),
+        }
+    }
+
+    /*
+     * The document ids and term frequencies of the postings of a term within the class
+     */
+    fn class_postings(&self, term: &str, doc_ids: &[u32]) -> Vec<(u32, u32)> {
+        match self.index.get_postings_no_positions(term) {
+            Ok(postings) => postings
+                .into_iter()
+                .filter(|posting| doc_ids.binary_search(&posting.0).is_ok())
+                .map(|posting| (posting.0, posting.1))
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/*
+ * The labelled classes a classifier is trained on. Documents are classified into one of the
+ * classes, in the order they are listed
+ */
+#[derive(Clone)]
+pub struct TrainingSet {
+    classes: Vec<TrainingClass>,
+}
+
+impl TrainingSet {
+    pub fn new(classes: Vec<TrainingClass>) -> TrainingSet {
+        TrainingSet { classes }
+    }
+
+    /*
+     * Discovers a class in every indexed subdirectory of a directory, labelled by the name of the
+     * subdirectory
+     *
+     * # Arguments
+     *
+     * *`directory` - The directory whose subdirectories are the classes
+     * *`excluded` - A subdirectory that is not a class, such as the one holding the documents to
+     * classify
+     *
+     * # Returns
+     *
+     * The classes sorted by label, or an error if the directory cannot be read or holds fewer than
+     * two indexed subdirectories
+     */
+    pub fn from_subdirectories(directory: &Path, excluded: Option<&Path>) -> Result<TrainingSet, &'static str> {
+        let excluded = excluded.and_then(|excluded| fs::canonicalize(excluded).ok());
+        let mut classes: BTreeMap<String, DiskInvertedIndex> = BTreeMap::new();
+        for entry in fs::read_dir(directory).map_err(|_| "Failed to read the training directory")?.flatten() {
+            let path = entry.path();
+            if excluded.is_some() && fs::canonicalize(&path).ok() == excluded {
+                continue;
+            }
+            let label = match path.file_name().and_then(|name| name.to_str()) {
+                Some(label) => label.to_string(),
+                None => continue,
+            };
+            // Subdirectories that have not been indexed are not classes
+            if let Ok(index) = DiskInvertedIndex::open(&path) {
+                classes.insert(label, index);
+            }
+        }
+        if classes.len() < 2 {
+            return Err("At least two indexed subdirectories are needed as classes");
+        }
+        Ok(TrainingSet {
+            classes: classes.iter().map(|(label, index)| TrainingClass::new(label, index)).collect(),
+        })
+    }
+
+    /*
+     * Reads the classes of the documents of an index from a label file. Every line holds a file
+     * name or path of the index followed by its label, separated by whitespace; blank lines and
+     * lines starting with '#' are skipped
+     *
+     * # Arguments
+     *
+     * *`index` - The Disk Inverted Index holding the labelled documents
+     * *`labels` - The label file
+     *
+     * # Returns
+     *
+     * The classes sorted by label, or an error if the file cannot be read, names a document missing
+     * from the index or labels documents of fewer than two classes
+     */
+    pub fn from_label_file(index: &DiskInvertedIndex, labels: &Path) -> Result<TrainingSet, &'static str> {
+        let contents = fs::read_to_string(labels).map_err(|_| "Failed to read the label file")?;
+        let mut classes: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (file, label) = match line.rfind(char::is_whitespace) {
+                Some(split) => (line[..split].trim(), line[split..].trim()),
+                None => return Err("Every line of the label file needs a file and a label"),
+            };
+            let doc_id = index.get_document_id(file).ok_or("The label file names a document missing from the index")?;
+            classes.entry(label.to_string()).or_default().push(doc_id);
+        }
+        if classes.len() < 2 {
+            return Err("At least two labels are needed as classes");
+        }
+        Ok(TrainingSet {
+            classes: classes
+                .into_iter()
+                .map(|(label, doc_ids)| TrainingClass::with_documents(&label, index, doc_ids))
+                .collect(),
+        })
+    }
+
+    pub fn get_classes(&self) -> &[TrainingClass] {
+        &self.classes
+    }
+
+    pub fn get_labels(&self) -> Vec<&str> {
+        self.classes.iter().map(|class| class.get_label()).collect()
+    }
+
+    /*
+     * Number of documents of every class (N)
+     */
+    pub fn get_num_documents(&self) -> u32 {
+        self.classes.iter().map(|class| class.get_num_documents()).sum()
+    }
+
+    /*
+     * The terms found in the documents of every class, sorted
+     */
+    pub fn get_vocab(&self) -> Vec<String> {
+        let mut vocabulary: HashSet<String> = HashSet::new();
+        for class in self.classes.iter() {
+            vocabulary.extend(class.get_vocab());
+        }
+        let mut vocab_list: Vec<String> = vocabulary.into_iter().collect();
+        vocab_list.sort();
+        vocab_list
+    }
+
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+}
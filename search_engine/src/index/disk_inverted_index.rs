@@ -1,21 +1,104 @@
-extern crate serde_json;
 use byteorder::{ReadBytesExt, BigEndian};
 use std::fs::File;
 use std::io::BufReader;
 use std::io::prelude::*;
 use std::io::SeekFrom;
 use std::collections::HashSet;
-use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic;
+use std::sync::atomic::AtomicU64;
 use std::cmp::Ordering;
 use index::variable_byte;
+use index::document_store;
+use index::document_store::StoredDocument;
+use index::doc_table::DocTable;
+use index::posting_bounds::BlockBounds;
+use index::posting_bounds::PostingBounds;
+use index::positional_reader::PositionalReader;
 use reader::read_file::read_n;
 
-pub struct DiskInvertedIndex<'a> {
-    path: &'a str,
+/*
+ * Number of u64 values stored for every term in vocab_table.bin: the position of the term in
+ * vocab.bin, the position of its postings in postings.bin and its collection frequency
+ */
+pub const VOCAB_TABLE_STRIDE: usize = 3;
+
+/*
+ * Bytes statistics.bin starts with, followed by the u32 version of the index format. Indexes built
+ * before the format was versioned start with their number of documents instead
+ */
+pub const INDEX_MAGIC: [u8; 4] = *b"SEIX";
+
+/*
+ * Version of the layout of the index files, raised whenever a file changes in a way older readers
+ * or writers would misread. Version 1 flags character offsets in the low bit of every tf_td, keeps
+ * the postings position of every block in bounds.bin and counts document lengths in tokens
+ */
+pub const INDEX_FORMAT_VERSION: u32 = 1;
+
+/*
+ * A posting read from postings.bin: document ID, tf_td, regular term score, tf_idf term score,
+ * okapi term score, wacky term score and positions
+ */
+pub type Posting = (u32, u32, f64, f64, f64, f64, Vec<u32>);
+
+/*
+ * Start and end character offsets in the document body of a term at each of its positions
+ */
+pub type TokenOffsets = Vec<(u32, u32)>;
+
+/*
+ * Files every index directory holds, checked before an index is opened. The K Gram Index
+ * (kgram.bin) is optional
+ */
+const INDEX_FILES: [&str; 12] = [
+    "vocab.bin",
+    "vocab_table.bin",
+    "doc_weights.bin",
+    "postings.bin",
+    "statistics.bin",
+    "forward_index.bin",
+    "forward_table.bin",
+    "bounds.bin",
+    "bounds_table.bin",
+    "documents.bin",
+    "documents_table.bin",
+    "doc_id.bin",
+];
+
+/*
+ * Handle to the index of a directory. Cloning the handle is cheap and every clone reads the same
+ * open files and tables, so a handle can be stored in structs, moved into threads or kept in an
+ * IndexSet for as long as needed
+ */
+#[derive(Clone)]
+pub struct DiskInvertedIndex {
+    path: PathBuf,
+    files: Arc<IndexFiles>,
+}
+
+/*
+ * The open files and the tables read from them when an index is opened
+ */
+struct IndexFiles {
     vocab_list: File,
     doc_weights: File,
-    pub postings: File,
+    postings: File,
     vocab_table: Vec<u64>,
+    number_of_documents: u32,
+    total_term_frequency: u64,
+    document_frequencies: Vec<u32>,
+    unique_term_counts: Vec<u64>,
+    forward_index: File,
+    forward_table: Vec<u64>,
+    bounds: File,
+    bounds_table: Vec<u64>,
+    documents: File,
+    documents_table: Vec<u64>,
+    doc_table: DocTable,
+    decoded_postings: AtomicU64,
 }
 
 pub trait IndexReader {
@@ -31,35 +114,230 @@ pub trait IndexReader {
     fn get_document_frequency(&self, term: &str) -> u32;
     fn get_terms_for_document(&self, doc_id: u32) -> HashSet<String>;
     fn get_term_frequency(&self, term: &str) -> u32;
-    fn get_total_term_frequency(&self) -> u32;
+    fn get_total_term_frequency(&self) -> u64;
     fn binary_search_vocabulary(&self, term: &str) -> i64;
     fn read_vocab_table(index_name: &str) -> Vec<u64>;
     fn get_term_count(&self) -> u32;
     fn get_num_documents(&self) -> Result<u32, &'static str>;
+    fn get_unique_term_count(&self, doc_id: u32) -> u32;
+    fn get_document_vector(&self, doc_id: u32) -> Result<Vec<(u32, u32)>, &'static str>; // Term ID, tf_td
+    fn get_term(&self, term_id: u32) -> Result<String, &'static str>;
+    fn get_term_id(&self, term: &str) -> Option<u32>; // Position of the term in the vocabulary, as used by the forward index
+    fn get_posting_bounds(&self, term: &str) -> Result<(PostingBounds, BlockBounds), &'static str>; // Bounds of the whole list, (last document id, postings position, bounds) of every block
+    fn get_postings_with_offsets(&self, term: &str) -> Result<Vec<(Posting, Option<TokenOffsets>)>, &'static str>; // Postings with the character offsets of every position, if they were recorded
+    fn get_document(&self, doc_id: u32) -> Result<StoredDocument, &'static str>; // Title, url, body and stored fields kept in the document store
+    fn get_document_path(&self, doc_id: u32) -> Result<String, &'static str>; // Path of the document's file, from the doc table
+    fn get_document_id(&self, path: &str) -> Option<u32>; // Document ID of a file, given its path or file name
 }
 
-impl<'a> DiskInvertedIndex<'a> {
-    pub fn new(path: &'a str) -> DiskInvertedIndex {
+impl DiskInvertedIndex {
+    /*
+     * Opens the index of a directory
+     *
+     * # Arguments
+     *
+     * *`path` - The indexed directory
+     *
+     * # Panics
+     *
+     * If a file of the index is missing or cannot be read, or the index has another format version,
+     * see `open`
+     */
+    pub fn new<P: AsRef<Path>>(path: P) -> DiskInvertedIndex {
+        let path = path.as_ref().to_path_buf();
+        let directory = path.display().to_string();
+        let path_str = directory.as_str();
+        let (number_of_documents, total_term_frequency, document_frequencies, unique_term_counts) =
+            DiskInvertedIndex::read_statistics(path_str);
+        let files = IndexFiles {
+            vocab_list: File::open(format!("{}/{}", path_str, "vocab.bin")).expect("Failed to open vocab.bin"),
+            doc_weights: File::open(format!("{}/{}", path_str, "doc_weights.bin")).expect("Failed to open doc_weights.bin"),
+            postings: File::open(format!("{}/{}", path_str, "postings.bin")).expect("Failed to open postings.bin"),
+            vocab_table: DiskInvertedIndex::read_vocab_table(path_str),
+            number_of_documents,
+            total_term_frequency,
+            document_frequencies,
+            unique_term_counts,
+            forward_index: File::open(format!("{}/{}", path_str, "forward_index.bin")).expect("Failed to open forward_index.bin"),
+            forward_table: DiskInvertedIndex::read_position_table(path_str, "forward_table.bin"),
+            bounds: File::open(format!("{}/{}", path_str, "bounds.bin")).expect("Failed to open bounds.bin"),
+            bounds_table: DiskInvertedIndex::read_position_table(path_str, "bounds_table.bin"),
+            documents: File::open(format!("{}/{}", path_str, "documents.bin")).expect("Failed to open documents.bin"),
+            documents_table: DiskInvertedIndex::read_position_table(path_str, "documents_table.bin"),
+            doc_table: DocTable::open(path_str).expect("Failed to open doc_id.bin"),
+            decoded_postings: AtomicU64::new(0),
+        };
         DiskInvertedIndex {
-            path: path.clone(),
-            vocab_list: File::open(format!("{}/{}", path, "vocab.bin")).expect("Failed to open vocab.bin"),
-            doc_weights: File::open(format!("{}/{}", path, "doc_weights.bin")).expect("Failed to open doc_weights.bin"),
-            postings: File::open(format!("{}/{}", path, "postings.bin")).expect("Failed to open postings.bin"),
-            vocab_table: DiskInvertedIndex::read_vocab_table(path),
+            path,
+            files: Arc::new(files),
         }
     }
-}
 
-impl<'a> IndexReader for DiskInvertedIndex<'a> {
-    fn read_postings_from_file(&self, mut postings: &File, postings_position: i64) -> Vec<(u32, u32, f64, f64, f64, f64, Vec<u32>)> {
-        let mut results: Vec<(u32, u32, f64, f64, f64, f64, Vec<u32>)> = Vec::new();
-        postings.seek(SeekFrom::Start(postings_position as u64)).unwrap();
+    /*
+     * Opens the index of a directory after checking it has been built
+     *
+     * # Arguments
+     *
+     * *`path` - The indexed directory
+     *
+     * # Returns
+     *
+     * The index, or an error if a file of the index is missing
+     */
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<DiskInvertedIndex, &'static str> {
+        let path = path.as_ref();
+        if !path.is_dir() {
+            return Err("Index directory does not exist");
+        }
+        if INDEX_FILES.iter().any(|file| !path.join(file).is_file()) {
+            return Err("Directory has not been indexed");
+        }
+        if DiskInvertedIndex::read_format_version(path) != Some(INDEX_FORMAT_VERSION) {
+            return Err("Index was built by another version of the search engine and must be rebuilt");
+        }
+        Ok(DiskInvertedIndex::new(path))
+    }
+
+    /*
+     * The indexed directory
+     */
+    pub fn get_directory(&self) -> &Path {
+        &self.path
+    }
+
+    /*
+     * Number of postings decoded from postings.bin since the index was opened, by every clone of
+     * the handle
+     */
+    pub fn get_decoded_postings(&self) -> u64 {
+        self.files.decoded_postings.load(atomic::Ordering::Relaxed)
+    }
+
+    /*
+     * Decodes one block of a term's postings without their positions
+     *
+     * # Arguments
+     *
+     * *`position` - Position of the block's first posting in postings.bin
+     * *`previous_doc_id` - Document ID of the posting before the block, or 0 for the first block
+     * *`count` - Number of postings in the block
+     *
+     * # Returns
+     *
+     * The document ID and tf_td of every posting of the block
+     */
+    pub fn read_postings_block(&self, position: u64, previous_doc_id: u32, count: u32) -> Vec<(u32, u32)> {
+        let postings = PositionalReader::new(&self.files.postings, position);
+        let block = DiskInvertedIndex::decode_postings_no_positions(postings, previous_doc_id, count);
+        self.count_decoded_postings(block.len());
+        block.into_iter().map(|posting| (posting.0, posting.1)).collect()
+    }
+
+    fn count_decoded_postings(&self, count: usize) {
+        self.files.decoded_postings.fetch_add(count as u64, atomic::Ordering::Relaxed);
+    }
+
+    /*
+     * Reads the format version of the index of a directory from the start of statistics.bin
+     *
+     * # Returns
+     *
+     * The version, or None if the index predates versioning or statistics.bin cannot be read
+     */
+    fn read_format_version(path: &Path) -> Option<u32> {
+        let mut statistics = File::open(path.join("statistics.bin")).ok()?;
+        let mut magic = [0; 4];
+        statistics.read_exact(&mut magic).ok()?;
+        if magic != INDEX_MAGIC {
+            return None;
+        }
+        statistics.read_u32::<BigEndian>().ok()
+    }
+
+    /*
+     * Reads a table of u64 positions, such as forward_table.bin or bounds_table.bin
+     */
+    fn read_position_table(path: &str, file_name: &str) -> Vec<u64> {
+        let table_file = File::open(format!("{}/{}", path, file_name)).expect("Failed to open position table");
+        let mut table = BufReader::new(table_file);
+        let mut forward_table = Vec::new();
+        while let Ok(position) = table.read_u64::<BigEndian>() {
+            forward_table.push(position);
+        }
+        forward_table
+    }
+
+    /*
+     * Reads the bytes between two positions of a file, or up to the end of the file
+     */
+    fn read_range(file: &File, start: u64, end: Option<u64>) -> Vec<u8> {
+        let end = end.unwrap_or_else(|| file.metadata().unwrap().len());
+        let mut buffer = vec![0; (end - start) as usize];
+        PositionalReader::new(file, start).read_exact(&mut buffer).expect("Error reading from file");
+        buffer
+    }
+
+    /*
+     * Reads the collection statistics written by the Index Writer to statistics.bin
+     *
+     * # Arguments
+     *
+     * *`path` - The directory of the index
+     *
+     * # Returns
+     *
+     * The number of documents, the total number of tokens, the document frequency of every term in
+     * vocabulary order and the number of distinct terms of every document
+     */
+    fn read_statistics(path: &str) -> (u32, u64, Vec<u32>, Vec<u64>) {
+        let statistics_file = File::open(format!("{}/{}", path, "statistics.bin")).expect("Failed to open statistics.bin");
+        let mut statistics = BufReader::new(statistics_file);
+        let mut magic = [0; 4];
+        statistics.read_exact(&mut magic).expect("Error reading from file");
+        let version = statistics.read_u32::<BigEndian>().expect("Error reading from file");
+        if magic != INDEX_MAGIC || version != INDEX_FORMAT_VERSION {
+            panic!("Index was built by another version of the search engine and must be rebuilt");
+        }
+        let number_of_documents = statistics.read_u32::<BigEndian>().expect("Error reading from file");
+        let total_term_frequency = statistics.read_u64::<BigEndian>().expect("Error reading from file");
+        let term_count = statistics.read_u32::<BigEndian>().expect("Error reading from file");
+
+        let mut document_frequencies = Vec::with_capacity(term_count as usize);
+        for _ in 0..term_count {
+            statistics.read_u64::<BigEndian>().expect("Error reading from file"); // Collection frequency, also kept in the vocab table
+            document_frequencies.push(statistics.read_u32::<BigEndian>().expect("Error reading from file"));
+        }
+
+        let mut unique_term_counts = Vec::with_capacity(number_of_documents as usize);
+        for _ in 0..number_of_documents {
+            unique_term_counts.push(statistics.read_u64::<BigEndian>().expect("Error reading from file"));
+        }
+        (number_of_documents, total_term_frequency, document_frequencies, unique_term_counts)
+    }
+
+    /*
+     * Reads the postings of a term, with the character offsets of every posting that recorded them
+     *
+     * # Arguments
+     *
+     * *`postings` - The postings file
+     * *`postings_position` - Position of the term's postings in the file
+     *
+     * # Returns
+     *
+     * The postings, and for every posting its start and end character offsets in position order
+     * or None
+     */
+    fn read_positional_postings(postings: &File, postings_position: i64) -> (Vec<Posting>, Vec<Option<TokenOffsets>>) {
+        let mut results: Vec<Posting> = Vec::new();
+        let mut offsets: Vec<Option<TokenOffsets>> = Vec::new();
+        let mut postings = PositionalReader::new(postings, postings_position as u64);
         let mut doc_freq_buffer = [0; 4]; // Four bytes of 0.
         postings.read_exact(&mut doc_freq_buffer).unwrap();
         let document_frequency = (&doc_freq_buffer[..]).read_u32::<BigEndian>().unwrap();
         let mut doc_id = 0;
         for _ in 0..document_frequency {
-            let (doc_id_vbe, doc_id_offset) = variable_byte::decode(postings).unwrap();
+            let (doc_id_vbe, doc_id_offset) = variable_byte::decode(&mut postings).unwrap();
             postings.seek(SeekFrom::Current(-(5 - doc_id_offset as i64)));
 
             doc_id += doc_id_vbe;
@@ -80,35 +358,58 @@ impl<'a> IndexReader for DiskInvertedIndex<'a> {
             postings.read_exact(&mut wacky_term_score_buffer).expect("Error reading buffer");
             let wacky_term_score = (&wacky_term_score_buffer[..]).read_f64::<BigEndian>().unwrap();
 
-            let (term_frequency_vbe, term_freq_offset) = variable_byte::decode(postings).unwrap();
+            let (term_frequency_vbe, term_freq_offset) = variable_byte::decode(&mut postings).unwrap();
             postings.seek(SeekFrom::Current(-(5 - term_freq_offset as i64)));
+            let has_offsets = term_frequency_vbe & 1 == 1;
+            let term_frequency = term_frequency_vbe >> 1;
 
             let mut postings_accumulator = 0;
             let mut positions = Vec::new();
-            for j in 0..term_frequency_vbe {
-                let (postings_pos_vbe, postings_pos_offset) = variable_byte::decode(postings).unwrap();
+            let mut start = 0;
+            let mut token_offsets = Vec::new();
+            for _ in 0..term_frequency {
+                let (postings_pos_vbe, postings_pos_offset) = variable_byte::decode(&mut postings).unwrap();
                 postings.seek(SeekFrom::Current(-(5 - postings_pos_offset as i64)));
 
                 postings_accumulator += postings_pos_vbe;
 
                 positions.push(postings_accumulator);
+
+                if has_offsets {
+                    let (start_gap, start_gap_offset) = variable_byte::decode(&mut postings).unwrap();
+                    postings.seek(SeekFrom::Current(-(5 - start_gap_offset as i64))).expect("Failed to seek in file");
+                    let (length, length_offset) = variable_byte::decode(&mut postings).unwrap();
+                    postings.seek(SeekFrom::Current(-(5 - length_offset as i64))).expect("Failed to seek in file");
+                    start += start_gap;
+                    token_offsets.push((start, start + length));
+                }
             }
-            
-            results.push((doc_id, term_frequency_vbe, term_score, tf_idf_term_score, okapi_term_score, wacky_term_score, positions));
+
+            results.push((doc_id, term_frequency, term_score, tf_idf_term_score, okapi_term_score, wacky_term_score, positions));
+            offsets.push(if has_offsets { Some(token_offsets) } else { None });
         }
-        results 
+        (results, offsets)
     }
 
-    fn read_postings_from_file_no_positions(&self, mut postings: &File, postings_position: i64) -> Vec<(u32, u32, f64, f64, f64, f64)> {
-        let mut results: Vec<(u32, u32, f64, f64, f64, f64)> = Vec::new();
-        postings.seek(SeekFrom::Start(postings_position as u64)).unwrap();
-        let mut doc_freq_buffer = [0; 4]; // Four bytes of 0.
-        postings.read_exact(&mut doc_freq_buffer).unwrap();
-        let document_frequency = (&doc_freq_buffer[..]).read_u32::<BigEndian>().unwrap();
-        let mut doc_id = 0;
-        for _ in 0..document_frequency {
-            let (doc_id_vbe, doc_id_offset) = variable_byte::decode(postings).unwrap();
-            postings.seek(SeekFrom::Current(-(5 - doc_id_offset as i64)));
+    /*
+     * Decodes consecutive postings of a term, skipping their positions and character offsets
+     *
+     * # Arguments
+     *
+     * *`postings` - Reader positioned at the first posting to decode
+     * *`previous_doc_id` - Document ID the first posting's gap is relative to
+     * *`count` - Number of postings to decode
+     *
+     * # Returns
+     *
+     * The document ID, tf_td and term scores of every decoded posting
+     */
+    fn decode_postings_no_positions(mut postings: PositionalReader, previous_doc_id: u32, count: u32) -> Vec<(u32, u32, f64, f64, f64, f64)> {
+        let mut results: Vec<(u32, u32, f64, f64, f64, f64)> = Vec::with_capacity(count as usize);
+        let mut doc_id = previous_doc_id;
+        for _ in 0..count {
+            let (doc_id_vbe, doc_id_offset) = variable_byte::decode(&mut postings).unwrap();
+            postings.seek(SeekFrom::Current(-(5 - doc_id_offset as i64))).expect("Failed to seek in file");
 
             doc_id += doc_id_vbe;
 
@@ -128,21 +429,79 @@ impl<'a> IndexReader for DiskInvertedIndex<'a> {
             postings.read_exact(&mut wacky_term_score_buffer).expect("Error reading buffer");
             let wacky_term_score = (&wacky_term_score_buffer[..]).read_f64::<BigEndian>().unwrap();
 
-            let (term_frequency_vbe, term_freq_offset) = variable_byte::decode(postings).unwrap();
-            postings.seek(SeekFrom::Current(-(5 - term_freq_offset as i64)));
+            let (term_frequency_vbe, term_freq_offset) = variable_byte::decode(&mut postings).unwrap();
+            postings.seek(SeekFrom::Current(-(5 - term_freq_offset as i64))).expect("Failed to seek in file");
+            let term_frequency = term_frequency_vbe >> 1;
 
-            results.push((doc_id, term_frequency_vbe, term_score, tf_idf_term_score, okapi_term_score, wacky_term_score));
-            
-            for _ in 0..term_frequency_vbe {
-                let (postings_pos_vbe, postings_pos_offset) = variable_byte::decode(postings).unwrap();
-                postings.seek(SeekFrom::Current(-(5 - postings_pos_offset as i64)));
+            results.push((doc_id, term_frequency, term_score, tf_idf_term_score, okapi_term_score, wacky_term_score));
+
+            // Every position is followed by two more numbers when character offsets were recorded
+            let numbers_per_position = if term_frequency_vbe & 1 == 1 { 3 } else { 1 };
+            for _ in 0..term_frequency * numbers_per_position {
+                let (_postings_pos_vbe, postings_pos_offset) = variable_byte::decode(&mut postings).unwrap();
+                postings.seek(SeekFrom::Current(-(5 - postings_pos_offset as i64))).expect("Failed to seek in file");
             }
         }
-        results 
+        results
+    }
+
+    /*
+     * Binary searches the vocabulary for a term
+     *
+     * # Arguments
+     *
+     * *`term` - The term to search for
+     *
+     * # Returns
+     *
+     * The index of the term in the vocab table, or None if the term is not in the vocabulary
+     */
+    fn search_vocabulary(&self, term: &str) -> Option<usize> {
+        let vocab_list = &self.files.vocab_list;
+        let term_count = (self.files.vocab_table.len() / VOCAB_TABLE_STRIDE) as i64;
+        let mut i : i64  = 0;
+        let mut j : i64  = term_count - 1;
+        while i <= j {
+            let m : i64 =  (i + j) / 2;
+            let vocab_list_position = self.files.vocab_table[m as usize * VOCAB_TABLE_STRIDE];
+            let term_length = if m == term_count - 1 {
+                vocab_list.metadata().unwrap().len() - vocab_list_position
+            } else {
+                self.files.vocab_table[(m as usize + 1) * VOCAB_TABLE_STRIDE] - vocab_list_position
+            };
+
+            let mut buffer = vec![0; term_length as usize];
+            PositionalReader::new(vocab_list, vocab_list_position).read_exact(&mut buffer).expect("Error reading from file");
+
+            let file_term = String::from_utf8(buffer).expect("Error getting string from buffer");
+
+            match term.cmp(&file_term) {
+                Ordering::Equal => return Some(m as usize),
+                Ordering::Less => j = m - 1,
+                Ordering::Greater => i = m + 1
+            }
+        }
+        None
+    }
+}
+
+impl IndexReader for DiskInvertedIndex {
+    fn read_postings_from_file(&self, postings: &File, postings_position: i64) -> Vec<(u32, u32, f64, f64, f64, f64, Vec<u32>)> {
+        DiskInvertedIndex::read_positional_postings(postings, postings_position).0
+    }
+
+    fn read_postings_from_file_no_positions(&self, postings: &File, postings_position: i64) -> Vec<(u32, u32, f64, f64, f64, f64)> {
+        let mut postings = PositionalReader::new(postings, postings_position as u64);
+        let mut doc_freq_buffer = [0; 4]; // Four bytes of 0.
+        postings.read_exact(&mut doc_freq_buffer).unwrap();
+        let document_frequency = (&doc_freq_buffer[..]).read_u32::<BigEndian>().unwrap();
+        let results = DiskInvertedIndex::decode_postings_no_positions(postings, 0, document_frequency);
+        self.count_decoded_postings(results.len());
+        results
     }
 
-    fn read_doc_weights_from_file(&self, mut doc_weights: &File, doc_id: u32) -> (f64, f64, u64, u64, f64) {
-        doc_weights.seek(SeekFrom::Start(0));
+    fn read_doc_weights_from_file(&self, doc_weights: &File, doc_id: u32) -> (f64, f64, u64, u64, f64) {
+        let mut doc_weights = PositionalReader::new(doc_weights, 0);
         let mut avg_doc_length_buffer = [0; 8];
         doc_weights.read_exact(&mut avg_doc_length_buffer).unwrap();
         let avg_doc_length = (&avg_doc_length_buffer[..]).read_f64::<BigEndian>().unwrap();
@@ -169,87 +528,41 @@ impl<'a> IndexReader for DiskInvertedIndex<'a> {
     }
     
     fn get_path(&self) -> String {
-        self.path.clone().to_string()
+        self.path.display().to_string()
     }
 
     fn get_document_frequency(&self, term: &str) -> u32 {
-        let postings_position = self.binary_search_vocabulary(term);
-        if postings_position == -1 {
-            return 0;
+        match self.search_vocabulary(term) {
+            Some(term_index) => self.files.document_frequencies[term_index],
+            None => 0,
         }
-        (&self.postings).seek(SeekFrom::Start(postings_position as u64)).expect("Error Seeking from Buffer");
-        let mut doc_freq_buffer = [0; 4];
-        (&self.postings).read_exact(&mut doc_freq_buffer).expect("Error Seeking from Buffer");
-        (&doc_freq_buffer[..]).read_u32::<BigEndian>().expect("Error Seeking from Buffer") // Return the document frequency
     }
 
     fn get_terms_for_document(&self, doc_id: u32) -> HashSet<String> {
-        let terms = self.get_vocab();
         let mut results = HashSet::new();
-        // results.push((doc_id, term_frequency_vbe, term_score, tf_idf_term_score, okapi_term_score, wacky_term_score, positions));
-        for term in &terms {
-            let postings = self.get_postings_no_positions(term).unwrap();
-            for posting in postings {
-                let (doc_id_term, _, _, _, _, _) = posting;
-                if doc_id == doc_id_term {
-                    results.insert(term.clone());
-                }
+        if let Ok(document_vector) = self.get_document_vector(doc_id) {
+            for (term_id, _) in document_vector {
+                results.insert(self.get_term(term_id).expect("Forward index refers to a missing term"));
             }
         }
         results
     }
 
     fn get_term_frequency(&self, term: &str) -> u32 {
-        let mut postings = &self.postings;
-        let postings_position = self.binary_search_vocabulary(term);
-
-        if postings_position == -1 {
-            return 0; 
+        match self.search_vocabulary(term) {
+            Some(term_index) => self.files.vocab_table[term_index * VOCAB_TABLE_STRIDE + 2] as u32,
+            None => 0,
         }
-        
-        postings.seek(SeekFrom::Start(postings_position as u64)).expect("(189)");
-        let mut doc_freq_buffer = [0; 4]; // Four bytes of 0.
-        postings.read_exact(&mut doc_freq_buffer).expect("191");
-        let document_frequency = (&doc_freq_buffer[..]).read_u32::<BigEndian>().expect("192");
-        let mut doc_id = 0;
-        let mut term_frequency_accumulator = 0;
-        for _ in 0..document_frequency {
-            let (doc_id_vbe, doc_id_offset) = variable_byte::decode(postings).expect("196");
-            postings.seek(SeekFrom::Current(-(5 - doc_id_offset as i64))).expect("197");
-
-            doc_id += doc_id_vbe;
-
-            postings.seek(SeekFrom::Current(32));
-
-            let (term_frequency_vbe, term_freq_offset) = variable_byte::decode(postings).unwrap();
-            postings.seek(SeekFrom::Current(-(5 - term_freq_offset as i64)));
-
-            term_frequency_accumulator += term_frequency_vbe;
-
-            for _ in 0..term_frequency_vbe {
-                let (postings_pos_vbe, postings_pos_offset) = variable_byte::decode(postings).unwrap();
-                postings.seek(SeekFrom::Current(-(5 - postings_pos_offset as i64)));
-            }
-        }
-        term_frequency_accumulator
     }
 
-    fn get_total_term_frequency(&self) -> u32 {
-        let vocabulary = self.get_vocab();
-
-        let mut total_term_frequency = 0;
-        for term in vocabulary {
-            total_term_frequency += self.get_term_frequency(&term); 
-        }
-
-        total_term_frequency
-
+    fn get_total_term_frequency(&self) -> u64 {
+        self.files.total_term_frequency
     }
 
     fn get_postings_no_positions(&self, term: &str) -> Result<Vec<(u32, u32, f64, f64, f64, f64)>, &'static str> {
         let postings_position = self.binary_search_vocabulary(term);
         match postings_position >= 0 {
-            true => Ok(self.read_postings_from_file_no_positions(&self.postings, postings_position)),
+            true => Ok(self.read_postings_from_file_no_positions(&self.files.postings, postings_position)),
             false => Err("Postings position is less than 0."),
         }
     }
@@ -257,21 +570,55 @@ impl<'a> IndexReader for DiskInvertedIndex<'a> {
     fn get_postings(&self, term: &str) -> Result<Vec<(u32, u32, f64, f64, f64, f64, Vec<u32>)>, &'static str> {
         let postings_position = self.binary_search_vocabulary(term);
         match postings_position >= 0 {
-            true => Ok(self.read_postings_from_file(&self.postings, postings_position)),
+            true => {
+                let postings = self.read_postings_from_file(&self.files.postings, postings_position);
+                self.count_decoded_postings(postings.len());
+                Ok(postings)
+            },
             false => Err("Postings position is less than 0."),
         }
     }
 
+    fn get_document(&self, doc_id: u32) -> Result<StoredDocument, &'static str> {
+        let block_position = *self.files.documents_table.get(doc_id as usize).ok_or("Document ID out of range")?;
+        // Documents of a block share its position in the table
+        let first_in_block = self.files.documents_table.partition_point(|&position| position < block_position);
+        let block_end = self.files.documents_table[doc_id as usize..]
+            .iter()
+            .find(|&&position| position > block_position)
+            .cloned();
+        let block = DiskInvertedIndex::read_range(&self.files.documents, block_position, block_end);
+        document_store::read_document(&block, doc_id as usize - first_in_block)
+    }
+
+    fn get_document_path(&self, doc_id: u32) -> Result<String, &'static str> {
+        self.files.doc_table.get_path(doc_id)
+    }
+
+    fn get_document_id(&self, path: &str) -> Option<u32> {
+        self.files.doc_table.get_doc_id(path)
+    }
+
+    fn get_postings_with_offsets(&self, term: &str) -> Result<Vec<(Posting, Option<TokenOffsets>)>, &'static str> {
+        let postings_position = self.binary_search_vocabulary(term);
+        if postings_position < 0 {
+            return Err("Term is not in the vocabulary");
+        }
+        let (postings, offsets) = DiskInvertedIndex::read_positional_postings(&self.files.postings, postings_position);
+        self.count_decoded_postings(postings.len());
+        Ok(postings.into_iter().zip(offsets).collect())
+    }
+
     fn get_document_weights(&self, doc_id: u32) -> Result<(f64, f64, u64, u64, f64), &'static str> {
         match doc_id >= 0 {
-            true => Ok(self.read_doc_weights_from_file(&self.doc_weights, doc_id)),
+            true => Ok(self.read_doc_weights_from_file(&self.files.doc_weights, doc_id)),
             false => Err("Document id not found."),
         }
     }
 
     fn get_vocab(&self) -> HashSet<String> {
 
-        let mut vocab_file = File::open(format!("{}/{}", self.path, "vocab.bin")).unwrap();
+        let mut vocab_file = File::open(format!("{}/{}", self.path.display(), "vocab.bin")).unwrap();
 
         let mut vocab_dict : HashSet<String> = HashSet::new();
 
@@ -279,15 +626,11 @@ impl<'a> IndexReader for DiskInvertedIndex<'a> {
 
         vocab_file.read_to_string(&mut contents).expect("Error reading file");
 
-        let vocab_table = &self.vocab_table;
+        let vocab_table = &self.files.vocab_table;
 
         let mut first_pos : u64 = 0;
         let mut second_pos : u64 = 0;
-        for (index, position) in vocab_table.iter().enumerate() {
-            if index % 2 != 0 {
-                continue; 
-            }
-
+        for (index, position) in vocab_table.iter().step_by(VOCAB_TABLE_STRIDE).enumerate() {
             if index == 0 {
                 continue;
             }
@@ -310,37 +653,10 @@ impl<'a> IndexReader for DiskInvertedIndex<'a> {
     }
 
     fn binary_search_vocabulary(&self, term: &str) -> i64 {
-        let mut vocab_list = &self.vocab_list;
-        let mut i : i64  = 0;
-        let mut j : i64  = self.vocab_table.len() as i64 / 2 - 1;
-        while i <= j {
-            let m : i64 =  (i + j) / 2;
-            // println!("i: {}, j: {}, m: {}", i, j, m);
-            let vocab_list_position = self.vocab_table.get(m as usize * 2).expect("Could not find vocabulary");
-            let mut term_length = 0;
-            if m == self.vocab_table.len() as i64 / 2 - 1 {
-                term_length = vocab_list.metadata().unwrap().len() as u64 - self.vocab_table[m as usize * 2];
-            }
-            else {
-                term_length = self.vocab_table.get((m as usize + 1) * 2).unwrap() - vocab_list_position;
-            }
-
-            vocab_list.seek(SeekFrom::Start(*vocab_list_position as u64)).expect("Failed to seek in file");
-
-            let mut buffer = vec![0; term_length as usize];
-            vocab_list.read_exact(&mut buffer).expect("Error reading from file");
-
-            let file_term = String::from_utf8(buffer).expect("Error getting string from buffer");
-
-            let compare_value = term.cmp(&file_term);
-
-            match compare_value {
-                Ordering::Equal => return *(self.vocab_table.get(m as usize * 2 + 1)).expect("Error comparing value") as i64,
-                Ordering::Less => j = m - 1,
-                Ordering::Greater => i = m + 1
-            }
+        match self.search_vocabulary(term) {
+            Some(term_index) => self.files.vocab_table[term_index * VOCAB_TABLE_STRIDE + 1] as i64,
+            None => -1,
         }
-        -1
     }
 
     fn read_vocab_table(index_name: &str) -> Vec<u64> {
@@ -364,24 +680,68 @@ impl<'a> IndexReader for DiskInvertedIndex<'a> {
     }
 
     fn get_term_count(&self) -> u32 {
-        return self.vocab_table.len() as u32 / 2;
+        (self.files.vocab_table.len() / VOCAB_TABLE_STRIDE) as u32
     }
     fn get_num_documents(&self) -> Result<u32, &'static str> {
-        let path = self.get_path();
-        let id_file_filename = format!("{}/{}", path, "id_file.bin");
+        match self.files.number_of_documents > 0 {
+            true => Ok(self.files.number_of_documents),
+            false => Err("Error: No documents found"),
+        }
+    }
 
-        let mut id_file_file = File::open(id_file_filename).unwrap();
+    fn get_unique_term_count(&self, doc_id: u32) -> u32 {
+        self.files.unique_term_counts.get(doc_id as usize).map_or(0, |&count| count as u32)
+    }
 
-        let mut id_file_contents = String::new();
-        id_file_file.read_to_string(&mut id_file_contents).expect("Failed to read id file");
+    fn get_document_vector(&self, doc_id: u32) -> Result<Vec<(u32, u32)>, &'static str> {
+        let start = match self.files.forward_table.get(doc_id as usize) {
+            Some(start) => *start,
+            None => return Err("Document id not found."),
+        };
+        let end = self.files.forward_table.get(doc_id as usize + 1).cloned();
+        let bytes = DiskInvertedIndex::read_range(&self.files.forward_index, start, end);
+
+        let (term_count, mut offset) = variable_byte::decode_bytes(&bytes).ok_or("Corrupt forward index")?;
+        let mut document_vector = Vec::with_capacity(term_count as usize);
+        let mut term_id = 0;
+        for _ in 0..term_count {
+            let (term_id_gap, gap_length) = variable_byte::decode_bytes(&bytes[offset..]).ok_or("Corrupt forward index")?;
+            offset += gap_length;
+            let (term_frequency, frequency_length) = variable_byte::decode_bytes(&bytes[offset..]).ok_or("Corrupt forward index")?;
+            offset += frequency_length;
+            term_id += term_id_gap;
+            document_vector.push((term_id, term_frequency));
+        }
+        Ok(document_vector)
+    }
 
-        let id_file : HashMap<u32, String> = serde_json::from_str(&id_file_contents).unwrap();
+    fn get_term(&self, term_id: u32) -> Result<String, &'static str> {
+        let term_index = term_id as usize * VOCAB_TABLE_STRIDE;
+        let start = match self.files.vocab_table.get(term_index) {
+            Some(start) => *start,
+            None => return Err("Term id not found."),
+        };
+        let end = self.files.vocab_table.get(term_index + VOCAB_TABLE_STRIDE).cloned();
+        let bytes = DiskInvertedIndex::read_range(&self.files.vocab_list, start, end);
+        String::from_utf8(bytes).map_err(|_| "Error getting string from buffer")
+    }
 
-        let num_documents = id_file.len();
+    fn get_term_id(&self, term: &str) -> Option<u32> {
+        self.search_vocabulary(term).map(|term_index| term_index as u32)
+    }
 
-        match num_documents > 0 {
-            true => Ok(num_documents as u32),
-            false => Err("Error: No documents found"),
+    fn get_posting_bounds(&self, term: &str) -> Result<(PostingBounds, BlockBounds), &'static str> {
+        let term_index = self.search_vocabulary(term).ok_or("Term not found.")?;
+        let mut bounds = BufReader::new(PositionalReader::new(&self.files.bounds, self.files.bounds_table[term_index]));
+
+        let term_bounds = PostingBounds::read_from(&mut bounds).map_err(|_| "Error reading bounds")?;
+        let block_count = bounds.read_u32::<BigEndian>().map_err(|_| "Error reading bounds")?;
+        let mut blocks = Vec::with_capacity(block_count as usize);
+        for _ in 0..block_count {
+            let last_doc_id = bounds.read_u32::<BigEndian>().map_err(|_| "Error reading bounds")?;
+            let postings_position = bounds.read_u64::<BigEndian>().map_err(|_| "Error reading bounds")?;
+            blocks.push((last_doc_id, postings_position, PostingBounds::read_from(&mut bounds).map_err(|_| "Error reading bounds")?));
         }
+        Ok((term_bounds, blocks))
     }
 }
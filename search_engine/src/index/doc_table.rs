@@ -0,0 +1,165 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::path::Path;
+use index::positional_reader::PositionalReader;
+
+/*
+ * Writes doc_id.bin, the table linking document IDs and the paths of their files. The file holds
+ * the number of documents (u32), the position of every document's path in the path area (u64, in
+ * document ID order), the document IDs sorted by file name (u32), then the UTF-8 paths themselves
+ *
+ * # Arguments
+ *
+ * *`folder` - The directory of the index
+ * *`paths` - The path of every document, in document ID order
+ */
+pub fn write_doc_table(folder: &str, paths: &[String]) {
+    let mut table = BufWriter::new(File::create(format!("{}/{}", folder, "doc_id.bin")).unwrap());
+    table.write_u32::<BigEndian>(paths.len() as u32).expect("Error writing to file");
+
+    let mut position = 0;
+    for path in paths {
+        table.write_u64::<BigEndian>(position).expect("Error writing to file");
+        position += path.len() as u64;
+    }
+
+    let mut sorted_ids: Vec<u32> = (0..paths.len() as u32).collect();
+    sorted_ids.sort_by(|&a, &b| compare_paths(&paths[a as usize], &paths[b as usize]));
+    for doc_id in sorted_ids {
+        table.write_u32::<BigEndian>(doc_id).expect("Error writing to file");
+    }
+
+    for path in paths {
+        table.write_all(path.as_bytes()).expect("Error writing to file");
+    }
+    table.flush().expect("Error writing to file");
+}
+
+/*
+ * The file name of a path, or the whole path if it has none
+ */
+fn file_name(path: &str) -> &str {
+    Path::new(path).file_name().and_then(|name| name.to_str()).unwrap_or(path)
+}
+
+/*
+ * Orders paths by file name, then by the whole path
+ */
+fn compare_paths(a: &str, b: &str) -> Ordering {
+    file_name(a).cmp(file_name(b)).then_with(|| a.cmp(b))
+}
+
+/*
+ * Reader of doc_id.bin. Only the position and sorted ID arrays are kept in memory; paths are read
+ * from the file when they are looked up
+ */
+pub struct DocTable {
+    file: File,
+    path_positions: Vec<u64>,
+    sorted_ids: Vec<u32>,
+    paths_start: u64,
+    paths_end: u64,
+}
+
+impl DocTable {
+    /*
+     * Opens the doc table of an index
+     *
+     * # Arguments
+     *
+     * *`folder` - The directory of the index
+     *
+     * # Returns
+     *
+     * The doc table, or an error if doc_id.bin is missing or truncated
+     */
+    pub fn open(folder: &str) -> Result<DocTable, &'static str> {
+        let file = File::open(format!("{}/{}", folder, "doc_id.bin")).map_err(|_| "Failed to open doc_id.bin")?;
+        let paths_end = file.metadata().map_err(|_| "Failed to read doc_id.bin")?.len();
+        let (path_positions, sorted_ids) = {
+            let mut table = BufReader::new(&file);
+            let count = table.read_u32::<BigEndian>().map_err(|_| "Corrupt doc table")?;
+            let mut path_positions = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                path_positions.push(table.read_u64::<BigEndian>().map_err(|_| "Corrupt doc table")?);
+            }
+            let mut sorted_ids = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                sorted_ids.push(table.read_u32::<BigEndian>().map_err(|_| "Corrupt doc table")?);
+            }
+            (path_positions, sorted_ids)
+        };
+        let paths_start = 4 + path_positions.len() as u64 * (8 + 4);
+        Ok(DocTable { file, path_positions, sorted_ids, paths_start, paths_end })
+    }
+
+    /*
+     * Number of documents in the table
+     */
+    pub fn len(&self) -> usize {
+        self.path_positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.path_positions.is_empty()
+    }
+
+    /*
+     * Reads the path of a document
+     *
+     * # Arguments
+     *
+     * *`doc_id` - The document ID
+     *
+     * # Returns
+     *
+     * The path of the document's file, or an error if the ID is not in the table
+     */
+    pub fn get_path(&self, doc_id: u32) -> Result<String, &'static str> {
+        let start = self.paths_start + *self.path_positions.get(doc_id as usize).ok_or("Document ID out of range")?;
+        let end = match self.path_positions.get(doc_id as usize + 1) {
+            Some(&end) => self.paths_start + end,
+            None => self.paths_end,
+        };
+        let mut buffer = vec![0; end.checked_sub(start).ok_or("Corrupt doc table")? as usize];
+        PositionalReader::new(&self.file, start).read_exact(&mut buffer).map_err(|_| "Corrupt doc table")?;
+        String::from_utf8(buffer).map_err(|_| "Corrupt doc table")
+    }
+
+    /*
+     * Finds the document ID of a file by binary searching the sorted IDs
+     *
+     * # Arguments
+     *
+     * *`path` - The path of the file, or only its file name
+     *
+     * # Returns
+     *
+     * The document ID, or None if no indexed file has that path or file name
+     */
+    pub fn get_doc_id(&self, path: &str) -> Option<u32> {
+        let name = file_name(path);
+        let whole_path = name != path;
+        let mut low = 0;
+        let mut high = self.sorted_ids.len();
+        while low < high {
+            let middle = (low + high) / 2;
+            let candidate = self.get_path(self.sorted_ids[middle]).ok()?;
+            let ordering = if whole_path {
+                compare_paths(&candidate, path)
+            } else {
+                file_name(&candidate).cmp(name)
+            };
+            match ordering {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return Some(self.sorted_ids[middle]),
+            }
+        }
+        None
+    }
+}
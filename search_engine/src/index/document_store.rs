@@ -0,0 +1,155 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io;
+use std::io::BufWriter;
+
+/*
+ * Size in bytes of uncompressed records after which a block of the document store is compressed
+ * and written
+ */
+pub const DOCUMENT_BLOCK_SIZE: usize = 16 * 1024;
+
+/*
+ * The stored fields of a document, kept by the index so results can be shown without the
+ * original file
+ */
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StoredDocument {
+    pub title: String,
+    pub url: String,
+    pub body: String,
+
+    /*
+     * Any other named values stored with the document, such as its original path
+     */
+    pub fields: BTreeMap<String, String>,
+}
+
+impl StoredDocument {
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_string(writer, &self.title)?;
+        write_string(writer, &self.url)?;
+        write_string(writer, &self.body)?;
+        writer.write_u32::<BigEndian>(self.fields.len() as u32)?;
+        for (name, value) in self.fields.iter() {
+            write_string(writer, name)?;
+            write_string(writer, value)?;
+        }
+        Ok(())
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> io::Result<StoredDocument> {
+        let title = read_string(reader)?;
+        let url = read_string(reader)?;
+        let body = read_string(reader)?;
+        let field_count = reader.read_u32::<BigEndian>()?;
+        let mut fields = BTreeMap::new();
+        for _ in 0..field_count {
+            let name = read_string(reader)?;
+            fields.insert(name, read_string(reader)?);
+        }
+        Ok(StoredDocument { title, url, body, fields })
+    }
+}
+
+fn write_string<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    writer.write_u32::<BigEndian>(value.len() as u32)?;
+    writer.write_all(value.as_bytes())
+}
+
+fn read_string<R: Read>(reader: &mut R) -> io::Result<String> {
+    let length = reader.read_u32::<BigEndian>()?;
+    let mut buffer = vec![0; length as usize];
+    reader.read_exact(&mut buffer)?;
+    String::from_utf8(buffer).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Stored string is not UTF-8"))
+}
+
+/*
+ * Writes the document store of an index. Documents are added in document ID order and grouped into
+ * zlib compressed blocks written to documents.bin. documents_table.bin holds, for every document,
+ * the position of its block as a u64
+ */
+pub struct DocumentStoreWriter {
+    documents: BufWriter<File>,
+    table: BufWriter<File>,
+    position: u64,
+    block: Vec<u8>,
+}
+
+impl DocumentStoreWriter {
+    /*
+     * Creates an empty document store
+     *
+     * # Arguments
+     *
+     * *`folder` - The directory of the index
+     *
+     * # Returns
+     *
+     * The writer of the new store
+     */
+    pub fn new(folder: &str) -> DocumentStoreWriter {
+        DocumentStoreWriter {
+            documents: BufWriter::new(File::create(format!("{}/{}", folder, "documents.bin")).unwrap()),
+            table: BufWriter::new(File::create(format!("{}/{}", folder, "documents_table.bin")).unwrap()),
+            position: 0,
+            block: Vec::new(),
+        }
+    }
+
+    /*
+     * Adds the next document to the store
+     */
+    pub fn add(&mut self, document: &StoredDocument) {
+        self.table.write_u64::<BigEndian>(self.position).expect("Error writing to file");
+        document.write_to(&mut self.block).expect("Error writing to buffer");
+        if self.block.len() >= DOCUMENT_BLOCK_SIZE {
+            self.write_block();
+        }
+    }
+
+    /*
+     * Writes the last block and flushes the store to disk
+     */
+    pub fn finish(mut self) {
+        if !self.block.is_empty() {
+            self.write_block();
+        }
+        self.documents.flush().expect("Error writing to file");
+        self.table.flush().expect("Error writing to file");
+    }
+
+    fn write_block(&mut self) {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&self.block).expect("Error compressing block");
+        let compressed = encoder.finish().expect("Error compressing block");
+        self.documents.write_all(&compressed).expect("Error writing to file");
+        self.position += compressed.len() as u64;
+        self.block.clear();
+    }
+}
+
+/*
+ * Reads a document out of a compressed block of the document store
+ *
+ * # Arguments
+ *
+ * *`block` - The compressed block holding the document
+ * *`slot` - Number of documents stored before it in the block
+ *
+ * # Returns
+ *
+ * The stored document, or an error if the block is corrupt
+ */
+pub fn read_document(block: &[u8], slot: usize) -> Result<StoredDocument, &'static str> {
+    let mut decoder = ZlibDecoder::new(block);
+    for _ in 0..slot {
+        StoredDocument::read_from(&mut decoder).map_err(|_| "Corrupt document store")?;
+    }
+    StoredDocument::read_from(&mut decoder).map_err(|_| "Corrupt document store")
+}
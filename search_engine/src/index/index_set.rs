@@ -0,0 +1,158 @@
+use index::disk_inverted_index::DiskInvertedIndex;
+use index::k_gram_index::KGramIndex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+/*
+ * Catalog of named Disk Inverted Indexes, each with the K Gram Index expanding its wildcards, that
+ * can be opened, looked up and closed while it is shared between threads. Lookups hand out clones
+ * of the cached handles, so an index is only read from disk once however many times it is
+ * requested, and closing an index only removes it from the catalog: handles already handed out
+ * keep reading it until they are dropped
+ */
+#[derive(Default)]
+pub struct IndexSet {
+    indexes: RwLock<HashMap<String, (DiskInvertedIndex, Arc<KGramIndex>)>>,
+}
+
+impl IndexSet {
+    pub fn new() -> IndexSet {
+        IndexSet {
+            indexes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /*
+     * Opens the index of a directory and its K Gram Index under a name. An index already open under
+     * the name is returned as is if it reads the same directory, and replaced otherwise
+     *
+     * # Arguments
+     *
+     * *`name` - The name the index is looked up by
+     * *`path` - The indexed directory
+     *
+     * # Returns
+     *
+     * The index, or an error if the directory has not been indexed
+     */
+    pub fn open<P: AsRef<Path>>(&self, name: &str, path: P) -> Result<DiskInvertedIndex, &'static str> {
+        let path = path.as_ref();
+        if let Some(index) = self.get(name) {
+            if index.get_directory() == path {
+                return Ok(index);
+            }
+        }
+        let index = DiskInvertedIndex::open(path)?;
+        let kgram = Arc::new(KGramIndex::read(path));
+        let mut indexes = self.indexes.write().unwrap_or_else(PoisonError::into_inner);
+        // Another thread may have opened the same directory in the meantime
+        if let Some((open, _)) = indexes.get(name) {
+            if open.get_directory() == path {
+                return Ok(open.clone());
+            }
+        }
+        indexes.insert(name.to_string(), (index.clone(), kgram));
+        Ok(index)
+    }
+
+    /*
+     * Adds an index opened by the caller under a name, replacing any index open under it
+     *
+     * # Arguments
+     *
+     * *`name` - The name the index is looked up by
+     * *`index` - The Disk Inverted Index
+     * *`kgram` - The K Gram Index used to expand its wildcards, empty if the index has none
+     */
+    pub fn insert(&self, name: &str, index: DiskInvertedIndex, kgram: Arc<KGramIndex>) {
+        self.indexes.write().unwrap_or_else(PoisonError::into_inner).insert(name.to_string(), (index, kgram));
+    }
+
+    /*
+     * Opens the index of a named directory and its K Gram Index again, picking up a rebuilt index
+     *
+     * # Arguments
+     *
+     * *`name` - The name the index was opened under
+     *
+     * # Returns
+     *
+     * The reopened index, or an error if no index is open under the name or the directory is no
+     * longer indexed. The previous index stays open under the name on error
+     */
+    pub fn reopen(&self, name: &str) -> Result<DiskInvertedIndex, &'static str> {
+        let path = match self.get(name) {
+            Some(index) => index.get_directory().to_path_buf(),
+            None => return Err("No index is open under that name"),
+        };
+        let index = DiskInvertedIndex::open(&path)?;
+        let kgram = Arc::new(KGramIndex::read(&path));
+        self.indexes.write().unwrap_or_else(PoisonError::into_inner).insert(name.to_string(), (index.clone(), kgram));
+        Ok(index)
+    }
+
+    /*
+     * Looks up an open index
+     *
+     * # Arguments
+     *
+     * *`name` - The name the index was opened under
+     *
+     * # Returns
+     *
+     * A handle to the index, or None if no index is open under the name
+     */
+    pub fn get(&self, name: &str) -> Option<DiskInvertedIndex> {
+        self.get_with_k_gram_index(name).map(|(index, _)| index)
+    }
+
+    /*
+     * Looks up an open index together with its K Gram Index, both from the same opening
+     *
+     * # Arguments
+     *
+     * *`name` - The name the index was opened under
+     *
+     * # Returns
+     *
+     * Handles to the indexes, or None if no index is open under the name
+     */
+    pub fn get_with_k_gram_index(&self, name: &str) -> Option<(DiskInvertedIndex, Arc<KGramIndex>)> {
+        self.indexes.read().unwrap_or_else(PoisonError::into_inner).get(name).cloned()
+    }
+
+    /*
+     * Removes an index from the set. Its files are closed once every handle to it is dropped
+     *
+     * # Arguments
+     *
+     * *`name` - The name the index was opened under
+     *
+     * # Returns
+     *
+     * Whether an index was open under the name
+     */
+    pub fn close(&self, name: &str) -> bool {
+        self.indexes.write().unwrap_or_else(PoisonError::into_inner).remove(name).is_some()
+    }
+
+    /*
+     * The names of the open indexes, sorted
+     */
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.indexes.read().unwrap_or_else(PoisonError::into_inner).keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn len(&self) -> usize {
+        self.indexes.read().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -1,11 +1,16 @@
 use byteorder::{WriteBytesExt, BigEndian};
 use std::fs::File;
 use std::io::prelude::*;
+use std::io::BufWriter;
 use std::fs;
-use std::mem;
 use index::positional_inverted_index::PositionalInvertedIndex;
 use parser::document_parser::DocumentWeight;
 use index::variable_byte;
+use index::posting_bounds::PostingBounds;
+use index::posting_bounds::BOUNDS_BLOCK_SIZE;
+use index::posting_bounds::BOUNDS_RECORD_SIZE;
+use index::disk_inverted_index::INDEX_FORMAT_VERSION;
+use index::disk_inverted_index::INDEX_MAGIC;
 
 pub struct IndexWriter<'a> {
     folder_path: &'a str
@@ -20,9 +25,11 @@ pub trait DiskIndex {
 
     fn build_index_for_directory(&self, index: &PositionalInvertedIndex, doc_weights: &Vec<DocumentWeight>, average_doc_length: f64, folder: &str);
     fn build_vocab_file(&self, folder: &str, dictionary: &Vec<&String>, vocab_positions: &mut Vec<u64>);
-    fn build_doc_id_file(&self, folder: &str, doc_weights: &Vec<DocumentWeight>, doc_id_positions: &mut Vec<u64>);
-    fn build_postings_file(&self, folder: &str, index: &PositionalInvertedIndex, dictionary: &Vec<&String>, vocab_positions: &mut Vec<u64>);
-    fn build_doc_weights_file(&self, folder: &str, average_doc_length: f64, doc_weights: &Vec<DocumentWeight>, doc_id_positions: &mut Vec<u64>);
+    fn build_postings_file(&self, folder: &str, index: &PositionalInvertedIndex, dictionary: &[&String], vocab_positions: &mut Vec<u64>, block_positions: &mut Vec<Vec<u64>>);
+    fn build_doc_weights_file(&self, folder: &str, average_doc_length: f64, doc_weights: &[DocumentWeight]);
+    fn build_statistics_file(&self, folder: &str, index: &PositionalInvertedIndex, dictionary: &[&String], doc_weights: &[DocumentWeight]);
+    fn build_forward_index_file(&self, folder: &str, index: &PositionalInvertedIndex, dictionary: &[&String], doc_weights: &[DocumentWeight]);
+    fn build_bounds_file(&self, folder: &str, index: &PositionalInvertedIndex, dictionary: &[&String], doc_weights: &[DocumentWeight], block_positions: &[Vec<u64>]);
 }
 
 impl<'a> IndexWriter<'a> {
@@ -39,11 +46,13 @@ impl<'a> DiskIndex for IndexWriter<'a> {
     fn build_index_for_directory(&self, index: &PositionalInvertedIndex, doc_weights: &Vec<DocumentWeight>, average_doc_length: f64, folder: &str) {
         let dictionary = index.get_dictionary();
         let mut vocab_positions : Vec<u64> = Vec::new();
+        let mut block_positions : Vec<Vec<u64>> = Vec::new();
         self.build_vocab_file(folder, &dictionary, &mut vocab_positions);
-        self.build_postings_file(folder, index, &dictionary, &mut vocab_positions);
-        let mut doc_id_positions : Vec<u64> = Vec::new();
-        self.build_doc_id_file(folder, doc_weights, &mut doc_id_positions);
-        self.build_doc_weights_file(folder, average_doc_length, &doc_weights, &mut doc_id_positions);
+        self.build_postings_file(folder, index, &dictionary, &mut vocab_positions, &mut block_positions);
+        self.build_doc_weights_file(folder, average_doc_length, doc_weights);
+        self.build_statistics_file(folder, index, &dictionary, doc_weights);
+        self.build_forward_index_file(folder, index, &dictionary, doc_weights);
+        self.build_bounds_file(folder, index, &dictionary, doc_weights, &block_positions);
     }
     
     fn build_vocab_file(&self, folder: &str, dictionary: &Vec<&String>, vocab_positions: &mut Vec<u64>) {
@@ -56,17 +65,11 @@ impl<'a> DiskIndex for IndexWriter<'a> {
         }
     }
 
-    fn build_doc_id_file(&self, folder: &str, doc_weights: &Vec<DocumentWeight>, doc_id_positions: &mut Vec<u64>) {
-        let mut doc_id_list = File::create(format!("{}/{}", folder, "doc_id.bin")).unwrap();
-        let mut doc_id_position = 0;
-        for doc_id in doc_weights.iter().map(|x| x.get_doc_id()).collect::<Vec<_>>() {
-            doc_id_positions.push(doc_id_position);
-            doc_id_list.write_u32::<BigEndian>(doc_id).expect("Error writing to file");
-            doc_id_position += mem::size_of::<u32>() as u64;
-        }
-    }
-
-    fn build_postings_file(&self, folder: &str, index: &PositionalInvertedIndex, dictionary: &Vec<&String>, vocab_positions: &mut Vec<u64>) {
+    /*
+     * Writes postings.bin and vocab_table.bin, recording in block_positions the position in
+     * postings.bin of the first posting of every block of BOUNDS_BLOCK_SIZE postings of every term
+     */
+    fn build_postings_file(&self, folder: &str, index: &PositionalInvertedIndex, dictionary: &[&String], vocab_positions: &mut Vec<u64>, block_positions: &mut Vec<Vec<u64>>) {
         let mut postings_file = File::create(format!("{}/{}", folder, "postings.bin")).unwrap();
         let mut vocab_table = File::create(format!("{}/{}", folder, "vocab_table.bin")).unwrap();
 
@@ -80,10 +83,17 @@ impl<'a> DiskIndex for IndexWriter<'a> {
 
             vocab_table.write_u64::<BigEndian>(postings_file_size).expect("Error writing to file");
 
+            let collection_frequency: u64 = postings.iter().map(|posting| posting.get_positions().len() as u64).sum();
+            vocab_table.write_u64::<BigEndian>(collection_frequency).expect("Error writing to file"); // Cf
+
             let document_frequency = postings.len() as u32;
             postings_file.write_u32::<BigEndian>(document_frequency).expect("Error writing to file");
             let mut last_doc_id = 0;
-            for doc_id in postings {
+            let mut term_block_positions = Vec::new();
+            for (i, doc_id) in postings.iter().enumerate() {
+                if i % BOUNDS_BLOCK_SIZE == 0 {
+                    term_block_positions.push(postings_file.stream_position().expect("Error reading file position"));
+                }
                 let doc_id_location = doc_id.get_doc_id() - last_doc_id;
                 let doc_id_location_vbe = variable_byte::encode(doc_id_location);
                 postings_file.write_all(&doc_id_location_vbe[..]).expect("Error writing to file");
@@ -101,24 +111,35 @@ impl<'a> DiskIndex for IndexWriter<'a> {
                 postings_file.write_f64::<BigEndian>(wacky_term_score).expect("Error writing to file"); //Wdt
 
                 let positions = doc_id.get_positions(); // Get postings positions for every document
+                let offsets = doc_id.get_offsets();
                 let term_frequency = positions.len() as u32;
-                let term_frequency_vbe = variable_byte::encode(term_frequency);
+                // The lowest bit flags postings whose positions are each followed by the start gap
+                // and length of the term's character offsets
+                let term_frequency_vbe = variable_byte::encode(term_frequency << 1 | offsets.is_some() as u32);
                 postings_file.write_all(&term_frequency_vbe[..]).expect("Error writing to file");
                 let mut last_pos = 0;
-                for pos in positions {
+                let mut last_start = 0;
+                for (j, pos) in positions.into_iter().enumerate() {
                     let pos_location = pos - last_pos;
                     let pos_location_vbe = variable_byte::encode(pos_location);
                     postings_file.write_all(&pos_location_vbe[..]).expect("Error writing to file");
                     last_pos = pos;
+                    if let Some(ref offsets) = offsets {
+                        let (start, end) = offsets[j];
+                        postings_file.write_all(&variable_byte::encode(start - last_start)).expect("Error writing to file");
+                        postings_file.write_all(&variable_byte::encode(end - start)).expect("Error writing to file");
+                        last_start = start;
+                    }
                 }
                 last_doc_id = doc_id.get_doc_id();
             }
+            block_positions.push(term_block_positions);
             vocab_index += 1;
         }
         
     }
 
-    fn build_doc_weights_file(&self, folder: &str, average_doc_length: f64, doc_weights: &Vec<DocumentWeight>, doc_id_positions: &mut Vec<u64>) {
+    fn build_doc_weights_file(&self, folder: &str, average_doc_length: f64, doc_weights: &[DocumentWeight]) {
         let mut document_weights = File::create(format!("{}/{}", folder, "doc_weights.bin")).unwrap();
         document_weights.write_f64::<BigEndian>(average_doc_length).expect("Error writing to file");
         for weight in doc_weights {
@@ -128,4 +149,110 @@ impl<'a> DiskIndex for IndexWriter<'a> {
             document_weights.write_f64::<BigEndian>(weight.get_avg_tftd()).expect("Error writing to file");
         }
     }
+
+    /*
+     * Writes statistics.bin: INDEX_MAGIC and INDEX_FORMAT_VERSION (u32), the number of documents
+     * (u32), the total number of tokens (u64), the number of terms (u32), then the collection
+     * frequency (u64) and document frequency (u32) of every term in vocabulary order, then the
+     * number of distinct terms (u64) of every document
+     */
+    fn build_statistics_file(&self, folder: &str, index: &PositionalInvertedIndex, dictionary: &[&String], doc_weights: &[DocumentWeight]) {
+        let mut term_statistics: Vec<(u64, u32)> = Vec::new();
+        for term in dictionary {
+            let postings = index.get_postings(term);
+            let collection_frequency: u64 = postings.iter().map(|posting| posting.get_positions().len() as u64).sum();
+            term_statistics.push((collection_frequency, postings.len() as u32));
+        }
+        let total_term_frequency: u64 = term_statistics.iter().map(|&(collection_frequency, _)| collection_frequency).sum();
+
+        let mut statistics = BufWriter::new(File::create(format!("{}/{}", folder, "statistics.bin")).unwrap());
+        statistics.write_all(&INDEX_MAGIC).expect("Error writing to file");
+        statistics.write_u32::<BigEndian>(INDEX_FORMAT_VERSION).expect("Error writing to file");
+        statistics.write_u32::<BigEndian>(doc_weights.len() as u32).expect("Error writing to file");
+        statistics.write_u64::<BigEndian>(total_term_frequency).expect("Error writing to file");
+        statistics.write_u32::<BigEndian>(term_statistics.len() as u32).expect("Error writing to file");
+        for (collection_frequency, document_frequency) in term_statistics {
+            statistics.write_u64::<BigEndian>(collection_frequency).expect("Error writing to file");
+            statistics.write_u32::<BigEndian>(document_frequency).expect("Error writing to file");
+        }
+        for weight in doc_weights {
+            statistics.write_u64::<BigEndian>(weight.get_unique_term_count()).expect("Error writing to file");
+        }
+        statistics.flush().expect("Error writing to file");
+    }
+
+    /*
+     * Writes the forward index. forward_index.bin holds, for every document in id order, the
+     * variable byte encoded number of distinct terms followed by (term id gap, tf) pairs sorted by
+     * term id, where a term id is the term's position in the vocabulary. forward_table.bin holds
+     * the u64 position of every document's entry in forward_index.bin
+     */
+    fn build_forward_index_file(&self, folder: &str, index: &PositionalInvertedIndex, dictionary: &[&String], doc_weights: &[DocumentWeight]) {
+        let mut document_vectors: Vec<Vec<(u32, u32)>> = vec![Vec::new(); doc_weights.len()];
+        for (term_id, term) in dictionary.iter().enumerate() {
+            for posting in index.get_postings(term) {
+                let term_frequency = posting.get_positions().len() as u32;
+                document_vectors[posting.get_doc_id() as usize].push((term_id as u32, term_frequency));
+            }
+        }
+
+        let mut forward_index = BufWriter::new(File::create(format!("{}/{}", folder, "forward_index.bin")).unwrap());
+        let mut forward_table = BufWriter::new(File::create(format!("{}/{}", folder, "forward_table.bin")).unwrap());
+        let mut forward_position: u64 = 0;
+        for document_vector in document_vectors {
+            forward_table.write_u64::<BigEndian>(forward_position).expect("Error writing to file");
+
+            let mut bytes = variable_byte::encode(document_vector.len() as u32);
+            let mut last_term_id = 0;
+            for (term_id, term_frequency) in document_vector {
+                bytes.extend(variable_byte::encode(term_id - last_term_id));
+                bytes.extend(variable_byte::encode(term_frequency));
+                last_term_id = term_id;
+            }
+            forward_index.write_all(&bytes).expect("Error writing to file");
+            forward_position += bytes.len() as u64;
+        }
+        forward_index.flush().expect("Error writing to file");
+        forward_table.flush().expect("Error writing to file");
+    }
+
+    /*
+     * Writes the score bounds used for dynamic pruning. For every term in vocabulary order,
+     * bounds.bin holds the PostingBounds of the whole posting list, the number of blocks (u32) and,
+     * for every block of BOUNDS_BLOCK_SIZE postings, its last document id (u32), the position of
+     * its first posting in postings.bin (u64) and its PostingBounds. bounds_table.bin holds the u64
+     * position of every term's entry in bounds.bin
+     */
+    fn build_bounds_file(&self, folder: &str, index: &PositionalInvertedIndex, dictionary: &[&String], doc_weights: &[DocumentWeight], block_positions: &[Vec<u64>]) {
+        let mut bounds_file = BufWriter::new(File::create(format!("{}/{}", folder, "bounds.bin")).unwrap());
+        let mut bounds_table = BufWriter::new(File::create(format!("{}/{}", folder, "bounds_table.bin")).unwrap());
+        let mut bounds_position: u64 = 0;
+        for (term, term_block_positions) in dictionary.iter().zip(block_positions) {
+            let postings = index.get_postings(term);
+            let mut term_bounds = PostingBounds::new();
+            let mut blocks: Vec<(u32, PostingBounds)> = Vec::new();
+            for block in postings.chunks(BOUNDS_BLOCK_SIZE) {
+                let mut block_bounds = PostingBounds::new();
+                for posting in block {
+                    let weight = &doc_weights[posting.get_doc_id() as usize];
+                    let term_frequency = posting.get_positions().len() as u32;
+                    block_bounds.add(term_frequency, weight.get_doc_weight(), weight.get_doc_length(), weight.get_byte_size(), weight.get_avg_tftd());
+                    term_bounds.add(term_frequency, weight.get_doc_weight(), weight.get_doc_length(), weight.get_byte_size(), weight.get_avg_tftd());
+                }
+                blocks.push((block.last().unwrap().get_doc_id(), block_bounds));
+            }
+
+            bounds_table.write_u64::<BigEndian>(bounds_position).expect("Error writing to file");
+            term_bounds.write_to(&mut bounds_file).expect("Error writing to file");
+            bounds_file.write_u32::<BigEndian>(blocks.len() as u32).expect("Error writing to file");
+            for ((last_doc_id, block_bounds), block_position) in blocks.iter().zip(term_block_positions) {
+                bounds_file.write_u32::<BigEndian>(*last_doc_id).expect("Error writing to file");
+                bounds_file.write_u64::<BigEndian>(*block_position).expect("Error writing to file");
+                block_bounds.write_to(&mut bounds_file).expect("Error writing to file");
+            }
+            bounds_position += BOUNDS_RECORD_SIZE + 4 + blocks.len() as u64 * (4 + 8 + BOUNDS_RECORD_SIZE);
+        }
+        bounds_file.flush().expect("Error writing to file");
+        bounds_table.flush().expect("Error writing to file");
+    }
 }
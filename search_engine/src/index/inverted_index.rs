@@ -9,7 +9,7 @@ impl InvertedIndex {
         self.m_index = HashMap::new();
     }
     pub fn add_term(&mut self, term: &str, doc_id: i32) {
-        println!("Adding term {} to {}", term, doc_id);
+        trace!("Adding term {} to {}", term, doc_id);
 
         if self.m_index.contains_key(term) {
             let mut p = self.m_index.get_mut(term);
@@ -17,17 +17,17 @@ impl InvertedIndex {
             if posting[posting.len() - 1] != doc_id {
                 posting.push(doc_id);
             }
-            println!("Term {} added to {}", term, doc_id);
+            trace!("Term {} added to {}", term, doc_id);
         } else {
             let mut posting = Vec::new();
             posting.push(doc_id);
             self.m_index.insert(term.to_string(), posting);
-            println!("Term {} added to {}", term, doc_id);
+            trace!("Term {} added to {}", term, doc_id);
         }
     }
 
     pub fn get_postings(&self, term: &str) -> &Vec<i32> {
-        println!("Getting postings for term {}", term);
+        trace!("Getting postings for term {}", term);
 
         self.m_index.get(term).unwrap()
 
@@ -1,5 +1,8 @@
+use serde_json;
 use std::ascii::AsciiExt;
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
 /*
  * Structure that represents KGram Index
@@ -34,6 +37,25 @@ impl KGramIndex {
         }
     }
 
+    /*
+     * Reads the K Gram Index saved with the index of a directory
+     *
+     * # Arguments
+     *
+     * *`directory` - The indexed directory
+     *
+     * # Returns
+     *
+     * The K Gram Index, or an empty one if the index was built without it. Wildcards are then not
+     * expanded
+     */
+    pub fn read<P: AsRef<Path>>(directory: P) -> KGramIndex {
+        fs::read_to_string(directory.as_ref().join("kgram.bin"))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_else(KGramIndex::new)
+    }
+
     /*
      * Checks a term 
      *
@@ -58,8 +80,8 @@ impl KGramIndex {
                     let buffer_first_half = &buffer_string[0..2];
                     let buffer_second_half = &buffer_string[1..3];
                     let buffer_last_char : String = buffer_string.chars().skip(2).take(1).collect();
-                    let buffer_mid_char : String = buffer_string.chars().skip(0).take(1).collect();
-                    let buffer_first_char : String = buffer_string.chars().skip(1).take(1).collect();
+                    let buffer_first_char : String = buffer_string.chars().take(1).collect();
+                    let buffer_mid_char : String = buffer_string.chars().skip(1).take(1).collect();
 
                     self.add_index(&buffer_string, &term);
                     self.add_index(&buffer_first_half, &term);
@@ -121,6 +143,26 @@ impl KGramIndex {
         self.m_index.get(gram).expect("Error retrieving grams")
     }
 
+    /*
+     * Finds the indexed terms starting with a prefix, among the terms of the gram marking the start
+     * of a term with the first characters of the prefix
+     *
+     * # Arguments
+     *
+     * *`prefix` - The start of the terms
+     *
+     * # Returns
+     *
+     * The matching terms, in no particular order
+     */
+    pub fn get_terms_with_prefix(&self, prefix: &str) -> Vec<&String> {
+        let gram: String = format!("${}", prefix).chars().take(3).collect();
+        match self.m_index.get(&gram) {
+            Some(terms) => terms.iter().filter(|term| term.starts_with(prefix)).collect(),
+            None => Vec::new(),
+        }
+    }
+
     /*
      * Returns the status of the k_gram_index
      *
@@ -4,3 +4,9 @@ pub mod k_gram_index;
 pub mod index_writer;
 pub mod disk_inverted_index;
 pub mod variable_byte;
+pub mod posting_bounds;
+pub mod document_store;
+pub mod doc_table;
+pub mod positional_reader;
+pub mod index_set;
+pub mod postings_cursor;
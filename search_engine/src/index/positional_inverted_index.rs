@@ -27,6 +27,11 @@ pub struct PositionalPosting {
      * The list of positions for each posting
      */
     m_positions: Vec<u32>,
+
+    /*
+     * Start and end character offsets of the term at every position, if they were recorded
+     */
+    m_offsets: Vec<(u32, u32)>,
 }
 
 /*
@@ -49,6 +54,7 @@ impl PositionalPosting {
         PositionalPosting {
             m_doc_id: doc_id,
             m_positions: Vec::new(),
+            m_offsets: Vec::new(),
             m_term_score: 0.0_f64,
             m_tf_idf_term_score: 0.0f64,
             m_okapi_term_score: 0.0f64,
@@ -100,6 +106,22 @@ impl PositionalPosting {
         self.m_positions.clone()
     }
 
+    /*
+     * Returns the character offsets of the term at every position
+     *
+     * # Returns
+     *
+     * The start and end character offsets in position order, or None if they were not recorded for
+     * every position
+     */
+    pub fn get_offsets(&self) -> Option<Vec<(u32, u32)>> {
+        if !self.m_offsets.is_empty() && self.m_offsets.len() == self.m_positions.len() {
+            Some(self.m_offsets.clone())
+        } else {
+            None
+        }
+    }
+
     /*
      * Adds a new position for the posting
      *
@@ -111,6 +133,20 @@ impl PositionalPosting {
         self.m_positions.push(pos);
     }
 
+    /*
+     * Adds a new position for the posting together with the character offsets of the term
+     *
+     * # Arguments
+     *
+     * *`pos` - The position to be added to the Posting
+     * *`start` - Character offset of the first character of the term
+     * *`end` - Character offset just past the last character of the term
+     */
+    pub fn add_position_with_offsets(&mut self, pos: u32, start: u32, end: u32) {
+        self.m_positions.push(pos);
+        self.m_offsets.push((start, end));
+    }
+
     /*
      * Returns the last position the posting was listed
      *
@@ -186,46 +222,41 @@ impl PositionalInvertedIndex {
      *
      */
     pub fn add_term(&mut self, term: &str, doc_id: u32, pos: u32) {
-        if self.m_index.contains_key(term) {
-            {
-                let positional_postings = self.m_index.get_mut(term)
-                    .expect("No term found");
-
-                let num_of_doc_ids = positional_postings.len();
-                let last_posting = positional_postings.get_mut(num_of_doc_ids - 1)
-                    .expect(
-                    "Could not get posting",
-                );
-
-                if last_posting.get_doc_id() == doc_id {
-                    let last_position = last_posting.get_last_position();
-                    if last_position != pos {
-                        last_posting.add_position(pos);
-                    }
-                }
-            }
-            {
-                if self.m_index.get(term).unwrap().last().unwrap().get_doc_id() != doc_id {
-                    let mut new_posting = PositionalPosting::new(doc_id);
-                    new_posting.add_position(pos);
-                    self.m_index.get_mut(term).expect("term not found").push(
-                        new_posting,
-                    );
-                }
-            }
-        } else {
-            let mut new_posting = PositionalPosting {
-                m_doc_id: doc_id,
-                m_positions: Vec::new(),
-                m_term_score: 0.0f64,
-                m_tf_idf_term_score: 0.0f64,
-                m_okapi_term_score: 0.0f64,
-                m_wacky_term_score: 0.0f64
-            };
-            new_posting.add_position(pos);
-            let mut positional_postings = Vec::new();
-            positional_postings.push(new_posting);
-            self.m_index.insert(term.to_string(), positional_postings);
+        self.add_occurrence(term, doc_id, pos, None);
+    }
+
+    /*
+     * Adds a term to the Positional Inverted Index like add_term, also recording where the term
+     * occurs in the text of the document
+     *
+     * # Arguments
+     *
+     * *`term` - The term to be added to the index
+     * *`doc_id` - The document id where this term exists
+     * *`pos` - The marked position of the term in the document
+     * *`start` - Character offset of the first character of the term in the document body
+     * *`end` - Character offset just past the last character of the term
+     */
+    pub fn add_term_with_offsets(&mut self, term: &str, doc_id: u32, pos: u32, start: u32, end: u32) {
+        self.add_occurrence(term, doc_id, pos, Some((start, end)));
+    }
+
+    /*
+     * Adds an occurrence of a term to its postings. A repeated position of the same document is
+     * ignored, keeping the offsets of its first occurrence
+     */
+    fn add_occurrence(&mut self, term: &str, doc_id: u32, pos: u32, offsets: Option<(u32, u32)>) {
+        let positional_postings = self.m_index.entry(term.to_string()).or_default();
+        let is_new_document = positional_postings.last().map_or(true, |posting| posting.get_doc_id() != doc_id);
+        if is_new_document {
+            positional_postings.push(PositionalPosting::new(doc_id));
+        } else if positional_postings.last().unwrap().get_last_position() == pos {
+            return;
+        }
+        let posting = positional_postings.last_mut().unwrap();
+        match offsets {
+            Some((start, end)) => posting.add_position_with_offsets(pos, start, end),
+            None => posting.add_position(pos),
         }
     }
 
@@ -0,0 +1,75 @@
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+/*
+ * Reads a file from a position of its own using positional reads (pread), without moving the
+ * cursor the file's handle shares with every other reader. Any number of readers can therefore
+ * read one open file at the same time, from any number of threads
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct PositionalReader<'f> {
+    file: &'f File,
+    position: u64,
+}
+
+impl<'f> PositionalReader<'f> {
+    /*
+     * Constructs a reader starting at a position of a file
+     *
+     * # Arguments
+     *
+     * *`file` - The file to read
+     * *`position` - Byte position the first read starts at
+     */
+    pub fn new(file: &'f File, position: u64) -> PositionalReader<'f> {
+        PositionalReader { file, position }
+    }
+
+    /*
+     * Byte position the next read starts at
+     */
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buffer: &mut [u8], position: u64) -> io::Result<usize> {
+    use std::os::unix::fs::FileExt;
+    file.read_at(buffer, position)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buffer: &mut [u8], position: u64) -> io::Result<usize> {
+    // seek_read also moves the handle's cursor, which no reader relies on
+    use std::os::windows::fs::FileExt;
+    file.seek_read(buffer, position)
+}
+
+impl<'f> Read for PositionalReader<'f> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let count = read_at(self.file, buffer, self.position)?;
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl<'f> Seek for PositionalReader<'f> {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match position {
+            SeekFrom::Start(position) => (position, 0),
+            SeekFrom::Current(offset) => (self.position, offset),
+            SeekFrom::End(offset) => (self.file.metadata()?.len(), offset),
+        };
+        match base.checked_add_signed(offset) {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "Seek before the start of the file")),
+        }
+    }
+}
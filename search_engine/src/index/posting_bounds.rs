@@ -0,0 +1,91 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian};
+use std::io::prelude::*;
+use std::io;
+
+/*
+ * Number of postings summarized by each block of bounds
+ */
+pub const BOUNDS_BLOCK_SIZE: usize = 64;
+
+/*
+ * Size in bytes of one PostingBounds record in bounds.bin
+ */
+pub const BOUNDS_RECORD_SIZE: u64 = 4 + 8 + 8 + 8 + 8;
+
+/*
+ * Last document id, position of the first posting in postings.bin and bounds of every block of a
+ * posting list
+ */
+pub type BlockBounds = Vec<(u32, u64, PostingBounds)>;
+
+/*
+ * Extremes of the statistics found among a run of postings. A scorer whose term weight grows with
+ * the term frequency and shrinks with the document statistics can bound the score of any posting
+ * of the run from these values alone
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PostingBounds {
+    pub max_term_frequency: u32,
+    pub min_doc_weight: f64,
+    pub min_doc_length: u64,
+    pub min_byte_size: u64,
+    pub min_avg_tftd: f64,
+}
+
+impl Default for PostingBounds {
+    fn default() -> PostingBounds {
+        PostingBounds::new()
+    }
+}
+
+impl PostingBounds {
+    /*
+     * Constructs the bounds of an empty run of postings
+     */
+    pub fn new() -> PostingBounds {
+        PostingBounds {
+            max_term_frequency: 0,
+            min_doc_weight: f64::INFINITY,
+            min_doc_length: u64::MAX,
+            min_byte_size: u64::MAX,
+            min_avg_tftd: f64::INFINITY,
+        }
+    }
+
+    /*
+     * Widens the bounds to cover another posting
+     *
+     * # Arguments
+     *
+     * *`term_frequency` - Frequency of the term in the posting's document
+     * *`doc_weight` - Euclidean weight of the document
+     * *`doc_length` - Number of distinct terms in the document
+     * *`byte_size` - Size of the document in bytes
+     * *`avg_tftd` - Average term frequency of the document
+     */
+    pub fn add(&mut self, term_frequency: u32, doc_weight: f64, doc_length: u64, byte_size: u64, avg_tftd: f64) {
+        self.max_term_frequency = self.max_term_frequency.max(term_frequency);
+        self.min_doc_weight = self.min_doc_weight.min(doc_weight);
+        self.min_doc_length = self.min_doc_length.min(doc_length);
+        self.min_byte_size = self.min_byte_size.min(byte_size);
+        self.min_avg_tftd = self.min_avg_tftd.min(avg_tftd);
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_u32::<BigEndian>(self.max_term_frequency)?;
+        writer.write_f64::<BigEndian>(self.min_doc_weight)?;
+        writer.write_u64::<BigEndian>(self.min_doc_length)?;
+        writer.write_u64::<BigEndian>(self.min_byte_size)?;
+        writer.write_f64::<BigEndian>(self.min_avg_tftd)
+    }
+
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<PostingBounds> {
+        Ok(PostingBounds {
+            max_term_frequency: reader.read_u32::<BigEndian>()?,
+            min_doc_weight: reader.read_f64::<BigEndian>()?,
+            min_doc_length: reader.read_u64::<BigEndian>()?,
+            min_byte_size: reader.read_u64::<BigEndian>()?,
+            min_avg_tftd: reader.read_f64::<BigEndian>()?,
+        })
+    }
+}
@@ -0,0 +1,89 @@
+use index::disk_inverted_index::DiskInvertedIndex;
+use index::disk_inverted_index::IndexReader;
+use index::posting_bounds::BlockBounds;
+use index::posting_bounds::BOUNDS_BLOCK_SIZE;
+
+/*
+ * Reads a term's postings one block at a time, in document ID order. Moving past whole blocks
+ * seeks straight to the next block needed, using the block positions kept in bounds.bin, so the
+ * postings of skipped blocks are never read nor decoded
+ */
+pub struct PostingsCursor {
+    index: DiskInvertedIndex,
+    document_frequency: u32,
+    blocks: Vec<(u32, u64)>,
+    block: usize,
+    postings: Vec<(u32, u32)>,
+    position: usize,
+}
+
+impl PostingsCursor {
+    /*
+     * Constructs a cursor at the first posting of a term
+     *
+     * # Arguments
+     *
+     * *`index` - The Disk Inverted Index holding the postings
+     * *`term` - The term whose postings are read
+     * *`blocks` - The term's block bounds, from get_posting_bounds
+     */
+    pub fn new(index: &DiskInvertedIndex, term: &str, blocks: &BlockBounds) -> PostingsCursor {
+        let mut cursor = PostingsCursor {
+            index: index.clone(),
+            document_frequency: index.get_document_frequency(term),
+            blocks: blocks.iter().map(|&(last_doc_id, position, _)| (last_doc_id, position)).collect(),
+            block: 0,
+            postings: Vec::new(),
+            position: 0,
+        };
+        if !cursor.blocks.is_empty() {
+            cursor.read_block(0);
+        }
+        cursor
+    }
+
+    /*
+     * Document ID of the current posting, or u32::MAX once every posting has been passed
+     */
+    pub fn doc_id(&self) -> u32 {
+        self.postings.get(self.position).map_or(u32::MAX, |posting| posting.0)
+    }
+
+    /*
+     * tf_td of the current posting
+     */
+    pub fn term_frequency(&self) -> u32 {
+        self.postings.get(self.position).map_or(0, |posting| posting.1)
+    }
+
+    /*
+     * Moves to the first posting whose document ID is at least target, reading only the block
+     * holding it
+     */
+    pub fn advance_to(&mut self, target: u32) {
+        if self.doc_id() >= target {
+            return;
+        }
+        let block = self.block + self.blocks[self.block..].partition_point(|&(last_doc_id, _)| last_doc_id < target);
+        if block >= self.blocks.len() {
+            self.block = self.blocks.len();
+            self.postings.clear();
+            self.position = 0;
+            return;
+        }
+        if block != self.block {
+            self.read_block(block);
+        }
+        let remaining = &self.postings[self.position..];
+        self.position += remaining.partition_point(|posting| posting.0 < target);
+    }
+
+    fn read_block(&mut self, block: usize) {
+        let previous_doc_id = if block == 0 { 0 } else { self.blocks[block - 1].0 };
+        let first_posting = (block * BOUNDS_BLOCK_SIZE) as u32;
+        let count = (self.document_frequency - first_posting).min(BOUNDS_BLOCK_SIZE as u32);
+        self.postings = self.index.read_postings_block(self.blocks[block].1, previous_doc_id, count);
+        self.block = block;
+        self.position = 0;
+    }
+}
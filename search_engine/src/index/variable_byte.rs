@@ -1,5 +1,5 @@
-use std::fs::File;
 use std::io::prelude::*;
+use std::io::SeekFrom;
 
 pub fn encode(mut number: u32) -> Vec<u8> {
     let mut bytes : Vec<u8> = Vec::new(); 
@@ -19,11 +19,21 @@ pub fn prepend(stuff_to_prepend: &mut Vec<u8>, prepend: &mut Vec<u8>) {
     prepend.append(stuff_to_prepend);
 }
 
-pub fn decode(mut file: &File) -> Option<(u32, u32)> {
+pub fn decode<R: Read + Seek>(mut file: R) -> Option<(u32, u32)> {
     let mut number : u32 = 0;
     let mut file_buf = [0; 5]; // At most 5 bytes.
-    file.read_exact(&mut file_buf);
-    let file_buf_iter = file_buf.into_iter();
+    let mut bytes_read = 0;
+    while bytes_read < file_buf.len() {
+        match file.read(&mut file_buf[bytes_read..]) {
+            Ok(0) | Err(_) => break,
+            Ok(count) => bytes_read += count,
+        }
+    }
+    // Callers step back from 5 bytes past the start of the number, even near the end of the file
+    if bytes_read < file_buf.len() {
+        file.seek(SeekFrom::Current((file_buf.len() - bytes_read) as i64)).ok()?;
+    }
+    let file_buf_iter = file_buf[..bytes_read].iter();
     let mut counter = 1; // We read at least one byte
     for byte in file_buf_iter {
         if *byte < 128 {
@@ -38,6 +48,30 @@ pub fn decode(mut file: &File) -> Option<(u32, u32)> {
     None
 }
 
+/*
+ * Decodes a variable byte encoded number from the start of a byte slice
+ *
+ * # Arguments
+ *
+ * *`bytes` - The encoded bytes
+ *
+ * # Returns
+ *
+ * The decoded number and the number of bytes it used, or None if the slice ends first
+ */
+pub fn decode_bytes(bytes: &[u8]) -> Option<(u32, usize)> {
+    let mut number : u32 = 0;
+    for (i, byte) in bytes.iter().take(5).enumerate() {
+        if *byte < 128 {
+            number = 128 * number + *byte as u32;
+        } else {
+            number = 128 * number + (*byte as u32 - 128);
+            return Some((number, i + 1));
+        }
+    }
+    None
+}
+
 // Example Usage:
 // println!("Variable Byte Encoded 24: {:?}", variable_byte::vb_encode(1337));
 // let mut test_buf = &variable_byte::encode(1337)[..];
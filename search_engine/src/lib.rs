@@ -1,13 +1,19 @@
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[macro_use]
 extern crate serde_json;
 extern crate stemmer;
 extern crate byteorder;
+extern crate flate2;
+#[macro_use]
+extern crate log;
+extern crate tiny_http;
 
 pub mod index;
 pub mod parser;
 pub mod paths;
 pub mod processor;
 pub mod reader;
-pub mod classifier;
\ No newline at end of file
+pub mod classifier;
+pub mod server;
\ No newline at end of file
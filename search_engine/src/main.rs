@@ -1,36 +1,183 @@
+extern crate log;
 extern crate search_engine;
 extern crate serde;
+#[macro_use]
 extern crate serde_json;
 extern crate stemmer;
+extern crate tiny_http;
 
 use search_engine::index::disk_inverted_index::DiskInvertedIndex;
 use search_engine::index::disk_inverted_index::IndexReader;
 use search_engine::parser::document_parser;
 use search_engine::paths::search_engine_paths;
+use search_engine::processor::evaluation;
+use search_engine::processor::federated_search;
+use search_engine::processor::federated_search::SearchTarget;
 use search_engine::processor::query_processor;
+use search_engine::processor::proximity::ProximityBoost;
+use search_engine::processor::relevance_feedback;
+use search_engine::processor::relevance_feedback::Feedback;
+use search_engine::processor::scorer::Bm25PlusScorer;
+use search_engine::processor::scorer::OkapiScorer;
+use search_engine::processor::scorer::Scorer;
+use search_engine::processor::scorer::ScorerRegistry;
+use search_engine::processor::snippet::Highlight;
+use search_engine::processor::search_results::SearchResults;
+use search_engine::reader::arguments;
+use search_engine::reader::arguments::Command;
 use search_engine::reader::read_file;
 use search_engine::reader::user_input;
+use search_engine::server::search_server::SearchServer;
+use search_engine::server::search_server::ServedIndex;
 use search_engine::index::positional_inverted_index::PositionalInvertedIndex;
 use search_engine::index::k_gram_index::KGramIndex;
+use search_engine::index::index_set::IndexSet;
 use search_engine::classifier::bayesian_classifier::BayesianClassifier;
 use search_engine::classifier::rocchio_classifier::RocchioClassifier;
 use search_engine::classifier::classifier::Classifier;
+use search_engine::classifier::knn_classifier::KnnClassifier;
+use search_engine::classifier::knn_classifier::Voting;
+use search_engine::classifier::knn_classifier::DEFAULT_NEIGHBOURS;
+use search_engine::classifier::model::ClassifierModel;
+use search_engine::classifier::classifier::TermComponentScore;
+use search_engine::classifier::training_set::TrainingSet;
+use log::LevelFilter;
+use log::Log;
+use log::Metadata;
+use log::Record;
 use std::collections::HashMap;
+use std::env;
 use std::fs::File;
 use std::fs::read_dir;
 use std::fs::remove_file;
 use std::io::prelude::*;
 use std::path::*;
+use std::process;
+use std::sync::Arc;
+use std::time::Instant;
+
+/*
+ * Exit code of a command that failed, such as a query that cannot be parsed or a missing index
+ */
+const EXIT_FAILURE: i32 = 1;
+
+/*
+ * Exit code of a command given invalid arguments
+ */
+const EXIT_USAGE: i32 = 2;
+
+/*
+ * Environment variable holding the most verbose level of diagnostics written to stderr, one of
+ * off, error, warn, info, debug or trace
+ */
+const LOG_VARIABLE: &str = "SEARCH_ENGINE_LOG";
+
+/*
+ * Writes the diagnostics logged by the library to stderr, so stdout only holds results
+ */
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/*
+ * Installs the stderr logger
+ *
+ * # Arguments
+ *
+ * *`default_level` - The level used unless SEARCH_ENGINE_LOG names another
+ */
+fn init_logging(default_level: LevelFilter) {
+    let level = env::var(LOG_VARIABLE)
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(default_level);
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
 
 fn main() {
+    let arguments: Vec<String> = env::args().skip(1).collect();
+    let command = match arguments.first().map(|command| command.as_str()) {
+        None | Some("repl") => {
+            // Progress such as the time taken to index a directory is shown in interactive mode
+            init_logging(LevelFilter::Info);
+            repl();
+            return;
+        },
+        Some("help") | Some("--help") | Some("-h") => {
+            print!("{}", usage());
+            return;
+        },
+        Some("index") => arguments::parse_command(&arguments, &["format"], &["no-kgram"]),
+        Some("query") => arguments::parse_command(
+            &arguments,
+            &["format", "mode", "scheme", "limit", "offset", "snippet"],
+            &["proximity", "feedback"],
+        ),
+        Some("classify") => arguments::parse_command(&arguments, &["format", "method", "training", "labels", "model", "save-model", "k", "voting"], &[]),
+        Some("stats") => arguments::parse_command(&arguments, &["format"], &[]),
+        Some("evaluate") => arguments::parse_command(&arguments, &[], &[]),
+        Some("serve") => arguments::parse_command(&arguments, &["address", "threads"], &[]),
+        Some(_) => Err("Unknown command"),
+    };
+    let command = match command {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, usage());
+            process::exit(EXIT_USAGE);
+        },
+    };
+    init_logging(LevelFilter::Warn);
+
+    let result = match command.name.as_str() {
+        "index" => index_command(&command),
+        "query" => query_command(&command),
+        "classify" => classify_command(&command),
+        "stats" => stats_command(&command),
+        "serve" => serve_command(&command),
+        _ => evaluate_command(&command),
+    };
+    if let Err((code, error)) = result {
+        eprintln!("{}", error);
+        if code == EXIT_USAGE {
+            eprint!("\n{}", usage());
+        }
+        process::exit(code);
+    }
+}
+
+/*
+ * Runs the interactive mode, which asks for a directory, then to build, query or classify it
+ * through ':' commands
+ */
+fn repl() {
     let mut index_path = search_engine_paths::initialize_path();
 
-    let mut scheme = "default";
+    let mut scorers = ScorerRegistry::new();
+    let mut scheme = String::from("default");
     let mut menu: i32;
     let mut input: String;
     let mut change: bool;
     let mut function = "";
     let mut ranked_retrieval : bool = false;
+    let mut proximity : Option<ProximityBoost> = None;
+    let mut feedback : Option<Feedback> = None;
+    let mut last_query = String::new();
     let mut classifier = "rocchio";
 
     let mut index = PositionalInvertedIndex::new();
@@ -82,64 +229,16 @@ fn main() {
     
     if function == "build" {
         k_gram_index.enable_k_gram();
-
-        let directory = read_dir(index_path.clone());
-        if let Ok(dir) = directory {
-            for entry in dir {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    let extension = "bin";
-                    if path.extension().unwrap() == extension {
-                        remove_file(path).unwrap();
-                    }
-                }
-            }
-        }
-
         println!("Building Index...");
-        // Builds new index if directory was changed
-        
-        // Links document ID's to file names
-        let id_file = build_index(&index_path, &mut index, &mut k_gram_index);
-
-        let serialized_id_file = serde_json::to_string(&id_file).unwrap();
-        let serialized_kgramindex = serde_json::to_string(&k_gram_index).unwrap();
-
-        
-        let id_file_filename = format!("{}/{}", index_path.display(), "id_file.bin");
-        let kgram_filename = format!("{}/{}", index_path.display(), "kgram.bin");
-
-        //  TODO: BUILD INDEX FILE HERE
-        
-        let mut id_file_file = match File::create(&id_file_filename) {
-            Err(why) => panic!("Couldn't create {} - {}", &id_file_filename, why),
-            Ok(file) => file,
-        };
-
-        let mut kgram_file = match File::create(&kgram_filename) {
-            Err(why) => panic!("Couldn't create {} - {}", &kgram_filename, why),
-            Ok(file) => file,
-        };
-
-        id_file_file.write(&serialized_id_file.as_bytes()).expect("Failed to write id file");
-        kgram_file.write(&serialized_kgramindex.as_bytes()).expect("Failed to write kgram file");
-
+        // Document ID's are linked to file names by the doc table written with the index
+        save_index(&index_path, &mut index, &mut k_gram_index);
     }
     
     if function == "query" {
-        let disk_inverted_index_path = index_path.clone();
-        let disk_inverted_index = DiskInvertedIndex::new(&disk_inverted_index_path.to_str().unwrap());
+        let disk_inverted_index = DiskInvertedIndex::new(&index_path);
 
-        let id_file_filename = format!("{}/{}", index_path.display(), "id_file.bin");
         let kgram_filename = format!("{}/{}", index_path.display(), "kgram.bin");
 
-        let mut id_file_file = File::open(id_file_filename).unwrap();
-
-        let mut id_file_contents = String::new();
-        id_file_file.read_to_string(&mut id_file_contents).expect("Failed to read id file");
-
-        let id_file : HashMap<u32, String> = serde_json::from_str(&id_file_contents).unwrap();
-
         let mut kgram_file = File::open(kgram_filename).unwrap();
 
         let mut kgram_file_contents = String::new();
@@ -173,6 +272,12 @@ fn main() {
             if ranked_retrieval {
                 println!("Ranked"); 
                 println!("Weighting Scheme: {}",scheme);
+                if proximity.is_some() {
+                    println!("Proximity Boost: On");
+                }
+                if feedback.is_some() {
+                    println!("Pseudo-Relevance Feedback: On");
+                }
             } else {
                 println!("Boolean"); 
             }
@@ -181,12 +286,15 @@ fn main() {
             println!();
 
             if !input.starts_with(":") {
-                process_query(ranked_retrieval, scheme, &input, &disk_inverted_index, &k_gram_index, &id_file);
+                let scorer = scorers.get(&scheme).expect("Weighting scheme is not registered");
+                let options = search_options(ranked_retrieval, scorer, proximity.as_ref());
+                process_query(&options, feedback.as_ref(), None, &input, &disk_inverted_index, &k_gram_index);
+                last_query = input.clone();
             } else {
                 if input == ":q" || input == ":quit" {
                    return (); 
                 } else if input.starts_with(":o ") || input.starts_with(":open ") {
-                    open_file(&index_path, input.as_str());
+                    open_document(&disk_inverted_index, input.as_str());
                 } else if input.starts_with(":s ") || input.starts_with(":stem ") {
                     stem_term(input.as_str());
                 } else if input.starts_with(":i ") || input.starts_with(":index ") {
@@ -195,14 +303,22 @@ fn main() {
                     ranked_retrieval = true; 
                 } else if input == ":mode b" || input == ":mode boolean" {
                     ranked_retrieval = false; 
-                } else if input == ":scheme d" || input == ":scheme default" {
-                    scheme = "default"; 
-                } else if input == ":scheme t" || input == ":scheme tfidf" {
-                    scheme = "tfidf"; 
-                } else if input == ":scheme o" || input == ":scheme okapi" {
-                    scheme = "okapi"; 
-                } else if input == ":scheme w" || input == ":scheme wacky" {
-                    scheme = "wacky"; 
+                } else if input.starts_with(":scheme ") {
+                    select_scheme(&scorers, &mut scheme, input.as_str());
+                } else if input.starts_with(":bm25 ") {
+                    tune_bm25(&mut scorers, input.as_str());
+                } else if input == ":proximity on" {
+                    proximity = Some(ProximityBoost::new());
+                } else if input == ":proximity off" {
+                    proximity = None;
+                } else if input == ":feedback on" {
+                    feedback = Some(Feedback::default());
+                } else if input == ":feedback off" {
+                    feedback = None;
+                } else if input.starts_with(":relevant ") {
+                    let scorer = scorers.get(&scheme).expect("Weighting scheme is not registered");
+                    let options = search_options(true, scorer, proximity.as_ref());
+                    mark_relevant(&options, &last_query, input.as_str(), &disk_inverted_index, &k_gram_index);
                 } else if input == ":v" || input == ":vocab" {
                     print_vocab(&disk_inverted_index);
                 } else if input == ":k" || input == ":kgram" {
@@ -217,61 +333,40 @@ fn main() {
     }
 
     if function == "classify" {
-        let index_name = index_path.file_name().expect("Error retrieving index name");
-        if index_name != "disputed" {
-            println!("Cannot use documents that are already classified");
-            return;
-        }
-        let mut index_path_copy = index_path.clone();
-        index_path_copy.pop();
-        index_path_copy.push("disputed");
-        let disputed_path = String::from(index_path_copy.to_str().unwrap());
-        index_path_copy.pop();
-        index_path_copy.push("hamilton");
-        let hamilton_path = String::from(index_path_copy.to_str().unwrap());
-        index_path_copy.pop();
-        index_path_copy.push("jay");
-        let jay_path = String::from(index_path_copy.to_str().unwrap());
-        index_path_copy.pop();
-        index_path_copy.push("madison");
-        let madison_path = String::from(index_path_copy.to_str().unwrap());
-
-        // println!("Building disputed disk index from {}", disputed_path);
-        let disputed_index = DiskInvertedIndex::new(&disputed_path);
-        // println!("Building Hamilton disk index from {}", hamilton_path);
-        let hamilton_index = DiskInvertedIndex::new(&hamilton_path);
-        // println!("Building Jay disk index from {}", jay_path);
-        let jay_index = DiskInvertedIndex::new(&jay_path);
-        // println!("Building Madison disk index from {}", madison_path);
-        let madison_index = DiskInvertedIndex::new(&madison_path);
-
-        let bayesian_classifier = BayesianClassifier::new(&disputed_index, &hamilton_index, &jay_index, &madison_index);
-        let rocchio_classifier = RocchioClassifier::new(&disputed_index, &hamilton_index, &jay_index, &madison_index);
-
-        let id_file_filename = format!("{}/{}", index_path.display(), "id_file.bin");
-        let mut id_file_file = File::open(id_file_filename).unwrap();
-
-        let mut id_file_contents = String::new();
-        id_file_file.read_to_string(&mut id_file_contents).expect("Failed to read id file");
-
-        let id_file_map : HashMap<u32, String> = serde_json::from_str(&id_file_contents).unwrap();
-
-        let mut file_id_map : HashMap<String, u32> = HashMap::new();
-
-        for id_file in id_file_map {
-            let mut full_file_path = PathBuf::new();
-            full_file_path.push(id_file.1);
-            let doc_id = id_file.0;
-
-            let file_name = full_file_path.file_name().expect("File not found");
-
-            let file_name_string = file_name.to_str().expect("Error converting to string");
-
-            file_id_map.insert(file_name_string.to_string(), doc_id);
+        // The classes are the indexed directories next to the one being classified
+        let parent = index_path.parent().expect("Error retrieving parent directory");
+        let training = match TrainingSet::from_subdirectories(parent, Some(&index_path)) {
+            Ok(training) => training,
+            Err(error) => {
+                println!("{}", error);
+                return;
+            },
+        };
+        let documents_index = DiskInvertedIndex::new(&index_path);
+        println!("Classes: {}", training.get_labels().join(", "));
+
+        let bayesian_classifier = BayesianClassifier::new(&documents_index, &training);
+        let rocchio_classifier = RocchioClassifier::new(&documents_index, &training);
+        let knn_classifier = KnnClassifier::new(&documents_index, &training, DEFAULT_NEIGHBOURS, Voting::Majority);
+
+        // The searched indexes are kept in an IndexSet, so :reopen picks up a rebuild
+        let index_name = index_path.file_name().and_then(|name| name.to_str()).unwrap_or("documents").to_string();
+        let searched = IndexSet::new();
+        searched.insert(&index_name, documents_index.clone(), Arc::new(KGramIndex::read(&index_path)));
+        let mut searched_names = vec![index_name];
+        for class in training.get_classes() {
+            let kgram = Arc::new(KGramIndex::read(class.get_index().get_directory()));
+            searched.insert(class.get_label(), class.get_index().clone(), kgram);
+            searched_names.push(class.get_label().to_string());
         }
 
         loop {
             println!("Classifier: {}", classifier);
+            let selected_classifier: &dyn Classifier = match classifier {
+                "rocchio" => &rocchio_classifier,
+                "bayesian" => &bayesian_classifier,
+                _ => &knn_classifier,
+            };
             print!("Input Command: ");
             input = user_input::read_input_line();
             println!();
@@ -287,18 +382,31 @@ fn main() {
                     classifier = "rocchio"; 
                 } else if input == ":c b" || input == ":c bayesian" || input == ":classifier bayesian" {
                     classifier = "bayesian"; 
+                } else if input == ":c k" || input == ":c knn" || input == ":classifier knn" {
+                    classifier = "knn";
                 } else if input == ":c all" || input == ":classify all" {
-                    classify_all(classifier, &bayesian_classifier, &rocchio_classifier, &file_id_map); 
+                    classify_all(selected_classifier, &documents_index); 
+                } else if let Some(query) = input.strip_prefix(":search ") {
+                    let scorer = scorers.get(&scheme).expect("Weighting scheme is not registered");
+                    let options = search_options(true, scorer, proximity.as_ref());
+                    federated_query(&options, query, &searched, &searched_names);
+                } else if input == ":reopen" {
+                    for name in searched_names.iter() {
+                        match searched.reopen(name) {
+                            Ok(_) => println!("Reopened {}", name),
+                            Err(error) => println!("Cannot reopen {}: {}", name, error),
+                        }
+                    }
                 } else if input.starts_with(":c ") || input.starts_with(":classify ") {
-                    classify_document(classifier, &bayesian_classifier, &rocchio_classifier, &input, &file_id_map);
+                    classify_document(selected_classifier, &input, &documents_index);
                 } else if input.starts_with(":d ") || input.starts_with(":discriminating_vocab ") {
-                    print_discriminating_vocab(&bayesian_classifier, &input);
+                    print_discriminating_vocab(&training, &input);
                 } else if input.starts_with(":centroid_vectors ") {
                     print_centroid_vectors(&rocchio_classifier);
                 } else if input == ":v" || input == ":vocab" {
-                    print_vocab(&disputed_index);
+                    print_vocab(&documents_index);
                 } else if input == ":va" || input == ":vocab all" {
-                    print_all_vocab(classifier, &bayesian_classifier, &rocchio_classifier);
+                    print_all_vocab(selected_classifier);
                 }
             }
         }
@@ -306,7 +414,8 @@ fn main() {
 }
 
 /*
- * Builds the Positional Inverted Index given the directory containing the files it will read
+ * Builds the index of a directory, replacing the index files of any previous build, and saves its
+ * K Gram Index to kgram.bin
  * 
  * # Arguments
  * 
@@ -319,13 +428,599 @@ fn main() {
  * HashMap that contains the association between each Document ID and the file name
  * 
  */
-fn build_index(
-    index_path: &PathBuf,
+fn save_index(
+    index_path: &Path,
     index: &mut PositionalInvertedIndex,
     k_gram_index: &mut KGramIndex,) -> HashMap<u32, String> {
 
+    if let Ok(directory) = read_dir(index_path) {
+        for entry in directory.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|extension| extension == "bin") {
+                remove_file(path).expect("Failed to remove index file");
+            }
+        }
+    }
+
     let directory = index_path.to_str().expect("Not a valid directory");
-    document_parser::build_index(directory.to_string(), index, k_gram_index)
+    let id_file = document_parser::build_index(directory.to_string(), index, k_gram_index);
+
+    let serialized_kgramindex = serde_json::to_string(&k_gram_index).unwrap();
+    let kgram_filename = format!("{}/{}", index_path.display(), "kgram.bin");
+    let mut kgram_file = match File::create(&kgram_filename) {
+        Err(why) => panic!("Couldn't create {} - {}", &kgram_filename, why),
+        Ok(file) => file,
+    };
+    kgram_file.write_all(serialized_kgramindex.as_bytes()).expect("Failed to write kgram file");
+    id_file
+}
+
+/*
+ * The subcommands of the command line interface
+ */
+fn usage() -> String {
+    let mut usage = String::new();
+    usage.push_str("Usage: search_engine COMMAND [OPTIONS]\n");
+    usage.push('\n');
+    usage.push_str("Commands:\n");
+    usage.push_str("  repl                            Interactive mode, also used when no command is given\n");
+    usage.push_str("  index DIRECTORY [--no-kgram]    Builds the index of the files in a directory\n");
+    usage.push_str("  query DIRECTORY QUERY...        Prints the results of a query\n");
+    usage.push_str("      --mode ranked|boolean       Retrieval method (default ranked)\n");
+    usage.push_str("      --scheme NAME               Weighting scheme of ranked retrieval (default default)\n");
+    usage.push_str("      --limit N --offset N        Page of results returned (default the first 10)\n");
+    usage.push_str("      --snippet N                 Tokens in the snippet of every result, 0 for none (default 24)\n");
+    usage.push_str("      --proximity --feedback      Proximity boost and pseudo-relevance feedback\n");
+    usage.push_str("  classify DIRECTORY [FILE...]    Classifies the documents of a directory, by default all\n");
+    usage.push_str("      --method rocchio|bayes|knn  Classifier (default rocchio)\n");
+    usage.push_str("      --k N                       Neighbours voting in knn (default 5)\n");
+    usage.push_str("      --voting majority|weighted  How the neighbours vote in knn (default majority)\n");
+    usage.push_str("      --training DIRECTORY        Directory whose indexed subdirectories are the classes\n");
+    usage.push_str("                                  (default the parent of DIRECTORY), or with --labels the\n");
+    usage.push_str("                                  training index (default DIRECTORY)\n");
+    usage.push_str("      --labels FILE               Lines of 'FILE LABEL' giving the class of training documents\n");
+    usage.push_str("      --save-model FILE           Saves the trained classifier\n");
+    usage.push_str("      --model FILE                Classifies with a saved classifier instead of training one\n");
+    usage.push_str("  stats DIRECTORY                 Prints statistics of an index\n");
+    usage.push_str("  evaluate DIRECTORY QUERIES QRELS [K] [SCHEME...]\n");
+    usage.push_str("                                  Compares weighting schemes against relevance judgments\n");
+    usage.push_str("  serve DIRECTORY...              Answers search, suggest, document, classify and stats\n");
+    usage.push_str("                                  requests over HTTP, naming every index after its directory\n");
+    usage.push_str("      --address HOST:PORT         Address listened on (default 127.0.0.1:8080)\n");
+    usage.push_str("      --threads N                 Requests answered at the same time (default 4)\n");
+    usage.push('\n');
+    usage.push_str("index, query, classify and stats take --format human|json|ndjson (default human). With ndjson\n");
+    usage.push_str("a query prints one line per hit and classify one line per document\n");
+    usage.push_str(&format!("{} sets the diagnostics written to stderr: off, error, warn (default), info, debug\n", LOG_VARIABLE));
+    usage.push_str("or trace\n");
+    usage.push_str(&format!("Exit codes: 0 success, {} failure, {} invalid arguments\n", EXIT_FAILURE, EXIT_USAGE));
+    usage
+}
+
+/*
+ * Error of a command: its exit code and message
+ */
+type CommandError = (i32, String);
+
+fn usage_error(message: &str) -> CommandError {
+    (EXIT_USAGE, message.to_string())
+}
+
+fn failure(message: &str) -> CommandError {
+    (EXIT_FAILURE, message.to_string())
+}
+
+/*
+ * How a command prints its results
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    /*
+     * Text meant to be read
+     */
+    Human,
+
+    /*
+     * A single JSON value
+     */
+    Json,
+
+    /*
+     * One JSON value per line, such as one per hit of a query
+     */
+    Ndjson,
+}
+
+/*
+ * Reads the --format option of a command
+ *
+ * # Returns
+ *
+ * The output format, or an error if the format is unknown
+ */
+fn output_format(command: &Command) -> Result<Format, CommandError> {
+    match command.option("format").unwrap_or("human") {
+        "human" => Ok(Format::Human),
+        "json" => Ok(Format::Json),
+        "ndjson" => Ok(Format::Ndjson),
+        _ => Err(usage_error("Unknown format, use human, json or ndjson")),
+    }
+}
+
+/*
+ * The directory given as the first positional argument of a command
+ */
+fn directory_argument(command: &Command) -> Result<&str, CommandError> {
+    command
+        .positional
+        .first()
+        .map(|directory| directory.as_str())
+        .ok_or_else(|| usage_error("No directory given"))
+}
+
+/*
+ * Opens the index of a directory and the K Gram Index saved with it
+ *
+ * # Arguments
+ *
+ * *`directory` - The indexed directory
+ *
+ * # Returns
+ *
+ * The Disk Inverted Index and K Gram Index, or an error if the directory has not been indexed or
+ * its index must be rebuilt
+ */
+fn open_index(directory: &str) -> Result<(DiskInvertedIndex, KGramIndex), CommandError> {
+    let index = DiskInvertedIndex::open(directory)
+        .map_err(|error| failure(&format!("{} ({}), build it with 'search_engine index {}'", error, directory, directory)))?;
+    Ok((index, KGramIndex::read(directory)))
+}
+
+/*
+ * The 'index' subcommand: builds the index of a directory
+ */
+fn index_command(command: &Command) -> Result<(), CommandError> {
+    let format = output_format(command)?;
+    let directory = directory_argument(command)?;
+    let index_path = PathBuf::from(directory);
+    if !index_path.is_dir() {
+        return Err(failure(&format!("{} is not a directory", directory)));
+    }
+
+    let mut index = PositionalInvertedIndex::new();
+    let mut k_gram_index = KGramIndex::new();
+    if !command.flag("no-kgram") {
+        k_gram_index.enable_k_gram();
+    }
+    let start = Instant::now();
+    let id_file = save_index(&index_path, &mut index, &mut k_gram_index);
+    let elapsed = start.elapsed();
+    let terms = index.get_dictionary().len();
+
+    if format != Format::Human {
+        let record = json!({
+            "directory": directory,
+            "documents": id_file.len(),
+            "terms": terms,
+            "elapsed_ms": elapsed.as_secs_f64() * 1000.0,
+        });
+        println!("{}", record);
+    } else {
+        println!("Indexed {} documents and {} terms in {}", id_file.len(), terms, directory);
+    }
+    Ok(())
+}
+
+/*
+ * The 'query' subcommand: prints one page of the results of a query
+ */
+fn query_command(command: &Command) -> Result<(), CommandError> {
+    let format = output_format(command)?;
+    let directory = directory_argument(command)?;
+    let input = command.positional[1..].join(" ");
+    if input.trim().is_empty() {
+        return Err(usage_error("No query given"));
+    }
+    let ranked_retrieval = match command.option("mode").unwrap_or("ranked") {
+        "ranked" | "r" => true,
+        "boolean" | "b" => false,
+        _ => return Err(usage_error("Unknown mode, use ranked or boolean")),
+    };
+    let scorers = ScorerRegistry::new();
+    let scheme = command.option("scheme").unwrap_or("default");
+    let scorer = scorers
+        .get(scheme)
+        .map_err(|error| usage_error(&format!("{} - Available schemes: {}", error, scorers.names().join(", "))))?;
+    let proximity = if command.flag("proximity") { Some(ProximityBoost::new()) } else { None };
+    let feedback = if command.flag("feedback") { Some(Feedback::default()) } else { None };
+
+    let mut options = query_processor::SearchOptions::new(ranked_retrieval, scorer);
+    options.proximity = proximity.as_ref();
+    options.limit = command.parse_option("limit", options.limit).map_err(usage_error)?;
+    options.offset = command.parse_option("offset", options.offset).map_err(usage_error)?;
+    options.snippet_length = command.parse_option("snippet", options.snippet_length).map_err(usage_error)?;
+
+    let (index, k_gram_index) = open_index(directory)?;
+    let (results, expansion) = run_query(&options, feedback.as_ref(), None, &input, &index, &k_gram_index)
+        .map_err(|error| failure(&format!("Invalid Query: {}", error)))?;
+
+    if format == Format::Ndjson {
+        for (rank, hit) in results.hits.iter().enumerate() {
+            let mut record = serde_json::to_value(hit).expect("Failed to serialize hit");
+            record["rank"] = json!(results.offset + rank + 1);
+            println!("{}", record);
+        }
+    } else if format == Format::Json {
+        let record = json!({
+            "query": input,
+            "mode": if ranked_retrieval { "ranked" } else { "boolean" },
+            "scheme": if ranked_retrieval { Some(scheme) } else { None },
+            "expansion": expansion,
+            "total": results.total,
+            "total_is_lower_bound": results.total_is_lower_bound,
+            "offset": results.offset,
+            "limit": results.limit,
+            "elapsed_ms": results.elapsed.as_secs_f64() * 1000.0,
+            "hits": results.hits,
+        });
+        println!("{}", record);
+    } else {
+        print_results(&results, &expansion);
+    }
+    Ok(())
+}
+
+/*
+ * The 'classify' subcommand: prints the class of documents of a directory. The classes are read
+ * from a label file over a training index, or are the indexed subdirectories of a training
+ * directory, by default the directories next to the classified one. A model saved by an earlier
+ * run can be used instead of training again
+ */
+fn classify_command(command: &Command) -> Result<(), CommandError> {
+    let format = output_format(command)?;
+    let directory = directory_argument(command)?;
+    let method = command.option("method").unwrap_or("rocchio");
+    if !["rocchio", "bayes", "bayesian", "knn"].contains(&method) {
+        return Err(usage_error("Unknown method, use rocchio, bayes or knn"));
+    }
+
+    let (documents_index, _) = open_index(directory)?;
+    let mut model = match command.option("model") {
+        Some(model) => {
+            if ["method", "training", "labels"].iter().any(|name| command.option(name).is_some()) {
+                return Err(usage_error("--model cannot be combined with --method, --training or --labels"));
+            }
+            ClassifierModel::load(Path::new(model)).map_err(failure)?
+        },
+        None => {
+            let training = match command.option("labels") {
+                Some(labels) => {
+                    let training_index = match command.option("training") {
+                        Some(training_directory) => open_index(training_directory)?.0,
+                        None => documents_index.clone(),
+                    };
+                    TrainingSet::from_label_file(&training_index, Path::new(labels)).map_err(failure)?
+                },
+                None => {
+                    let parent = Path::new(directory).parent().unwrap_or_else(|| Path::new(""));
+                    let training_directory = command.option("training").map(Path::new).unwrap_or(parent);
+                    TrainingSet::from_subdirectories(training_directory, Some(Path::new(directory))).map_err(failure)?
+                },
+            };
+            ClassifierModel::fit(method, &training).map_err(failure)?
+        },
+    };
+    if command.option("k").is_some() || command.option("voting").is_some() {
+        match model {
+            ClassifierModel::Knn(ref mut model) => {
+                let k = command.parse_option("k", model.get_k()).map_err(usage_error)?;
+                let voting = command.parse_option("voting", model.get_voting()).map_err(usage_error)?;
+                model.set_neighbours(k, voting);
+            },
+            _ => return Err(usage_error("--k and --voting only apply to the knn method")),
+        }
+    }
+    if let Some(path) = command.option("save-model") {
+        model.save(Path::new(path)).map_err(failure)?;
+    }
+    let method = model.get_method();
+
+    let mut files: Vec<String> = command.positional[1..].to_vec();
+    if files.is_empty() {
+        for doc_id in 0..documents_index.get_num_documents().unwrap_or(0) {
+            let path = documents_index.get_document_path(doc_id).map_err(failure)?;
+            files.push(Path::new(&path).file_name().and_then(|name| name.to_str()).unwrap_or(&path).to_string());
+        }
+    }
+    let mut doc_ids: Vec<u32> = Vec::new();
+    for file in files.iter() {
+        match documents_index.get_document_id(file) {
+            Some(doc_id) => doc_ids.push(doc_id),
+            None => return Err(failure(&format!("{} is not in the index", file))),
+        }
+    }
+
+    // Training is done once, every document is then scored against the model
+    let classifier = model.classifier(&documents_index);
+
+    let mut records = Vec::new();
+    for (file, doc_id) in files.iter().zip(doc_ids) {
+        let classification = classifier.classify(doc_id);
+        if format == Format::Human {
+            println!("{} was classified as {}", file, classification.label);
+            continue;
+        }
+        let mut scores = serde_json::Map::new();
+        for score in classification.scores.iter() {
+            scores.insert(score.label.clone(), json!(score.score));
+        }
+        let record = json!({
+            "file": file,
+            "doc_id": doc_id,
+            "method": method,
+            "label": classification.label,
+            "scores": scores,
+        });
+        if format == Format::Ndjson {
+            println!("{}", record);
+        } else {
+            records.push(record);
+        }
+    }
+    if format == Format::Json {
+        println!("{}", serde_json::Value::Array(records));
+    }
+    Ok(())
+}
+
+/*
+ * The 'stats' subcommand: prints the size of an index
+ */
+fn stats_command(command: &Command) -> Result<(), CommandError> {
+    let format = output_format(command)?;
+    let directory = directory_argument(command)?;
+    let (index, _) = open_index(directory)?;
+
+    let documents = index.get_num_documents().unwrap_or(0);
+    let terms = index.get_term_count();
+    let tokens = index.get_total_term_frequency();
+    let average_document_length = if documents > 0 {
+        index.get_document_weights(0).map_err(failure)?.0
+    } else {
+        0.0
+    };
+    let mut index_bytes = 0;
+    for entry in read_dir(directory).map_err(|_| failure("Failed to read directory"))?.flatten() {
+        if entry.path().extension().is_some_and(|extension| extension == "bin") {
+            index_bytes += entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        }
+    }
+
+    if format != Format::Human {
+        let record = json!({
+            "directory": directory,
+            "documents": documents,
+            "terms": terms,
+            "tokens": tokens,
+            "average_document_length": average_document_length,
+            "index_bytes": index_bytes,
+        });
+        println!("{}", record);
+    } else {
+        println!("Directory: {}", directory);
+        println!("Documents: {}", documents);
+        println!("Terms: {}", terms);
+        println!("Tokens: {}", tokens);
+        println!("Average Document Length: {:.2} tokens", average_document_length);
+        println!("Index Size: {} bytes", index_bytes);
+    }
+    Ok(())
+}
+
+/*
+ * The 'serve' subcommand: answers requests over HTTP for the indexes of one or more directories
+ * until the process is stopped
+ */
+fn serve_command(command: &Command) -> Result<(), CommandError> {
+    if command.positional.is_empty() {
+        return Err(usage_error("No directory given"));
+    }
+    let address = command.option("address").unwrap_or("127.0.0.1:8080");
+    let threads = command.parse_option("threads", 4usize).map_err(usage_error)?;
+
+    let mut indexes = Vec::new();
+    for directory in command.positional.iter() {
+        let name = Path::new(directory)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(directory.as_str());
+        if indexes.iter().any(|served: &ServedIndex| served.get_name() == name) {
+            return Err(usage_error(&format!("Two directories are named {}", name)));
+        }
+        let (index, k_gram_index) = open_index(directory)?;
+        indexes.push(ServedIndex::new(name, index, k_gram_index));
+    }
+    let names: Vec<String> = indexes.iter().map(|served| served.get_name().to_string()).collect();
+
+    let http = tiny_http::Server::http(address).map_err(|error| failure(&format!("Cannot listen on {}: {}", address, error)))?;
+    println!("Serving {} on http://{}", names.join(", "), address);
+    SearchServer::new(indexes).serve(&http, threads);
+    Ok(())
+}
+
+/*
+ * The 'evaluate' subcommand: runs the queries of a query file under several weighting schemes and
+ * prints their effectiveness against a qrels file, per query and averaged
+ *
+ * # Arguments
+ *
+ * *`command` - DIRECTORY QUERIES QRELS [K] [SCHEME...], where K (default 10) is the cutoff of P@k
+ * and nDCG@k and the schemes default to default, tfidf, okapi and wacky
+ */
+fn evaluate_command(command: &Command) -> Result<(), CommandError> {
+    let arguments = &command.positional;
+    if arguments.len() < 3 {
+        return Err(usage_error("evaluate needs a directory, a query file and a qrels file"));
+    }
+    let (k, schemes) = match arguments.get(3).map(|k| k.parse::<usize>()) {
+        Some(Ok(k)) => (k, &arguments[4..]),
+        _ => (10, &arguments[3..]),
+    };
+    let mut schemes: Vec<&str> = schemes.iter().map(|scheme| scheme.as_str()).collect();
+    if schemes.is_empty() {
+        schemes = vec!["default", "tfidf", "okapi", "wacky"];
+    }
+
+    let topics = evaluation::read_topics(&arguments[1]).map_err(failure)?;
+    let qrels = evaluation::read_qrels(&arguments[2]).map_err(failure)?;
+    let (index, k_gram_index) = open_index(&arguments[0])?;
+
+    let scorers = ScorerRegistry::new();
+    let evaluations = evaluation::compare_schemes(&scorers, &schemes, &topics, &qrels, &index, &k_gram_index, k).map_err(failure)?;
+
+    let header = format!("{:<16}{:>8}{:>8}{:>8}{:>9}{:>8}", "", "AP", format!("P@{}", k), "R-Prec", format!("nDCG@{}", k), "RR");
+    for evaluation in evaluations.iter() {
+        println!("Weighting Scheme: {}", evaluation.scheme);
+        println!("{}{:>6}{:>6}{:>10}", header, "Rel", "Ret", "ms");
+        for query in evaluation.queries.iter() {
+            println!(
+                "{:<16}{:>8.4}{:>8.4}{:>8.4}{:>9.4}{:>8.4}{:>6}{:>6}{:>10.3}",
+                query.query_id,
+                query.average_precision,
+                query.precision_at_k,
+                query.r_precision,
+                query.ndcg_at_k,
+                query.reciprocal_rank,
+                query.relevant,
+                query.relevant_retrieved,
+                query.elapsed.as_secs_f64() * 1000.0
+            );
+            if let Some(error) = query.error {
+                println!("{:<16}Query not run: {}", "", error);
+            }
+        }
+        println!();
+    }
+
+    println!("{} Queries judged", evaluations.first().map_or(0, |evaluation| evaluation.queries.len()));
+    let failed = evaluations.first().map_or(0, |evaluation| evaluation.get_failed_queries().len());
+    if failed > 0 {
+        println!("{} Queries could not be parsed and count as retrieving nothing", failed);
+    }
+    println!("{}{:>10}", header.replacen("AP", "MAP", 1).replacen("RR", "MRR", 1), "ms");
+    for evaluation in evaluations.iter() {
+        println!(
+            "{:<16}{:>8.4}{:>8.4}{:>8.4}{:>9.4}{:>8.4}{:>10.3}",
+            evaluation.scheme,
+            evaluation.mean_average_precision(),
+            evaluation.mean_precision_at_k(),
+            evaluation.mean_r_precision(),
+            evaluation.mean_ndcg_at_k(),
+            evaluation.mean_reciprocal_rank(),
+            evaluation.mean_response_time().as_secs_f64() * 1000.0
+        );
+    }
+    Ok(())
+}
+
+/*
+ * Builds the options of a query entered in the query REPL. Boolean queries list every matching
+ * document
+ *
+ * # Arguments
+ *
+ * *`ranked_retrieval` - Whether documents are ranked by score
+ * *`scorer` - The weighting scheme used in ranked retrieval
+ * *`proximity` - Term-pair proximity boost added to ranked scores, if enabled
+ */
+fn search_options<'s>(
+    ranked_retrieval: bool,
+    scorer: &'s dyn Scorer,
+    proximity: Option<&'s ProximityBoost>) -> query_processor::SearchOptions<'s> {
+
+    let mut options = query_processor::SearchOptions::new(ranked_retrieval, scorer);
+    options.proximity = proximity;
+    if !ranked_retrieval {
+        options.limit = usize::MAX;
+    }
+    options
+}
+
+/*
+ * Answers a query, expanding ranked queries by relevance feedback when requested
+ *
+ * # Arguments
+ *
+ * *`options` - How the query is answered
+ * *`feedback` - Parameters of pseudo-relevance feedback expanding ranked queries, if enabled
+ * *`judgments` - Documents marked relevant and non-relevant, expanding the ranked query by
+ * explicit relevance feedback
+ * *`input` - The inputted query that will be processed
+ * *`index` - The Disk Inverted Index being searched
+ * *`k_gram_index` - The K Gram Index used to expand wildcards
+ *
+ * # Returns
+ *
+ * The results and the terms added by relevance feedback, or an error if the query is invalid
+ */
+fn run_query(
+    options: &query_processor::SearchOptions,
+    feedback: Option<&Feedback>,
+    judgments: Option<(&[u32], &[u32])>,
+    input: &str,
+    index: &DiskInvertedIndex,
+    k_gram_index: &KGramIndex) -> Result<(SearchResults, Vec<TermComponentScore>), &'static str> {
+
+    if options.ranked_retrieval && (feedback.is_some() || judgments.is_some()) {
+        let feedback = feedback.cloned().unwrap_or_default();
+        relevance_feedback::process_query_with_feedback(options, &feedback, judgments, input, index, k_gram_index)
+    } else {
+        query_processor::process_query(options, input, index, k_gram_index).map(|results| (results, Vec::new()))
+    }
+}
+
+/*
+ * Prints the results of a query
+ *
+ * # Arguments
+ *
+ * *`results` - The page of results
+ * *`expansion` - Weights of the query terms after relevance feedback
+ */
+fn print_results(results: &SearchResults, expansion: &[TermComponentScore]) {
+    if !expansion.is_empty() {
+        let terms: Vec<String> = expansion.iter().map(|term| format!("{} ({:.3})", term.term, term.score)).collect();
+        println!("Expanded query: {}\n", terms.join(", "));
+    }
+    for hit in results.hits.iter() {
+        let file: &Path = hit.path.as_ref();
+        let file_name = file.file_name().unwrap().to_str().unwrap();
+        match hit.score {
+            Some(score) => {
+                println!("{} - {}", file_name, score);
+                if hit.title != hit.path {
+                    println!("    {}", hit.title);
+                }
+                for component in hit.explanation.iter() {
+                    println!("    {} (tf {}): {}", component.label, component.term_frequency, component.score);
+                }
+            },
+            None => println!("Result: {}", file_name),
+        }
+        if let Some(ref snippet) = hit.snippet {
+            println!("    {}", snippet.render(Highlight::Ansi));
+        }
+    }
+    println!();
+    if results.total_is_lower_bound {
+        print!("At least ");
+    }
+    if results.total != 1 {
+        print!("{} Documents", results.total);
+    } else {
+        print!("{} Document", results.total);
+    }
+    println!(" ({:.3} ms)", results.elapsed.as_secs_f64() * 1000.0);
 }
 
 /*
@@ -333,33 +1028,113 @@ fn build_index(
  * 
  * # Arguments
  * 
+ * *`options` - How the query is answered
+ * *`feedback` - Parameters of pseudo-relevance feedback expanding ranked queries, if enabled
+ * *`judgments` - Documents marked relevant and non-relevant, expanding the ranked query by
+ * explicit relevance feedback
  * *`input` - The inputted query that will be processed
  * *`index` - The Positional Inverted Index that will be used to process the term
- * *`id_file` - HashMap that contains the association between a Document ID and the file name
  */
 fn process_query(
-    ranked_retrieval: bool,
-    scheme: &str,
+    options: &query_processor::SearchOptions,
+    feedback: Option<&Feedback>,
+    judgments: Option<(&[u32], &[u32])>,
     input: &str,
     index: &DiskInvertedIndex,
-    k_gram_index: &KGramIndex,
-    id_file: &HashMap<u32, String>) {
+    k_gram_index: &KGramIndex) {
 
     println!();
-    let results = query_processor::process_query(ranked_retrieval, scheme, input, index, k_gram_index, id_file);
+    match run_query(options, feedback, judgments, input, index, k_gram_index) {
+        Ok((results, expansion)) => print_results(&results, &expansion),
+        Err(error) => println!("Invalid Query: {}", error),
+    }
     println!();
-    for result in results.clone() {
-        println!("Result: {}", result);
+}
+
+/*
+ * Processes a query across several indexes before printing the merged results, each tagged with
+ * the index it was found in
+ *
+ * # Arguments
+ *
+ * *`options` - How the query is answered
+ * *`input` - The inputted query that will be processed
+ * *`indexes` - The open indexes
+ * *`names` - Names of the indexes searched, in the order their results are merged
+ */
+fn federated_query(options: &query_processor::SearchOptions, input: &str, indexes: &IndexSet, names: &[String]) {
+    let opened: Vec<(&String, DiskInvertedIndex, Arc<KGramIndex>)> = names
+        .iter()
+        .filter_map(|name| indexes.get_with_k_gram_index(name).map(|(index, kgram)| (name, index, kgram)))
+        .collect();
+    let targets: Vec<SearchTarget> = opened.iter().map(|(name, index, kgram)| SearchTarget::new(name, index, kgram)).collect();
+    println!();
+    match federated_search::process_query(options, input, &targets) {
+        Ok(results) => {
+            for federated in results.hits.iter() {
+                let hit = &federated.hit;
+                let file: &Path = hit.path.as_ref();
+                let file_name = file.file_name().unwrap().to_str().unwrap();
+                match hit.score {
+                    Some(score) => println!("[{}] {} - {}", federated.index, file_name, score),
+                    None => println!("[{}] {}", federated.index, file_name),
+                }
+                if let Some(ref snippet) = hit.snippet {
+                    println!("    {}", snippet.render(Highlight::Ansi));
+                }
+            }
+            println!();
+            if results.total_is_lower_bound {
+                print!("At least ");
+            }
+            if results.total != 1 {
+                print!("{} Documents", results.total);
+            } else {
+                print!("{} Document", results.total);
+            }
+            println!(" ({:.3} ms)", results.elapsed.as_secs_f64() * 1000.0);
+        },
+        Err(error) => println!("Invalid Query: {}", error),
     }
     println!();
-    if !ranked_retrieval {
-        if results.len() != 1 {
-            println!("{} Documents", results.len());
-        } else {
-            println!("{} Document", results.len());
+}
+
+/*
+ * Re-runs the last ranked query expanded by explicit relevance feedback
+ *
+ * # Arguments
+ *
+ * *`options` - How the expanded query is answered
+ * *`last_query` - The last query entered
+ * *`input` - The ':relevant FILE... [-FILE...]' command, where files prefixed by '-' are
+ * non-relevant
+ * *`index` - The Disk Inverted Index being searched
+ * *`k_gram_index` - The K Gram Index used to expand wildcards
+ */
+fn mark_relevant(
+    options: &query_processor::SearchOptions,
+    last_query: &str,
+    input: &str,
+    index: &DiskInvertedIndex,
+    k_gram_index: &KGramIndex) {
+
+    if last_query.is_empty() {
+        println!("Enter a query before marking its results\n");
+        return;
+    }
+    let mut relevant: Vec<u32> = Vec::new();
+    let mut non_relevant: Vec<u32> = Vec::new();
+    for file in input.split_whitespace().skip(1) {
+        let (file, judged) = match file.strip_prefix('-') {
+            Some(file) => (file, &mut non_relevant),
+            None => (file, &mut relevant),
+        };
+        match index.get_document_id(file) {
+            Some(doc_id) => judged.push(doc_id),
+            None => println!("{} is not in the index", file),
         }
     }
-    println!();
+    process_query(options, None, Some((&relevant, &non_relevant)), last_query, index, k_gram_index);
 }
 
 /*
@@ -431,12 +1206,50 @@ fn open_file(
     }
 }
 
-fn classify_all(classifier: &str, bayesian_classifier: &BayesianClassifier, rocchio_classifier: &RocchioClassifier, file_id_map: &HashMap<String, u32>) {
-    for (file,_) in file_id_map {
-        classify_document(classifier, &bayesian_classifier, &rocchio_classifier, file, &file_id_map);
+/*
+ * Prints out the stored fields of an indexed document, read from the document store of the index
+ *
+ * # Arguments
+ *
+ * *`index` - The Disk Inverted Index holding the document
+ * *`input` - User input representing the file name of the document
+ */
+fn open_document(
+    index: &DiskInvertedIndex,
+    input: &str) {
+
+    let mut string = input.split_whitespace();
+    let file = string.nth(1).expect("Not a valid file");
+    println!("Opening {}", file);
+    let doc_id = index.get_document_id(file);
+    let document = match doc_id.map(|doc_id| index.get_document(doc_id)) {
+        Some(Ok(document)) => document,
+        Some(Err(error)) => {
+            println!("{}", error);
+            return;
+        }
+        None => {
+            println!("{} is not in the index", file);
+            return;
+        }
+    };
+    println!("\n{}", document.title);
+    println!("\n{}", document.body);
+    println!("\n{}", document.url);
+    for (name, value) in document.fields.iter() {
+        println!("{}: {}", name, value);
+    }
+    println!();
+}
+
+fn classify_all(classifier: &dyn Classifier, index: &DiskInvertedIndex) {
+    for doc_id in 0..index.get_num_documents().unwrap_or(0) {
+        let path = index.get_document_path(doc_id).expect("Doc id not found");
+        let file = Path::new(&path).file_name().and_then(|name| name.to_str()).expect("Error converting to string");
+        classify_document(classifier, file, index);
     }
 }
-fn classify_document(classifier: &str, bayesian_classifier: &BayesianClassifier, rocchio_classifier: &RocchioClassifier, input: &str, file_id_map: &HashMap<String, u32>) {
+fn classify_document(classifier: &dyn Classifier, input: &str, index: &DiskInvertedIndex) {
     let mut file_name = "";
     if input.starts_with(":") {
         let mut tokens = input.split_whitespace();
@@ -444,14 +1257,15 @@ fn classify_document(classifier: &str, bayesian_classifier: &BayesianClassifier,
     } else {
         file_name = input;
     }
-    let doc_id = file_id_map.get(file_name).expect("Doc id not found");
-    let classification : String; 
-    if classifier == "rocchio" {
-        classification = rocchio_classifier.classify(*doc_id).to_string();
-    } else {
-        classification = bayesian_classifier.classify(*doc_id).to_string();
-    }
-    println!("{} was written by {}", file_name, classification);
+    let doc_id = match index.get_document_id(file_name) {
+        Some(doc_id) => doc_id,
+        None => {
+            println!("{} is not in the index", file_name);
+            return;
+        }
+    };
+    let classification = classifier.classify(doc_id);
+    println!("{} was classified as {}", file_name, classification.label);
 }
 
 /*
@@ -488,13 +1302,8 @@ fn print_vocab(
     println!("Vocabulary Size : {}", vocab_list.len());
 }
 
-fn print_all_vocab(classifier: &str, bayesian_classifier: &BayesianClassifier, rocchio_classifier: &RocchioClassifier) {
-    let mut vocab_list : Vec<String> = Vec::new();
-    if classifier == "rocchio" {
-        vocab_list = rocchio_classifier.get_all_vocab(); 
-    } else if classifier == "bayesian" {
-        vocab_list = bayesian_classifier.get_all_vocab(); 
-    }
+fn print_all_vocab(classifier: &dyn Classifier) {
+    let vocab_list = classifier.get_all_vocab();
 
     let vocab_len = vocab_list.len();
 
@@ -526,14 +1335,14 @@ fn print_kgram(
     println!("Total kgrams: {}", kgrams.len());
 }
 
-fn print_discriminating_vocab(bayesian_classifier: &BayesianClassifier, input: &str) {
+fn print_discriminating_vocab(training: &TrainingSet, input: &str) {
 
     let mut tokens = input.split_whitespace();
     let count_string = tokens.nth(1).expect("Error retrieving number");
     let count = count_string.parse::<u32>().expect("Not an unsigned integer");
 
 
-    let discriminating_vocab = bayesian_classifier.build_discriminating_vocab_set(count);
+    let discriminating_vocab = BayesianClassifier::build_discriminating_vocab_set(training, count);
     let mut counter = 1;
     for x in &discriminating_vocab {
         println!("Discriminating Vocab {}: {}", counter, x);
@@ -542,12 +1351,60 @@ fn print_discriminating_vocab(bayesian_classifier: &BayesianClassifier, input: &
 }
 
 fn print_centroid_vectors(rocchio_classifier: &RocchioClassifier) {
-    println!("Hamilton");
-    rocchio_classifier.get_hamilton_centroid();
-    println!("Jay");
-    rocchio_classifier.get_jay_centroid();
-    println!("Madison");
-    rocchio_classifier.get_madison_centroid();
+    for (class, centroid) in rocchio_classifier.get_centroids() {
+        println!("{}", class);
+        for (i, component) in centroid.iter().take(30).enumerate() {
+            println!("{}. Term: {}, Score: {}", i + 1, component.term, component.score);
+        }
+    }
+}
+
+/*
+ * Changes the weighting scheme used in ranked retrieval
+ *
+ * # Arguments
+ *
+ * *`scorers` - The registered weighting schemes
+ * *`scheme` - The name of the current weighting scheme, replaced when the new one exists
+ * *`input` - The ':scheme NAME' command, where NAME may be abbreviated to its first letter for
+ * the built in schemes
+ */
+fn select_scheme(scorers: &ScorerRegistry, scheme: &mut String, input: &str) {
+    let name = match input.trim_start_matches(":scheme ").trim() {
+        "d" => "default",
+        "t" => "tfidf",
+        "o" => "okapi",
+        "w" => "wacky",
+        name => name,
+    };
+    match scorers.get(name) {
+        Ok(scorer) => *scheme = scorer.name().to_string(),
+        Err(error) => println!("{} - Available schemes: {}", error, scorers.names().join(", ")),
+    }
+}
+
+/*
+ * Changes the k1 and b parameters of the BM25 weighting schemes
+ *
+ * # Arguments
+ *
+ * *`scorers` - The registered weighting schemes, where okapi and bm25plus are replaced
+ * *`input` - The ':bm25 K1 B' command
+ */
+fn tune_bm25(scorers: &mut ScorerRegistry, input: &str) {
+    let parameters: Vec<f64> = input
+        .split_whitespace()
+        .skip(1)
+        .filter_map(|parameter| parameter.parse().ok())
+        .collect();
+    if parameters.len() != 2 || parameters[0] < 0.0 || parameters[1] < 0.0 || parameters[1] > 1.0 {
+        println!("Usage: :bm25 K1 B - where K1 >= 0 and 0 <= B <= 1");
+        return;
+    }
+    let (k1, b) = (parameters[0], parameters[1]);
+    scorers.register(Box::new(OkapiScorer::new(k1, b)));
+    scorers.register(Box::new(Bm25PlusScorer::new(k1, b, Bm25PlusScorer::default().delta)));
+    println!("BM25 parameters: k1 = {}, b = {}", k1, b);
 }
 
 /*
@@ -569,12 +1426,21 @@ fn print_help(){
     println!(":scheme t || :scheme tfidf - Use 'tf-idf' Weight Scheme in Ranked Retrieval");
     println!(":scheme o || :scheme okapi - Use Okapi BM25 Weight Scheme in Ranked Retrieval");
     println!(":scheme w || :scheme wacky - Use Wacky Weight Scheme in Ranked Retrieval");
+    println!(":scheme NAME - Use any registered Weight Scheme in Ranked Retrieval");
+    println!(":scheme bm25plus - Use BM25+ Weight Scheme in Ranked Retrieval");
+    println!(":bm25 K1 B - Set the k1 and b parameters of the okapi and bm25plus Weight Schemes");
+    println!(":feedback on || :feedback off - Expand Ranked Retrieval queries with terms of their top 10 documents (pseudo-relevance feedback)");
+    println!(":relevant FILE... -FILE... - Re-run the last query expanded with terms of the files marked relevant, and away from those prefixed by '-'");
+    println!(":proximity on || :proximity off - Boost Ranked Retrieval scores of documents where query terms appear close together");
     println!(":classifier rocchio - Use Rocchio Classifier");
     println!(":classifier bayesian - Use Bayesian Classifier");
+    println!(":classifier knn - Use k-Nearest-Neighbours Classifier (5 neighbours, majority voting)");
     println!(":classify FILE_NAME - Use classifier to classify specified document");
     println!(":classify all - Classify all documents in directory");
     println!(":centroid_vectors - Print First 30 components of normalized centroid vectors from each classifier");
     println!(":discriminating_vocab NUM - Print the Top Number of discriminating terms");
-    println!(":vocab all - Retrieve vocabulary of entire corpus (Disputed, Hamilton, Jay, Madison");
+    println!(":vocab all - Retrieve vocabulary of every class");
+    println!(":search QUERY - Rank the documents of the classified index and of every class together");
+    println!(":reopen - Open the searched indexes again after they were rebuilt");
     println!();
 }
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use std::fs::{self};
 use std::time::SystemTime;
+use index::document_store::DocumentStoreWriter;
+use index::document_store::StoredDocument;
+use index::doc_table;
 use index::index_writer::IndexWriter;
 use index::index_writer::DiskIndex;
 use index::k_gram_index::KGramIndex;
@@ -26,16 +29,18 @@ pub struct DocumentWeight {
     doc_id: u32,
     doc_weight: f64,
     doc_length: u64,
+    unique_term_count: u64,
     byte_size: u64,
     avg_tftd: f64,
 }
 
 impl DocumentWeight {
-    fn new(doc_id: u32, doc_weight: f64, doc_length: u64, byte_size: u64, avg_tftd: f64) -> Self {
+    fn new(doc_id: u32, doc_weight: f64, doc_length: u64, unique_term_count: u64, byte_size: u64, avg_tftd: f64) -> Self {
         DocumentWeight {
             doc_id: doc_id,
             doc_weight: doc_weight,
             doc_length: doc_length,
+            unique_term_count,
             byte_size: byte_size,
             avg_tftd: avg_tftd,
         }
@@ -53,6 +58,10 @@ impl DocumentWeight {
         self.doc_length
     }
 
+    pub fn get_unique_term_count(&self) -> u64 {
+        self.unique_term_count
+    }
+
     pub fn get_byte_size(&self) -> u64 {
         self.byte_size
     }
@@ -70,17 +79,25 @@ pub fn build_index(
     let paths = fs::read_dir(directory.clone()).unwrap();
     let mut files = Vec::new();
 
-    // Add all files in path to vector
+    // Add all files in path to vector, skipping index files written by a previous build
     for path in paths {
-        files.push(path.unwrap().path().display().to_string())
+        let path = path.unwrap().path();
+        if path.is_dir() || path.extension().is_some_and(|extension| extension == "bin") {
+            continue;
+        }
+        files.push(path.display().to_string())
     }
+    // Sorting keeps document ids stable across platforms and rebuilds
+    files.sort();
 
     let mut id_number = HashMap::new();
 
     let now = SystemTime::now();
-    println!("Indexing...");
+    info!("Indexing {} files in {}", files.len(), directory);
     let mut avg_doc_weight_accumulator = 0;
     let mut doc_weights : Vec<DocumentWeight> = Vec::new();
+    let mut stemmer = Stemmer::new("english").unwrap();
+    let mut document_store = DocumentStoreWriter::new(directory.as_str());
     //iterate through all files in directory
     for (i, file) in files.iter().enumerate() {
         // println!("Indexing {} out of {}...", i, files.len());
@@ -88,31 +105,39 @@ pub fn build_index(
         //read the file and split it into each word
         let document = read_file::read_file(file);
         let document_body = document.clone().get_body();
-        let iter = document_body.split_whitespace();
 
         id_number.insert(i as u32, file.to_string());
 
-        let mut tftd: HashMap<String,u32> = HashMap::new(); 
-
-        //normalize each token in the file and add it to the index with its document id and position
-        for (j, word) in iter.enumerate() {
+        let mut fields = read_file::read_stored_fields(file);
+        fields.insert(String::from("path"), file.to_string());
+        document_store.add(&StoredDocument {
+            title: document.get_title(),
+            url: document.get_url(),
+            body: document_body.clone(),
+            fields,
+        });
 
+        let mut tftd: HashMap<String,u32> = HashMap::new(); 
 
-            // println!("File {} / {} - Indexing token {} out of {}...", i, files.len(), j, iter_length);
+        //normalize each token in the file and add it to the index with its document id, position and
+        //character offsets
+        let mut position: u32 = 0;
+        for (word, start, _) in tokenize(&document_body) {
             let normalized_tokens = normalize_token(word.to_string());
-            let tokens = normalized_tokens.clone();
-            let stemmed_tokens = stem_terms(normalized_tokens);
             if k_gram_index.is_enabled() {
-                k_gram_index.check_terms(&tokens);
+                k_gram_index.check_terms(&normalized_tokens);
             }
-            for term in stemmed_tokens {
-                if !tftd.contains_key(&term) {
-                    tftd.insert(term.to_string(),1);
-                } else {
-                    *tftd.get_mut(&term).unwrap() = tftd.get(&term).unwrap() + 1;
+            let offsets = term_offsets(word, &normalized_tokens);
+            let (term_positions, width) = relative_positions(&normalized_tokens);
+            for ((token, (term_start, term_end)), term_position) in normalized_tokens.iter().zip(offsets).zip(term_positions) {
+                if token.trim() == "" {
+                    continue;
                 }
-                index.add_term(&term, i as u32, j as u32);
+                let term = stemmer.stem(token);
+                *tftd.entry(term.clone()).or_insert(0) += 1;
+                index.add_term_with_offsets(&term, i as u32, position + term_position, start + term_start, start + term_end);
             }
+            position += width;
         }
 
         let mut wdt: HashMap<String,f64> = HashMap::new();
@@ -134,7 +159,9 @@ pub fn build_index(
             index.set_wacky_score(term,wacky_weight);
         }
 
-        avg_doc_weight_accumulator += tftd.len();
+        // BM25 normalizes by the number of tokens of a document, not by its number of distinct terms
+        let doc_length = tftd.values().sum::<u32>() as u64;
+        avg_doc_weight_accumulator += doc_length;
 
 
         let mut sum_weights_squared: f64 = 0.0f64;
@@ -143,15 +170,16 @@ pub fn build_index(
         }
 
         let euclidian_doc_weights = sum_weights_squared.sqrt();
-        let doc_length = tftd.len() as u64;
         let byte_size = fs::metadata(file).unwrap().len();
         let avg_tftd = (tftd.values().sum::<u32>() as f64) / (tftd.len() as f64);
 
-        doc_weights.push(DocumentWeight::new(i as u32, euclidian_doc_weights, doc_length, byte_size, avg_tftd));
+        doc_weights.push(DocumentWeight::new(i as u32, euclidian_doc_weights, doc_length, tftd.len() as u64, byte_size, avg_tftd));
 
 
     }
 
+    document_store.finish();
+    doc_table::write_doc_table(directory.as_str(), &files);
     let avg_doc_length = avg_doc_weight_accumulator as f64 / doc_weights.len() as f64;
 
     // Build DiskInvertedIndex
@@ -159,24 +187,113 @@ pub fn build_index(
     index_writer.build_index_for_directory(index, &doc_weights, avg_doc_length, directory.as_str());
 
 
-    println!("Indexing complete!\n");
-
     let time_elapsed = now.elapsed().expect("Invalid time");
     let time_elapsed_seconds = time_elapsed.as_secs();
     let time_elapsed_nano = time_elapsed.subsec_nanos();
 
-    print!("Directory indexed in: ");
-
     if time_elapsed_seconds > 1 {
-        println!("{} Seconds", time_elapsed_seconds);
+        info!("Directory indexed in: {} Seconds", time_elapsed_seconds);
     } else {
-        println!("{} Nanoseconds", time_elapsed_nano);
+        info!("Directory indexed in: {} Nanoseconds", time_elapsed_nano);
     }
-    println!();
 
     return id_number;
 }
 
+/*
+ * Splits a document body into its whitespace separated tokens. Positions are given to the terms
+ * of each token by relative_positions
+ *
+ * # Arguments
+ *
+ * *`body` - The body of the document
+ *
+ * # Returns
+ *
+ * Every token with its start and end character offsets in the body
+ */
+pub fn tokenize(body: &str) -> Vec<(&str, u32, u32)> {
+    let mut tokens = Vec::new();
+    let mut start: Option<(usize, u32)> = None;
+    let mut characters = 0;
+    for (i, c) in body.char_indices() {
+        if c.is_whitespace() {
+            if let Some((byte_start, char_start)) = start.take() {
+                tokens.push((&body[byte_start..i], char_start, characters));
+            }
+        } else if start.is_none() {
+            start = Some((i, characters));
+        }
+        characters += 1;
+    }
+    if let Some((byte_start, char_start)) = start {
+        tokens.push((&body[byte_start..], char_start, characters));
+    }
+    tokens
+}
+
+/*
+ * Finds which characters of a token each of its normalized terms came from. The parts of a
+ * hyphenated token map to the characters between the hyphens, the joined term and any other term
+ * to the token without its leading and trailing punctuation
+ *
+ * # Arguments
+ *
+ * *`token` - The token as it appears in the document
+ * *`terms` - The terms normalize_token produced from the token
+ *
+ * # Returns
+ *
+ * The start and end character offsets, relative to the token, of every term
+ */
+pub fn term_offsets(token: &str, terms: &[String]) -> Vec<(u32, u32)> {
+    let characters: Vec<char> = token.chars().collect();
+    let is_word_character = |c: &char| c.is_ascii_digit() || c.is_alphabetic();
+    let start = characters.iter().position(is_word_character).unwrap_or(0);
+    let end = characters.iter().rposition(is_word_character).map_or(start, |last| last + 1);
+
+    let mut offsets: Vec<(u32, u32)> = Vec::new();
+    if terms.len() > 1 {
+        let mut part_start = start;
+        for (i, c) in characters.iter().enumerate().take(end).skip(start) {
+            if *c == '-' || *c == '(' || *c == ')' {
+                offsets.push((part_start as u32, i as u32));
+                part_start = i + 1;
+            }
+        }
+        offsets.push((part_start as u32, end as u32));
+    }
+    offsets.truncate(terms.len());
+    while offsets.len() < terms.len() {
+        offsets.push((start as u32, end as u32));
+    }
+    offsets
+}
+
+/*
+ * Positions of the terms normalize_token produced from a token, relative to the token's first
+ * position. The parts of a hyphenated token take a position each and the joined term shares the
+ * first part's, so a phrase matches the parts as separate words as well as the whole token
+ *
+ * # Arguments
+ *
+ * *`terms` - The terms normalize_token produced from the token
+ *
+ * # Returns
+ *
+ * The relative position of every term, and the number of positions the token takes up
+ */
+pub fn relative_positions(terms: &[String]) -> (Vec<u32>, u32) {
+    if terms.len() < 2 {
+        return (vec![0; terms.len()], 1);
+    }
+    // normalize_token lists the parts of a hyphenated token before the joined term
+    let parts = terms.len() as u32 - 1;
+    let mut positions: Vec<u32> = (0..parts).collect();
+    positions.push(0);
+    (positions, parts)
+}
+
 /*
  * Function to perform token normalization to obtain the stem of a word
  *
@@ -179,3 +179,345 @@ impl QueryParser {
         final_results
     }
 }
+
+/*
+ * Node of a parsed boolean query. Produced by QueryParser::parse and evaluated by the query
+ * processor
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryNode {
+    /*
+     * A single query token as typed by the user (may contain a '*' wildcard)
+     */
+    Term(String),
+
+    /*
+     * A phrase literal, e.g. "hello world"
+     */
+    Phrase(Vec<String>),
+
+    /*
+     * Terms or phrases occurring close to each other in a document,
+     * e.g. hello NEAR/2 world, hello ONEAR/2 world or WITHIN/5(a "b c" d)
+     */
+    Proximity {
+        operands: Vec<QueryNode>,
+        distance: u32,
+        kind: ProximityKind,
+    },
+
+    /*
+     * Documents fulfilling every operand
+     */
+    And(Vec<QueryNode>),
+
+    /*
+     * Documents fulfilling at least one operand
+     */
+    Or(Vec<QueryNode>),
+
+    /*
+     * Documents not fulfilling the operand
+     */
+    Not(Box<QueryNode>),
+}
+
+/*
+ * How the operands of a proximity query must be arranged
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProximityKind {
+    /*
+     * a ONEAR/k b - b starts at most k positions after a ends
+     */
+    Ordered,
+
+    /*
+     * a NEAR/k b - a and b are at most k positions apart, in either order
+     */
+    Unordered,
+
+    /*
+     * WITHIN/k(a b c) - every operand occurs inside a window spanning at most k positions
+     */
+    Window,
+}
+
+/*
+ * Lexical tokens of a boolean query
+ */
+#[derive(Debug, Clone, PartialEq)]
+enum QueryToken {
+    Word(String),
+    Phrase(Vec<String>),
+    Proximity(u32, ProximityKind),
+    LeftParenthesis,
+    RightParenthesis,
+    And,
+    Or,
+    Not,
+}
+
+impl QueryParser {
+    /*
+     * Parses a boolean query into a QueryNode tree.
+     *
+     * Operators, from highest to lowest precedence:
+     * NOT (or a leading '-'), NEAR/k and ONEAR/k, AND (or whitespace), OR (or '+').
+     * Parentheses group sub-queries and may be nested to any depth.
+     * WITHIN/k(...) takes two or more terms or phrases between its parentheses.
+     * E.g. "(a OR b) AND NOT c" -> And([Or([a, b]), Not(c)])
+     *
+     * # Arguments
+     *
+     * *`input` - The query to parse
+     *
+     * # Returns
+     *
+     * The root of the parsed query or an error describing why the query is malformed
+     */
+    pub fn parse(&self, input: &str) -> Result<QueryNode, &'static str> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err("Empty query");
+        }
+        let mut position = 0;
+        let node = parse_or(&tokens, &mut position)?;
+        if position != tokens.len() {
+            return Err("Unbalanced parentheses");
+        }
+        Ok(node)
+    }
+}
+
+/*
+ * Splits a query into lexical tokens
+ *
+ * # Arguments
+ *
+ * *`input` - The query to split
+ *
+ * # Returns
+ *
+ * The tokens of the query in order
+ */
+fn tokenize(input: &str) -> Result<Vec<QueryToken>, &'static str> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' {
+            chars.next();
+            tokens.push(QueryToken::LeftParenthesis);
+        } else if c == ')' {
+            chars.next();
+            tokens.push(QueryToken::RightParenthesis);
+        } else if c == '"' {
+            chars.next();
+            let mut phrase = String::new();
+            let mut closed = false;
+            for next in chars.by_ref() {
+                if next == '"' {
+                    closed = true;
+                    break;
+                }
+                phrase.push(next);
+            }
+            if !closed {
+                return Err("Unterminated phrase literal");
+            }
+            let words: Vec<String> = phrase.split_whitespace().map(String::from).collect();
+            match words.len() {
+                0 => return Err("Empty phrase literal"),
+                1 => tokens.push(QueryToken::Word(words[0].clone())),
+                _ => tokens.push(QueryToken::Phrase(words)),
+            }
+        } else if c == '-' {
+            // A leading '-' negates whatever follows it
+            chars.next();
+            tokens.push(QueryToken::Not);
+        } else {
+            let mut word = String::new();
+            while let Some(&next) = chars.peek() {
+                if next.is_whitespace() || next == '(' || next == ')' || next == '"' {
+                    break;
+                }
+                word.push(next);
+                chars.next();
+            }
+            tokens.push(word_to_token(word)?);
+        }
+    }
+    Ok(tokens)
+}
+
+/*
+ * Converts a bare word into an operator token if it is one, otherwise into a word token
+ */
+fn word_to_token(word: String) -> Result<QueryToken, &'static str> {
+    if word == "+" || word == "OR" {
+        return Ok(QueryToken::Or);
+    }
+    if word == "AND" {
+        return Ok(QueryToken::And);
+    }
+    if word == "NOT" {
+        return Ok(QueryToken::Not);
+    }
+    let operators = [
+        ("NEAR/", ProximityKind::Unordered),
+        ("ONEAR/", ProximityKind::Ordered),
+        ("WITHIN/", ProximityKind::Window),
+    ];
+    for &(prefix, kind) in operators.iter() {
+        if let Some(distance) = word.strip_prefix(prefix) {
+            return match distance.parse::<u32>() {
+                Ok(k) => Ok(QueryToken::Proximity(k, kind)),
+                Err(_) => Err("Proximity operators must be followed by a distance"),
+            };
+        }
+    }
+    Ok(QueryToken::Word(word))
+}
+
+fn parse_or(tokens: &[QueryToken], position: &mut usize) -> Result<QueryNode, &'static str> {
+    let mut operands = vec![parse_and(tokens, position)?];
+    while *position < tokens.len() && tokens[*position] == QueryToken::Or {
+        *position += 1;
+        operands.push(parse_and(tokens, position)?);
+    }
+    if operands.len() == 1 {
+        return Ok(operands.remove(0));
+    }
+    Ok(QueryNode::Or(operands))
+}
+
+fn parse_and(tokens: &[QueryToken], position: &mut usize) -> Result<QueryNode, &'static str> {
+    let mut operands = vec![parse_not(tokens, position)?];
+    while *position < tokens.len() {
+        match tokens[*position] {
+            QueryToken::And => {
+                *position += 1;
+                operands.push(parse_not(tokens, position)?);
+            },
+            // Juxtaposition is an implicit AND
+            QueryToken::Word(_)
+            | QueryToken::Phrase(_)
+            | QueryToken::LeftParenthesis
+            | QueryToken::Not
+            | QueryToken::Proximity(_, ProximityKind::Window) => {
+                operands.push(parse_not(tokens, position)?);
+            },
+            _ => break,
+        }
+    }
+    if operands.len() == 1 {
+        return Ok(operands.remove(0));
+    }
+    Ok(QueryNode::And(operands))
+}
+
+fn parse_not(tokens: &[QueryToken], position: &mut usize) -> Result<QueryNode, &'static str> {
+    if *position < tokens.len() && tokens[*position] == QueryToken::Not {
+        *position += 1;
+        let operand = parse_not(tokens, position)?;
+        // Double negation cancels out
+        return match operand {
+            QueryNode::Not(inner) => Ok(*inner),
+            other => Ok(QueryNode::Not(Box::new(other))),
+        };
+    }
+    parse_near(tokens, position)
+}
+
+fn parse_near(tokens: &[QueryToken], position: &mut usize) -> Result<QueryNode, &'static str> {
+    if let Some(&QueryToken::Proximity(distance, ProximityKind::Window)) = tokens.get(*position) {
+        *position += 1;
+        return parse_window(tokens, position, distance);
+    }
+    let first = parse_primary(tokens, position)?;
+    if let Some(&QueryToken::Proximity(distance, kind)) = tokens.get(*position) {
+        // A window operator after a term starts the next operand of an implicit AND
+        if kind == ProximityKind::Window {
+            return Ok(first);
+        }
+        *position += 1;
+        let second = parse_primary(tokens, position)?;
+        if !is_positional(&first) || !is_positional(&second) {
+            return Err("Proximity operands must be terms or phrases");
+        }
+        if let Some(&QueryToken::Proximity(_, ProximityKind::Unordered)) | Some(&QueryToken::Proximity(_, ProximityKind::Ordered)) = tokens.get(*position) {
+            return Err("Use WITHIN/k(...) to relate more than two operands");
+        }
+        return Ok(QueryNode::Proximity {
+            operands: vec![first, second],
+            distance,
+            kind,
+        });
+    }
+    Ok(first)
+}
+
+/*
+ * Parses the parenthesised operand list of a WITHIN/k query
+ */
+fn parse_window(tokens: &[QueryToken], position: &mut usize, distance: u32) -> Result<QueryNode, &'static str> {
+    if tokens.get(*position) != Some(&QueryToken::LeftParenthesis) {
+        return Err("WITHIN/k must precede its parenthesised operands");
+    }
+    *position += 1;
+    let mut operands = Vec::new();
+    loop {
+        match tokens.get(*position) {
+            Some(&QueryToken::RightParenthesis) => {
+                *position += 1;
+                break;
+            },
+            Some(QueryToken::Word(word)) => operands.push(QueryNode::Term(word.clone())),
+            Some(QueryToken::Phrase(words)) => operands.push(QueryNode::Phrase(words.clone())),
+            Some(_) => return Err("Proximity operands must be terms or phrases"),
+            None => return Err("Unbalanced parentheses"),
+        }
+        *position += 1;
+    }
+    if operands.len() < 2 {
+        return Err("WITHIN/k needs at least two operands");
+    }
+    Ok(QueryNode::Proximity {
+        operands,
+        distance,
+        kind: ProximityKind::Window,
+    })
+}
+
+/*
+ * Checks if a node has positions in a document, i.e. can be an operand of a proximity query
+ */
+fn is_positional(node: &QueryNode) -> bool {
+    matches!(*node, QueryNode::Term(_) | QueryNode::Phrase(_))
+}
+
+fn parse_primary(tokens: &[QueryToken], position: &mut usize) -> Result<QueryNode, &'static str> {
+    let token = match tokens.get(*position) {
+        Some(token) => token.clone(),
+        None => return Err("Query ended unexpectedly"),
+    };
+    *position += 1;
+    match token {
+        QueryToken::Word(word) => Ok(QueryNode::Term(word)),
+        QueryToken::Phrase(words) => Ok(QueryNode::Phrase(words)),
+        QueryToken::LeftParenthesis => {
+            let inner = parse_or(tokens, position)?;
+            match tokens.get(*position) {
+                Some(&QueryToken::RightParenthesis) => {
+                    *position += 1;
+                    Ok(inner)
+                },
+                _ => Err("Unbalanced parentheses"),
+            }
+        },
+        QueryToken::RightParenthesis => Err("Unbalanced parentheses"),
+        _ => Err("Operator is missing an operand"),
+    }
+}
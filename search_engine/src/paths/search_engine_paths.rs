@@ -11,8 +11,11 @@ use std::path::PathBuf;
 pub fn initialize_path() -> PathBuf {
     let mut index_path = current_exe().expect("Not a valid path");
 
-    while !index_path.ends_with("CECS-429-Group-Project") {
-        index_path.pop();
+    // Walk up from the executable until the project root is found, whatever the clone is named
+    while !index_path.join("search_engine").join("assets").is_dir() {
+        if !index_path.pop() {
+            panic!("Could not locate the search_engine/assets folder");
+        }
     }
     index_path.push("search_engine");
     index_path.push("assets");
@@ -89,6 +92,6 @@ pub fn verify_path(path_buf: PathBuf) -> bool {
     if path_buf.exists() && path_buf.is_dir() {
         return true;
     }
-    println!("{} does not exist", path_buf.display());
+    warn!("{} does not exist", path_buf.display());
     return false;
 }
@@ -0,0 +1,394 @@
+use index::disk_inverted_index::DiskInvertedIndex;
+use index::disk_inverted_index::IndexReader;
+use index::k_gram_index::KGramIndex;
+use parser::query_parser::QueryParser;
+use processor::query_processor;
+use processor::query_processor::SearchOptions;
+use processor::scorer::ScorerRegistry;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+/*
+ * Number of ranked documents retrieved for every query when measuring average precision
+ */
+pub const EVALUATION_DEPTH: usize = 1000;
+
+/*
+ * A query of an evaluation, identified as in the qrels file
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Topic {
+    pub id: String,
+    pub query: String,
+}
+
+/*
+ * Relevance judgments: for every query ID, the grade of every judged document name. Grades of 0 or
+ * less mark non-relevant documents
+ */
+pub type Qrels = HashMap<String, HashMap<String, u32>>;
+
+/*
+ * Parses a query file. Either TREC topics, where each <top> holds a <num> and a <title> used as the
+ * query, or one query per line written as its ID followed by whitespace and the query text
+ *
+ * # Arguments
+ *
+ * *`text` - Contents of the query file
+ *
+ * # Returns
+ *
+ * The queries in file order, or an error if a line has no query text
+ */
+pub fn parse_topics(text: &str) -> Result<Vec<Topic>, &'static str> {
+    let mut topics: Vec<Topic> = Vec::new();
+    if text.contains("<top>") {
+        for top in text.split("<top>").skip(1) {
+            let id = tag_text(top, "<num>")
+                .map(|num| num.trim().trim_start_matches("Number:").trim().to_string())
+                .ok_or("Topic without a <num>")?;
+            let query = tag_text(top, "<title>").map(|title| title.trim().trim_start_matches("Topic:").trim().to_string()).ok_or("Topic without a <title>")?;
+            topics.push(Topic { id, query });
+        }
+        return Ok(topics);
+    }
+
+    for line in text.lines().map(|line| line.trim()).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let mut parts = line.splitn(2, char::is_whitespace);
+        let id = parts.next().unwrap_or("").to_string();
+        let query = parts.next().map(|query| query.trim()).unwrap_or("");
+        if query.is_empty() {
+            return Err("Query line without query text");
+        }
+        topics.push(Topic { id, query: query.to_string() });
+    }
+    Ok(topics)
+}
+
+/*
+ * The text of a TREC topic field, up to the next tag or line break
+ */
+fn tag_text<'t>(top: &'t str, tag: &str) -> Option<&'t str> {
+    let start = top.find(tag)? + tag.len();
+    let rest = &top[start..];
+    let end = rest.find(['<', '\n']).unwrap_or(rest.len());
+    Some(&rest[..end])
+}
+
+/*
+ * Parses a TREC qrels file, where every line holds a query ID, an unused iteration number, a
+ * document name and a relevance grade
+ *
+ * # Arguments
+ *
+ * *`text` - Contents of the qrels file
+ *
+ * # Returns
+ *
+ * The judgments of every query, or an error if a line is malformed
+ */
+pub fn parse_qrels(text: &str) -> Result<Qrels, &'static str> {
+    let mut qrels: Qrels = HashMap::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != 4 {
+            return Err("Qrels line does not have 4 fields");
+        }
+        let grade: i32 = fields[3].parse().map_err(|_| "Relevance grade is not a number")?;
+        qrels
+            .entry(fields[0].to_string())
+            .or_default()
+            .insert(fields[2].to_string(), grade.max(0) as u32);
+    }
+    Ok(qrels)
+}
+
+/*
+ * Reads and parses a query file, see parse_topics
+ */
+pub fn read_topics(file_name: &str) -> Result<Vec<Topic>, &'static str> {
+    parse_topics(&fs::read_to_string(file_name).map_err(|_| "Failed to read query file")?)
+}
+
+/*
+ * Reads and parses a qrels file, see parse_qrels
+ */
+pub fn read_qrels(file_name: &str) -> Result<Qrels, &'static str> {
+    parse_qrels(&fs::read_to_string(file_name).map_err(|_| "Failed to read qrels file")?)
+}
+
+/*
+ * The effectiveness of the ranking returned for one query
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryEvaluation {
+    pub query_id: String,
+    pub average_precision: f64,
+    pub precision_at_k: f64,
+    pub r_precision: f64,
+    pub ndcg_at_k: f64,
+    pub reciprocal_rank: f64,
+
+    /*
+     * Number of documents judged relevant to the query
+     */
+    pub relevant: usize,
+
+    /*
+     * Number of relevant documents among those retrieved
+     */
+    pub relevant_retrieved: usize,
+
+    /*
+     * Time spent ranking the query
+     */
+    pub elapsed: Duration,
+
+    /*
+     * Why the query could not be run, in which case every metric is 0
+     */
+    pub error: Option<&'static str>,
+}
+
+/*
+ * Measures a ranking against the relevance grades of a query
+ *
+ * # Arguments
+ *
+ * *`query_id` - ID of the query
+ * *`ranking` - The retrieved document names, best first
+ * *`judgments` - Relevance grade of every judged document name
+ * *`k` - Cutoff rank of P@k and nDCG@k
+ * *`elapsed` - Time spent ranking the query
+ *
+ * # Returns
+ *
+ * The metrics of the ranking. Average precision and R-precision count relevant documents that were
+ * not retrieved, and nDCG@k uses the graded judgments
+ */
+pub fn evaluate_ranking(query_id: &str, ranking: &[String], judgments: &HashMap<String, u32>, k: usize, elapsed: Duration) -> QueryEvaluation {
+    let grade = |name: &String| judgments.get(name).cloned().unwrap_or(0);
+    let relevant = judgments.values().filter(|&&grade| grade > 0).count();
+
+    let mut relevant_retrieved = 0;
+    let mut precision_sum = 0.0;
+    let mut reciprocal_rank = 0.0;
+    let mut relevant_at_k = 0;
+    let mut relevant_at_r = 0;
+    let mut dcg = 0.0;
+    for (rank, name) in ranking.iter().enumerate() {
+        let document_grade = grade(name);
+        if document_grade == 0 {
+            continue;
+        }
+        relevant_retrieved += 1;
+        precision_sum += relevant_retrieved as f64 / (rank + 1) as f64;
+        if reciprocal_rank == 0.0 {
+            reciprocal_rank = 1.0 / (rank + 1) as f64;
+        }
+        if rank < k {
+            relevant_at_k += 1;
+            dcg += discounted_gain(document_grade, rank);
+        }
+        if rank < relevant {
+            relevant_at_r += 1;
+        }
+    }
+
+    let mut ideal_grades: Vec<u32> = judgments.values().cloned().filter(|&grade| grade > 0).collect();
+    ideal_grades.sort_by(|a, b| b.cmp(a));
+    let ideal_dcg: f64 = ideal_grades.iter().take(k).enumerate().map(|(rank, &grade)| discounted_gain(grade, rank)).sum();
+
+    QueryEvaluation {
+        query_id: query_id.to_string(),
+        average_precision: ratio(precision_sum, relevant as f64),
+        precision_at_k: ratio(relevant_at_k as f64, k as f64),
+        r_precision: ratio(relevant_at_r as f64, relevant as f64),
+        ndcg_at_k: ratio(dcg, ideal_dcg),
+        reciprocal_rank,
+        relevant,
+        relevant_retrieved,
+        elapsed,
+        error: None,
+    }
+}
+
+/*
+ * Gain of a document with a relevance grade at a rank (starting at 0), (2^grade - 1) / log2(rank + 2)
+ */
+fn discounted_gain(grade: u32, rank: usize) -> f64 {
+    (2f64.powi(grade as i32) - 1.0) / ((rank + 2) as f64).log2()
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+/*
+ * The effectiveness of one weighting scheme over every judged query
+ */
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    pub scheme: String,
+
+    /*
+     * Cutoff rank of P@k and nDCG@k
+     */
+    pub k: usize,
+
+    /*
+     * Metrics of every query with at least one relevant document, in query file order
+     */
+    pub queries: Vec<QueryEvaluation>,
+}
+
+impl Evaluation {
+    /*
+     * The queries that could not be run, which count as retrieving nothing in the means
+     */
+    pub fn get_failed_queries(&self) -> Vec<&QueryEvaluation> {
+        self.queries.iter().filter(|query| query.error.is_some()).collect()
+    }
+
+    fn mean<F: Fn(&QueryEvaluation) -> f64>(&self, metric: F) -> f64 {
+        ratio(self.queries.iter().map(metric).sum(), self.queries.len() as f64)
+    }
+
+    pub fn mean_average_precision(&self) -> f64 {
+        self.mean(|query| query.average_precision)
+    }
+
+    pub fn mean_precision_at_k(&self) -> f64 {
+        self.mean(|query| query.precision_at_k)
+    }
+
+    pub fn mean_r_precision(&self) -> f64 {
+        self.mean(|query| query.r_precision)
+    }
+
+    pub fn mean_ndcg_at_k(&self) -> f64 {
+        self.mean(|query| query.ndcg_at_k)
+    }
+
+    pub fn mean_reciprocal_rank(&self) -> f64 {
+        self.mean(|query| query.reciprocal_rank)
+    }
+
+    pub fn mean_response_time(&self) -> Duration {
+        match self.queries.len() {
+            0 => Duration::from_secs(0),
+            count => self.queries.iter().map(|query| query.elapsed).sum::<Duration>() / count as u32,
+        }
+    }
+}
+
+/*
+ * Runs every judged query through ranked retrieval with a weighting scheme and measures the
+ * rankings. Documents are matched to the judgments by the file names (or paths) of the doc table.
+ * Queries without a relevant document are left out, as trec_eval does. A query that cannot be
+ * parsed is recorded with its error and every metric at 0, and the other queries are still run
+ *
+ * # Arguments
+ *
+ * *`options` - The ranked retrieval options, whose limit is the evaluation depth
+ * *`topics` - The queries
+ * *`qrels` - The relevance judgments
+ * *`index` - The Disk Inverted Index being evaluated
+ * *`kgram` - The K Gram Index used to expand wildcards
+ * *`k` - Cutoff rank of P@k and nDCG@k
+ *
+ * # Returns
+ *
+ * The evaluation, or an error if a postings list or a document path cannot be read
+ */
+pub fn evaluate(
+    options: &SearchOptions,
+    topics: &[Topic],
+    qrels: &Qrels,
+    index: &DiskInvertedIndex,
+    kgram: &KGramIndex,
+    k: usize,
+) -> Result<Evaluation, &'static str> {
+    let parser = QueryParser::new();
+    let number_of_docs = index.get_num_documents().unwrap_or(0);
+    let mut queries: Vec<QueryEvaluation> = Vec::new();
+    for topic in topics {
+        let judgments = match qrels.get(&topic.id) {
+            Some(judgments) if judgments.values().any(|&grade| grade > 0) => judgments,
+            _ => continue,
+        };
+        let start = Instant::now();
+        let query = match parser.parse(&topic.query) {
+            Ok(query) => query,
+            Err(error) => {
+                let mut failed = evaluate_ranking(&topic.id, &[], judgments, k, Duration::from_secs(0));
+                failed.error = Some(error);
+                queries.push(failed);
+                continue;
+            }
+        };
+        let (ranked, _, _) = query_processor::rank_query(&query, options, index, kgram, number_of_docs)?;
+        let elapsed = start.elapsed();
+
+        let mut ranking: Vec<String> = Vec::new();
+        for document in ranked {
+            let path = index.get_document_path(document.doc_id)?;
+            // Judgments may name documents by path or by file name
+            if judgments.contains_key(&path) {
+                ranking.push(path);
+            } else {
+                let name = Path::new(&path).file_name().and_then(|name| name.to_str()).unwrap_or(&path).to_string();
+                ranking.push(name);
+            }
+        }
+        queries.push(evaluate_ranking(&topic.id, &ranking, judgments, k, elapsed));
+    }
+    Ok(Evaluation {
+        scheme: options.scorer.name().to_string(),
+        k,
+        queries,
+    })
+}
+
+/*
+ * Evaluates several weighting schemes over the same queries, see evaluate
+ *
+ * # Arguments
+ *
+ * *`scorers` - The registered weighting schemes
+ * *`schemes` - Names of the schemes to compare
+ * *`topics` - The queries
+ * *`qrels` - The relevance judgments
+ * *`index` - The Disk Inverted Index being evaluated
+ * *`kgram` - The K Gram Index used to expand wildcards
+ * *`k` - Cutoff rank of P@k and nDCG@k
+ *
+ * # Returns
+ *
+ * The evaluation of every scheme in the order given, or an error if a scheme is not registered
+ */
+pub fn compare_schemes(
+    scorers: &ScorerRegistry,
+    schemes: &[&str],
+    topics: &[Topic],
+    qrels: &Qrels,
+    index: &DiskInvertedIndex,
+    kgram: &KGramIndex,
+    k: usize,
+) -> Result<Vec<Evaluation>, &'static str> {
+    let mut evaluations: Vec<Evaluation> = Vec::new();
+    for scheme in schemes {
+        let mut options = SearchOptions::new(true, scorers.get(scheme)?);
+        options.limit = EVALUATION_DEPTH;
+        options.snippet_length = 0;
+        evaluations.push(evaluate(&options, topics, qrels, index, kgram, k)?);
+    }
+    Ok(evaluations)
+}
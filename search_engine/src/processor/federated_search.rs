@@ -0,0 +1,113 @@
+use index::disk_inverted_index::DiskInvertedIndex;
+use index::k_gram_index::KGramIndex;
+use parser::query_parser::QueryParser;
+use processor::query_processor;
+use processor::query_processor::SearchOptions;
+use processor::search_results::FederatedHit;
+use processor::search_results::FederatedResults;
+use processor::search_results::SearchResults;
+use std::cmp::Ordering;
+use std::thread;
+use std::time::Instant;
+
+/*
+ * An index searched by a federated query, under the name its hits are tagged with
+ */
+#[derive(Clone, Copy)]
+pub struct SearchTarget<'i> {
+    pub name: &'i str,
+    pub index: &'i DiskInvertedIndex,
+
+    /*
+     * The K Gram Index expanding wildcards in the index
+     */
+    pub kgram: &'i KGramIndex,
+}
+
+impl<'i> SearchTarget<'i> {
+    pub fn new(name: &'i str, index: &'i DiskInvertedIndex, kgram: &'i KGramIndex) -> SearchTarget<'i> {
+        SearchTarget { name, index, kgram }
+    }
+}
+
+/*
+ * Processes a query against several indexes at once and merges their results into one page.
+ * Every index is searched on a thread of its own.
+ *
+ * Boolean results are listed index by index, in the order of the targets, and by document ID
+ * within an index. Ranked results are ordered by score, with ties broken by target order then
+ * document ID. Unless the options already hold global statistics, ranked queries are scored with
+ * the statistics of every index combined (number of documents, average document length, document
+ * and collection frequencies), so a score means the same in every index
+ *
+ * # Arguments
+ *
+ * *`options` - How the query is answered and which page of the merged results is returned
+ * *`input` - The query inputted and will be processed
+ * *`targets` - The indexes searched
+ *
+ * # Returns
+ *
+ * The page of merged results, or an error if the query cannot be parsed or a postings list or
+ * document cannot be read from an index
+ */
+pub fn process_query(options: &SearchOptions, input: &str, targets: &[SearchTarget]) -> Result<FederatedResults, &'static str> {
+    let start = Instant::now();
+    let query = QueryParser::new().parse(input)?;
+
+    let global;
+    let mut statistics = options.statistics;
+    if options.ranked_retrieval && statistics.is_none() {
+        let indexes: Vec<(&DiskInvertedIndex, &KGramIndex)> = targets.iter().map(|target| (target.index, target.kgram)).collect();
+        global = query_processor::global_statistics(&query, options.expansion, &indexes)?;
+        statistics = Some(&global);
+    }
+
+    // Every index returns the documents up to the end of the page, which is then cut from the merge
+    let target_options = SearchOptions {
+        offset: 0,
+        limit: options.offset.saturating_add(options.limit),
+        statistics,
+        ..*options
+    };
+    let results: Vec<Result<SearchResults, &'static str>> = thread::scope(|scope| {
+        let searches: Vec<_> = targets
+            .iter()
+            .map(|target| {
+                let target_options = &target_options;
+                scope.spawn(move || query_processor::process_query(target_options, input, target.index, target.kgram))
+            })
+            .collect();
+        searches
+            .into_iter()
+            .map(|search| search.join().unwrap_or(Err("Search of an index failed")))
+            .collect()
+    });
+
+    let mut total = 0;
+    let mut total_is_lower_bound = false;
+    let mut hits: Vec<FederatedHit> = Vec::new();
+    for (target, results) in targets.iter().zip(results) {
+        let results = results?;
+        total += results.total;
+        total_is_lower_bound |= results.total_is_lower_bound;
+        hits.extend(results.hits.into_iter().map(|hit| FederatedHit { index: target.name.to_string(), hit }));
+    }
+    if options.ranked_retrieval {
+        // The sort is stable, so hits of equal score keep the order of their target and rank
+        hits.sort_by(|a, b| {
+            let (a, b) = (a.hit.score.unwrap_or(0.0), b.hit.score.unwrap_or(0.0));
+            b.partial_cmp(&a).unwrap_or(Ordering::Equal)
+        });
+    }
+    let hits: Vec<FederatedHit> = hits.into_iter().skip(options.offset).take(options.limit).collect();
+
+    Ok(FederatedResults {
+        hits,
+        total,
+        total_is_lower_bound,
+        offset: options.offset,
+        limit: options.limit,
+        elapsed: start.elapsed(),
+    })
+}
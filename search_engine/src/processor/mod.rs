@@ -1,2 +1,11 @@
 pub mod query_processor;
 pub mod document_accumulator;
+pub mod evaluation;
+pub mod federated_search;
+pub mod proximity;
+pub mod relevance_feedback;
+pub mod scorer;
+pub mod search_results;
+pub mod snippet;
+pub mod suggest;
+pub mod wand;
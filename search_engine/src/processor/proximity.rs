@@ -0,0 +1,107 @@
+/*
+ * Term-pair proximity boost for ranked retrieval, in the style of BM25TP (Rasolofo & Savoy).
+ *
+ * Every pair of distinct query terms occurring at most `window` positions apart in a document
+ * contributes 1 / distance^2. The sum is saturated like a BM25 term frequency and weighted by the
+ * smaller query weight of the pair, so documents where the query terms appear close together
+ * rank higher.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct ProximityBoost {
+    /*
+     * Largest distance between two positions still considered close
+     */
+    pub window: u32,
+
+    /*
+     * Saturation of the accumulated proximity
+     */
+    pub k1: f64,
+
+    /*
+     * Strength of document length normalization
+     */
+    pub b: f64,
+
+    /*
+     * Multiplier applied to the boost before it is added to a document's score
+     */
+    pub weight: f64,
+}
+
+impl Default for ProximityBoost {
+    fn default() -> ProximityBoost {
+        ProximityBoost::new()
+    }
+}
+
+impl ProximityBoost {
+    /*
+     * Constructs a ProximityBoost with the parameters suggested for BM25TP
+     *
+     * # Returns
+     *
+     * A boost using a window of 5 positions, k1 = 1.2 and b = 0.75
+     */
+    pub fn new() -> ProximityBoost {
+        ProximityBoost {
+            window: 5,
+            k1: 1.2,
+            b: 0.75,
+            weight: 1.0,
+        }
+    }
+
+    /*
+     * Calculates the proximity boost of a document
+     *
+     * # Arguments
+     *
+     * *`terms` - For each distinct query term found in the document, its query weight and its
+     * sorted positions in the document
+     * *`doc_length` - Length of the document
+     * *`avg_doc_length` - Average length of the documents in the index
+     *
+     * # Returns
+     *
+     * The amount to add to the document's score
+     */
+    pub fn score(&self, terms: &[(f64, &[u32])], doc_length: f64, avg_doc_length: f64) -> f64 {
+        let length_ratio = if avg_doc_length > 0.0 { doc_length / avg_doc_length } else { 1.0 };
+        let normalizer = self.k1 * ((1.0 - self.b) + self.b * length_ratio);
+
+        let mut boost = 0.0;
+        for (i, &(first_weight, first_positions)) in terms.iter().enumerate() {
+            for &(second_weight, second_positions) in terms.iter().skip(i + 1) {
+                let proximity = self.pair_proximity(first_positions, second_positions);
+                if proximity == 0.0 {
+                    continue;
+                }
+                let saturated = (self.k1 + 1.0) * proximity / (normalizer + proximity);
+                boost += first_weight.min(second_weight) * saturated;
+            }
+        }
+        self.weight * boost
+    }
+
+    /*
+     * Sums 1 / distance^2 over every pair of positions of two terms lying within the window
+     */
+    fn pair_proximity(&self, first_positions: &[u32], second_positions: &[u32]) -> f64 {
+        let mut proximity = 0.0;
+        for &position in first_positions {
+            let lowest = position.saturating_sub(self.window);
+            let start = second_positions.partition_point(|&other| other < lowest);
+            for &other in second_positions[start..].iter() {
+                if other > position + self.window {
+                    break;
+                }
+                if other != position {
+                    let distance = (other as f64 - position as f64).abs();
+                    proximity += 1.0 / (distance * distance);
+                }
+            }
+        }
+        proximity
+    }
+}
@@ -1,444 +1,963 @@
-use index::positional_inverted_index::PositionalPosting;
 use index::disk_inverted_index::DiskInvertedIndex;
 use index::disk_inverted_index::IndexReader;
 use index::k_gram_index::KGramIndex;
+use index::postings_cursor::PostingsCursor;
+use classifier::classifier::TermComponentScore;
 use parser::document_parser;
 use parser::query_parser::QueryParser;
-use processor::document_accumulator::DocumentAccumulator;
+use parser::query_parser::QueryNode;
+use parser::query_parser::ProximityKind;
+use processor::proximity::ProximityBoost;
+use processor::scorer::CollectionStatistics;
+use processor::scorer::DocumentStatistics;
+use processor::scorer::GlobalStatistics;
+use processor::scorer::Scorer;
+use processor::scorer::TermStatistics;
+use processor::search_results::RankedDocument;
+use processor::search_results::ScoreComponent;
+use processor::search_results::SearchHit;
+use processor::search_results::SearchResults;
+use processor::snippet;
+use processor::snippet::TermOccurrences;
+use processor::wand;
+use processor::wand::Pruning;
+use processor::wand::TermCursor;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::collections::BinaryHeap;
-use std::path::*;
+use std::collections::BTreeMap;
+use std::time::Instant;
 
-pub fn process_query(
-    ranked_retrieval: bool,
-    scheme: &str,
-    input: &str,
-    index: &DiskInvertedIndex,
-    kgram: &KGramIndex,
-    id_file: &HashMap<u32, String>,
-) -> HashSet<String> {
+/*
+ * Options controlling how a query is answered
+ */
+pub struct SearchOptions<'s> {
+    /*
+     * Whether documents are ranked by score rather than matched as a boolean query
+     */
+    pub ranked_retrieval: bool,
 
-    let result : HashSet<String>;
-    if ranked_retrieval {
-        result = process_query_rank(scheme, input, index, kgram, id_file);
-    } else { 
-        result = process_query_bool(input,  index, kgram, id_file); 
-    }
-    result
+    /*
+     * The weighting scheme used in ranked retrieval
+     */
+    pub scorer: &'s dyn Scorer,
+
+    /*
+     * Term-pair proximity boost added to every score, if enabled
+     */
+    pub proximity: Option<&'s ProximityBoost>,
+
+    /*
+     * Strategy used to find the top documents. Pruning only applies to plain term queries ranked by
+     * a scorer with score bounds and without a proximity boost; other queries are scored
+     * exhaustively
+     */
+    pub pruning: Pruning,
+
+    /*
+     * Weights of normalized terms of a ranked query, such as the query vector built by relevance
+     * feedback. The query weight of a listed term is multiplied by its score instead of counting
+     * once per occurrence in the query, listed terms missing from the query are added to it, and
+     * terms with a score of 0 or less are not scored
+     */
+    pub expansion: &'s [TermComponentScore],
+
+    /*
+     * Number of documents skipped before the returned page
+     */
+    pub offset: usize,
 
+    /*
+     * Largest number of documents returned
+     */
+    pub limit: usize,
+
+    /*
+     * Number of tokens in the snippet of every returned document, or 0 for no snippets
+     */
+    pub snippet_length: usize,
+
+    /*
+     * Statistics of several indexes searched together, used in ranked retrieval instead of those
+     * of the searched index
+     */
+    pub statistics: Option<&'s GlobalStatistics>,
 }
 
+impl<'s> SearchOptions<'s> {
+    /*
+     * Constructs the options of a search returning the first 10 documents
+     *
+     * # Arguments
+     *
+     * *`ranked_retrieval` - Whether documents are ranked by score
+     * *`scorer` - The weighting scheme used in ranked retrieval
+     *
+     * # Returns
+     *
+     * Options without a proximity boost, expansion terms or global statistics, pruned with
+     * Block-Max WAND, with snippets of 24 tokens
+     */
+    pub fn new(ranked_retrieval: bool, scorer: &'s dyn Scorer) -> SearchOptions<'s> {
+        SearchOptions {
+            ranked_retrieval,
+            scorer,
+            proximity: None,
+            pruning: Pruning::BlockMaxWand,
+            expansion: &[],
+            offset: 0,
+            limit: 10,
+            snippet_length: 24,
+            statistics: None,
+        }
+    }
+}
 
 /*
- * Processes a query and returns results containing the files fulfilling the query
+ * Processes a query and returns one page of the documents fulfilling it. Boolean results are
+ * ordered by document ID, ranked results by score
  *
  * # Arguments
  *
+ * *`options` - How the query is answered and which page is returned
  * *`input` - The query inputted and will be processed
- * *`index` - The Positional Inverted Index that will be used
- * *`id_file` - HashMap containing the associations of the document id and file
+ * *`index` - The Disk Inverted Index that will be used
+ * *`kgram` - The K Gram Index used to expand wildcards
  *
  * # Returns
  *
- * The HashSet containing the files fulfilling the query
+ * The page of results, or an error if the query cannot be parsed, a postings list cannot be read
+ * or a document cannot be read from the document store or doc table
  */
-pub fn process_query_bool(
+pub fn process_query(
+    options: &SearchOptions,
     input: &str,
     index: &DiskInvertedIndex,
     kgram: &KGramIndex,
-    id_file: &HashMap<u32, String>,
-) -> HashSet<String> {
+) -> Result<SearchResults, &'static str> {
+    let start = Instant::now();
+    let number_of_docs = index.get_num_documents().unwrap_or(0);
     let parser = QueryParser::new();
-    let processed_query = QueryParser::process_query(&parser, input);
-    println!("Processed Query: {:?}", processed_query);
-
-    let mut results: HashSet<String> = HashSet::new();
-    let mut or_results = Vec::new();
-    let mut and_entries_precursor_string_vec = Vec::new(); // Dirty hack to get around lifetimes...
-    for query in processed_query {
-        and_entries_precursor_string_vec.clear(); // Need to clear it here and only here...
-        println!("Query For: {}", query);
-        let mut and_entries = Vec::new();
-        let and_entries_precursor: Vec<&str> = query.split_whitespace().collect();
-        for item in and_entries_precursor {
-            and_entries_precursor_string_vec.push(String::from(item));
-        }
-        let mut and_entries_precursor_iter = and_entries_precursor_string_vec.iter();
-        let mut entry_builder: Vec<String> = Vec::new();
-        while let Some(entry) = and_entries_precursor_iter.next() {
-            if entry.starts_with("\"") || entry.starts_with("-\"") {
-                let mut modified_entry : String = match entry.starts_with("\"") {
-                    true => {
-                        let new_query = entry.chars().skip(1).collect();
-                        new_query
-                    },
-                    false => {
-                        let mut prefix = String::from("-");
-                        let rest_of_query : String = entry.chars().skip(2).collect();
-                        prefix.push_str(rest_of_query.as_str());
-                        prefix
-                    }
-                };
-                entry_builder.push(modified_entry);
-                while let Some(next_entry) = and_entries_precursor_iter.next() {
-                    if next_entry.ends_with("\"") {
-                        modified_entry = next_entry.chars().take(next_entry.len() - 1).collect();
-                        entry_builder.push(modified_entry);
-                        and_entries.push(entry_builder.join(" "));
-                        entry_builder.clear();
-                        break;
-                    }
-                    entry_builder.push(next_entry.to_string());
-                }
-                continue;
-            }
-            and_entries.push(String::from(entry.clone()));
+    let query = parser.parse(input)?;
+
+    let mut page: Vec<(u32, Option<f64>, Vec<ScoreComponent>)> = Vec::new();
+    let mut total_is_lower_bound = false;
+    let total = if options.ranked_retrieval {
+        let (ranked, matching, lower_bound) = rank_query(&query, options, index, kgram, number_of_docs)?;
+        for document in ranked {
+            page.push((document.doc_id, Some(document.score), document.explanation));
         }
-        // Should check if NEAR/K is in this query... if so, call function to handle... add to and
-        // results....
-        let mut and_results = Vec::new();
-        let mut not_results = Vec::new();
+        total_is_lower_bound = lower_bound;
+        matching
+    } else {
+        let all_doc_ids: Vec<u32> = (0..number_of_docs).collect();
 
-        let mut new_and_entries : Vec<String> = Vec::new();
+        let matches = evaluate_query(&query, index, kgram, &all_doc_ids)?;
+        for &doc_id in matches.iter().skip(options.offset).take(options.limit) {
+            page.push((doc_id, None, Vec::new()));
+        }
+        matches.len()
+    };
 
-        for entry in and_entries {
-            if entry.contains("*") {
-                let mut results = get_wildcards(&entry, kgram);
-                new_and_entries.append(&mut results);
-            } else {
-                println!("NOT WILDCARD: {}", entry);
-                new_and_entries.push(entry); 
-            }
+    let mut occurrences: HashMap<u32, Vec<TermOccurrences>> = HashMap::new();
+    if options.snippet_length > 0 {
+        let doc_ids: Vec<u32> = page.iter().map(|hit| hit.0).collect();
+        occurrences = snippet_occurrences(&query, &doc_ids, index, kgram)?;
+    }
+    let mut hits: Vec<SearchHit> = Vec::new();
+    for (doc_id, score, explanation) in page {
+        let path = index.get_document_path(doc_id)?;
+        let document = index.get_document(doc_id)?;
+        let mut snippet = None;
+        if options.snippet_length > 0 {
+            let terms = occurrences.remove(&doc_id).unwrap_or_default();
+            snippet = Some(snippet::generate(&document.body, &terms, options.snippet_length));
         }
-        println!("Full Query: {:?}", new_and_entries);
+        hits.push(SearchHit {
+            doc_id,
+            path,
+            title: document.title,
+            score,
+            explanation,
+            snippet,
+        });
+    }
 
-        if query.contains("NEAR/") {
-            let near_k_results: Vec<u32> = near_query(query.clone(), index);
-            let mut near_k_inner_results = HashSet::new();
-            for result in near_k_results {
-                let file_path = id_file.get(&result).unwrap().to_string();
-                let file: &Path = file_path.as_ref();
-                let file_name = file.file_name();
-                near_k_inner_results.insert(String::from(file_name.unwrap().to_str().unwrap()));
-            }
-            if near_k_inner_results.len() != 0 {
-                and_results.push(near_k_inner_results);
+    Ok(SearchResults {
+        hits,
+        total,
+        total_is_lower_bound,
+        offset: options.offset,
+        limit: options.limit,
+        elapsed: start.elapsed(),
+    })
+}
+
+/*
+ * Reads the occurrences of the query terms in the returned documents, for their snippets
+ *
+ * # Arguments
+ *
+ * *`query` - The parsed query
+ * *`doc_ids` - The returned documents
+ * *`index` - The Disk Inverted Index being searched
+ * *`kgram` - The K Gram Index used to expand wildcards
+ *
+ * # Returns
+ *
+ * For every returned document containing a query term, the occurrences of each distinct query
+ * term, or an error if a postings list cannot be read
+ */
+fn snippet_occurrences(
+    query: &QueryNode,
+    doc_ids: &[u32],
+    index: &DiskInvertedIndex,
+    kgram: &KGramIndex,
+) -> Result<HashMap<u32, Vec<TermOccurrences>>, &'static str> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut features: Vec<&QueryNode> = Vec::new();
+    collect_ranked_features(query, kgram, &mut terms, &mut features);
+    terms.sort();
+    terms.dedup();
+
+    let wanted: HashSet<u32> = doc_ids.iter().cloned().collect();
+    let mut occurrences: HashMap<u32, Vec<TermOccurrences>> = HashMap::new();
+    for term in terms {
+        if !index.contains_term(&term) {
+            continue;
+        }
+        for (posting, offsets) in index.get_postings_with_offsets(&term)? {
+            if wanted.contains(&posting.0) {
+                occurrences.entry(posting.0).or_default().push(TermOccurrences { positions: posting.6, offsets });
             }
-        } else {
-            for entry in new_and_entries {
-                // println!("AND ENTRY DAVID {}", entry);
-                let not_query = entry.starts_with("-");
-                let phrase_literal_vec: Vec<&str> = entry.split_whitespace().collect();
-                let phrase_literal = phrase_literal_vec.len() > 1;
-                // println!("Phrase literal for {}? {}", entry, phrase_literal);
-                // println!("Not query for {}? {}", entry, not_query);
-                if phrase_literal && not_query {
-                    // println!("Handling phrase literal and not query");
-                    // strip out "-" letter... then split whitespace maybe... or not if function
-                    // takes a string
-                    // call function to get doc id. get file name, add to not list...
-                    let phrase : String = entry.chars().skip(1).collect();
-                    let results_to_remove: Vec<u32> = phrase_query(phrase, index);
-                    for doc_id in results_to_remove {
-                        let file_path =
-                            id_file.get(&doc_id).unwrap().to_string();
-                        let file: &Path = file_path.as_ref();
-                        let file_name = file.file_name();
-                        not_results.push(String::from(
-                            file_name.unwrap().to_str().unwrap(),
-                        ));
-                    }
-                }
-                else if phrase_literal && !not_query {
-                    // call function to process
-                    // read results into and results vec (might have to get file name)
-                    let phrase_literal_results: Vec<u32> = phrase_query(entry.clone(), &index);
-                    let mut phrase_literal_inner_results = HashSet::new();
-                    for result in phrase_literal_results {
-                        let file_path = id_file.get(&result).unwrap().to_string();
-                        let file: &Path = file_path.as_ref();
-                        let file_name = file.file_name();
-                        phrase_literal_inner_results.insert(String::from(
-                            file_name
-                                .unwrap()
-                                .to_str()
-                                .unwrap(),
-                        ));
-                    }
-                    and_results.push(phrase_literal_inner_results);
-                // call function to process
-                // read results into and results vec (might have to get file name)
-                }
-                else {
-                    let normalized_tokens = document_parser::normalize_token(entry.to_string());
-                    let stemmed_tokens = document_parser::stem_terms(normalized_tokens);
-                    for stemmed_token in stemmed_tokens {
-                        if !index.contains_term(stemmed_token.as_str()) {
-                            println!("Breaking because index does not contain term: {}", stemmed_token);
-                            break;
-                        }
-                        let postings = index.get_postings(stemmed_token.as_str()).expect("Failed to get postings");
-                        let mut and_inner_results = HashSet::new();
-                        for posting in postings {
-                            let doc_id = posting.0;
-                            if not_query {
-                                let file_path =
-                                    id_file.get(&doc_id).unwrap().to_string();
-                                let file: &Path = file_path.as_ref();
-                                let file_name = file.file_name();
-                                not_results.push(String::from(
-                                    file_name.unwrap().to_str().unwrap(),
-                                ));
-                            } else {
-                                let file_path =
-                                    id_file.get(&doc_id).unwrap().to_string();
-                                let file: &Path = file_path.as_ref();
-                                let file_name = file.file_name();
-                                and_inner_results.insert(String::from(
-                                    file_name.unwrap().to_str().unwrap(),
-                                ));
-                            }
-                        }
-                        if !not_query {
-                            and_results.push(and_inner_results);
-                        }
-                    }
+        }
+    }
+    Ok(occurrences)
+}
+
+/*
+ * Evaluates a parsed boolean query against the index
+ *
+ * # Arguments
+ *
+ * *`query` - The parsed query
+ * *`index` - The Disk Inverted Index that will be used
+ * *`kgram` - The K Gram Index used to expand wildcards
+ * *`all_doc_ids` - Every document id in the index, in increasing order. Negations are evaluated
+ * against this set
+ *
+ * # Returns
+ *
+ * The document ids fulfilling the query in increasing order, or an error if a postings list
+ * cannot be read
+ */
+pub fn evaluate_query(
+    query: &QueryNode,
+    index: &DiskInvertedIndex,
+    kgram: &KGramIndex,
+    all_doc_ids: &[u32],
+) -> Result<Vec<u32>, &'static str> {
+    Ok(match *query {
+        QueryNode::Term(ref token) => term_query(token, index, kgram),
+        QueryNode::Phrase(ref words) => {
+            phrase_positions(&phrase_terms(words), index)?.into_iter().map(|(doc_id, _)| doc_id).collect()
+        },
+        QueryNode::Proximity { ref operands, distance, kind } => {
+            proximity_matches(operands, distance, kind, index, kgram)?
+                .into_iter()
+                .map(|(doc_id, _)| doc_id)
+                .collect()
+        },
+        QueryNode::And(ref operands) => {
+            let mut positive: Option<Vec<u32>> = None;
+            let mut negative: Vec<u32> = Vec::new();
+            for operand in operands {
+                if let QueryNode::Not(ref inner) = *operand {
+                    negative = union_doc_ids(&negative, &evaluate_query(inner, index, kgram, all_doc_ids)?);
+                    continue;
                 }
+                let doc_ids = evaluate_query(operand, index, kgram, all_doc_ids)?;
+                positive = Some(match positive {
+                    Some(current) => intersect_doc_ids(&current, &doc_ids),
+                    None => doc_ids,
+                });
             }
-        }
-        // Let's handle the AND logic...
-        let mut and_results_iter = and_results.iter();
-        let first_and_result = match and_results_iter.next() {
-            Some(result) => result,
-            None => continue,
-        };
-        let mut intersection = HashSet::new();
-        for item in first_and_result {
-            intersection.insert(item.clone());
-        }
-        while let Some(and_result) = and_results_iter.next() {
-            let intersection_result: HashSet<_> =
-                and_result.intersection(&intersection).cloned().collect();
-            for item in intersection_result {
-                intersection.insert(item);
+            // A conjunction of negations only is evaluated against every document
+            let base = match positive {
+                Some(doc_ids) => doc_ids,
+                None => all_doc_ids.to_vec(),
+            };
+            subtract_doc_ids(&base, &negative)
+        },
+        QueryNode::Or(ref operands) => {
+            let mut doc_ids = Vec::new();
+            for operand in operands {
+                doc_ids = union_doc_ids(&doc_ids, &evaluate_query(operand, index, kgram, all_doc_ids)?);
             }
-        }
-        for not_entry in not_results {
-            if intersection.contains(&not_entry) {
-                intersection.remove(&not_entry);
+            doc_ids
+        },
+        QueryNode::Not(ref inner) => {
+            subtract_doc_ids(all_doc_ids, &evaluate_query(inner, index, kgram, all_doc_ids)?)
+        },
+    })
+}
+
+/*
+ * Retrieves the documents containing a single query token. Wildcard tokens match any of their
+ * expansions, and tokens split into several terms (e.g. hyphenated words) must match all of them
+ */
+fn term_query(token: &str, index: &DiskInvertedIndex, kgram: &KGramIndex) -> Vec<u32> {
+    if token.contains('*') {
+        let mut doc_ids = Vec::new();
+        for expansion in get_wildcards(token, kgram) {
+            for term in normalize_query_token(&expansion) {
+                doc_ids = union_doc_ids(&doc_ids, &term_doc_ids(&term, index));
             }
         }
-        or_results.push(intersection);
+        return doc_ids;
     }
-    // Let's handle the OR logic...
-    let mut or_results_iter = or_results.iter();
-    let first_or_result = match or_results_iter.next() {
-        Some(result) => result,
-        None => {
-            return HashSet::new();
+    let mut result: Option<Vec<u32>> = None;
+    for term in normalize_query_token(token) {
+        let doc_ids = term_doc_ids(&term, index);
+        result = Some(match result {
+            Some(current) => intersect_doc_ids(&current, &doc_ids),
+            None => doc_ids,
+        });
+    }
+    result.unwrap_or_default()
+}
+
+/*
+ * Returns the ids of documents containing an already normalized term
+ */
+fn term_doc_ids(term: &str, index: &DiskInvertedIndex) -> Vec<u32> {
+    match index.get_postings_no_positions(term) {
+        Ok(postings) => postings.iter().map(|posting| posting.0).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/*
+ * Normalizes and stems a query token the same way documents are indexed
+ */
+fn normalize_query_token(token: &str) -> Vec<String> {
+    let normalized_tokens = document_parser::normalize_token(token.to_string());
+    document_parser::stem_terms(normalized_tokens)
+        .into_iter()
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+/*
+ * Merges two sorted lists of document ids, keeping the ids present in both
+ */
+pub fn intersect_doc_ids(first: &[u32], second: &[u32]) -> Vec<u32> {
+    let mut merged = Vec::new();
+    let mut i = 0;
+    let mut j = 0;
+    while i < first.len() && j < second.len() {
+        if first[i] == second[j] {
+            merged.push(first[i]);
+            i += 1;
+            j += 1;
+        } else if first[i] < second[j] {
+            i += 1;
+        } else {
+            j += 1;
         }
-    };
-    let mut union = HashSet::new();
-    for item in first_or_result {
-        union.insert(item.clone());
     }
-    while let Some(or_result) = or_results_iter.next() {
-        let union_result: HashSet<_> = or_result.union(&union).cloned().collect();
-        union.clear();
-        for item in union_result {
-            union.insert(item);
+    merged
+}
+
+/*
+ * Merges two sorted lists of document ids, keeping the ids present in either
+ */
+pub fn union_doc_ids(first: &[u32], second: &[u32]) -> Vec<u32> {
+    let mut merged = Vec::with_capacity(first.len() + second.len());
+    let mut i = 0;
+    let mut j = 0;
+    while i < first.len() && j < second.len() {
+        if first[i] == second[j] {
+            merged.push(first[i]);
+            i += 1;
+            j += 1;
+        } else if first[i] < second[j] {
+            merged.push(first[i]);
+            i += 1;
+        } else {
+            merged.push(second[j]);
+            j += 1;
         }
     }
-    for x in union {
-        results.insert(x);
+    merged.extend_from_slice(&first[i..]);
+    merged.extend_from_slice(&second[j..]);
+    merged
+}
+
+/*
+ * Merges two sorted lists of document ids, keeping the ids of the first list absent from the
+ * second
+ */
+pub fn subtract_doc_ids(first: &[u32], second: &[u32]) -> Vec<u32> {
+    let mut merged = Vec::new();
+    let mut j = 0;
+    for &doc_id in first {
+        while j < second.len() && second[j] < doc_id {
+            j += 1;
+        }
+        if j >= second.len() || second[j] != doc_id {
+            merged.push(doc_id);
+        }
     }
-    results
+    merged
 }
 
-fn process_query_rank(
-    scheme: &str,
-    input: &str,
+/*
+ * A scored part of a ranked query: a term, or a phrase or proximity clause scored like an extra
+ * term whose occurrences are the places where the clause matches. The postings of a term are left
+ * empty when it is ranked with pruning, which reads them block by block instead
+ */
+struct RankedFeature {
+    label: String,
+    statistics: TermStatistics,
+    query_weight: f64,
+
+    /*
+     * Multiplier of the scorer's query weight, given by the expansion, included in query_weight
+     */
+    weight: f64,
+    postings: Vec<(u32, u32)>,
+}
+
+/*
+ * Ranks the documents matching a parsed query
+ *
+ * # Arguments
+ *
+ * *`query` - The parsed query
+ * *`options` - How documents are scored and which page is returned
+ * *`index` - The Disk Inverted Index being searched
+ * *`kgram` - The K Gram Index used to expand wildcards
+ * *`number_of_docs` - Number of documents in the index
+ *
+ * # Returns
+ *
+ * The documents of the requested page, best first with ties broken by lower document ID, the
+ * number of documents containing a query term or clause with a positive query weight, and whether
+ * that number is only a lower bound. Pruning skips documents without reading their postings, so
+ * it cannot count every match; it counts enough of them to tell whether documents remain after
+ * the page. An error is returned if a postings list cannot be read
+ */
+pub fn rank_query(
+    query: &QueryNode,
+    options: &SearchOptions,
     index: &DiskInvertedIndex,
     kgram: &KGramIndex,
-    id_file: &HashMap<u32, String>,
-) -> HashSet<String> {
+    number_of_docs: u32,
+) -> Result<(Vec<RankedDocument>, usize, bool), &'static str> {
+    let scorer = options.scorer;
+    let proximity = options.proximity;
 
-    let parser = QueryParser::new();
-    let processed_query = QueryParser::process_query(&parser, input);
-    println!("Processed Query: {:?}", processed_query);
-
-    if processed_query.len() > 1 {
-        println!("Invalid Query"); 
-        return HashSet::new();
-    }
-    let mut and_entries_precursor_string_vec = Vec::new(); // Dirty hack to get around lifetimes...
-    let results: HashSet<String> = HashSet::new();
-    for query in processed_query {
-        and_entries_precursor_string_vec.clear(); // Need to clear it here and only here...
-        println!("Query For: {}", query);
-        let mut and_entries = Vec::new();
-        let and_entries_precursor: Vec<&str> = query.split_whitespace().collect();
-        for item in and_entries_precursor {
-            and_entries_precursor_string_vec.push(String::from(item));
-        }
-        let mut and_entries_precursor_iter = and_entries_precursor_string_vec.iter();
-        let mut entry_builder: Vec<String> = Vec::new();
-        while let Some(entry) = and_entries_precursor_iter.next() {
-            if entry.starts_with("\"") || entry.starts_with("-\"") {
-                let mut modified_entry : String = match entry.starts_with("\"") {
-                    true => {
-                        let new_query = entry.chars().skip(1).collect();
-                        new_query
-                    },
-                    false => {
-                        let mut prefix = String::from("-");
-                        let rest_of_query : String = entry.chars().skip(2).collect();
-                        prefix.push_str(rest_of_query.as_str());
-                        prefix
-                    }
-                };
-                entry_builder.push(modified_entry);
-                while let Some(next_entry) = and_entries_precursor_iter.next() {
-                    if next_entry.ends_with("\"") {
-                        modified_entry = next_entry.chars().take(next_entry.len() - 1).collect();
-                        entry_builder.push(modified_entry);
-                        and_entries.push(entry_builder.join(" "));
-                        entry_builder.clear();
-                        break;
-                    }
-                    entry_builder.push(next_entry.to_string());
-                }
-                continue; // Why?
-            }
-            and_entries.push(String::from(entry.clone()));
+    let mut terms: Vec<String> = Vec::new();
+    let mut features: Vec<&QueryNode> = Vec::new();
+    collect_ranked_features(query, kgram, &mut terms, &mut features);
+    let prunable = options.pruning != Pruning::Exhaustive && proximity.is_none() && features.is_empty();
+
+    let collection = match options.statistics {
+        Some(global) => global.collection,
+        None => collection_statistics(index, number_of_docs),
+    };
+    let mut documents: HashMap<u32, DocumentStatistics> = HashMap::new();
+
+    // Query weight and positions of every distinct term, per document, for the proximity boost
+    let mut term_positions: Vec<(f64, HashMap<u32, Vec<u32>>)> = Vec::new();
+    let mut seen_terms: HashSet<String> = HashSet::new();
+
+    // Query terms weighted by the expansion take its weight once, other query terms weigh 1 per
+    // occurrence
+    let mut weighted_terms: Vec<(String, f64)> = Vec::new();
+    for term in terms.into_iter().chain(options.expansion.iter().map(|expansion| expansion.term.clone())) {
+        match options.expansion.iter().find(|expansion| expansion.term == term) {
+            Some(_) if weighted_terms.iter().any(|(weighted, _)| *weighted == term) => {},
+            Some(expansion) => weighted_terms.push((term, expansion.score)),
+            None => weighted_terms.push((term, 1.0)),
         }
-        // Should check if NEAR/K is in this query... if so, call function to handle... add to and
-        // results....
+    }
+    weighted_terms.retain(|&(_, weight)| weight > 0.0);
 
-        let mut new_and_entries : Vec<String> = Vec::new();
+    let mut ranked_features: Vec<RankedFeature> = Vec::new();
+    for (term, weight) in weighted_terms {
+        if !index.contains_term(&term) {
+            continue;
+        }
+        let statistics = global_term_statistics(options, &term).unwrap_or_else(|| TermStatistics {
+            document_frequency: index.get_document_frequency(&term),
+            collection_frequency: index.get_term_frequency(&term) as u64,
+        });
+        let query_weight = weight * scorer.query_weight(&collection, &statistics);
+        let postings: Vec<(u32, u32)> = if proximity.is_some() {
+            let postings = index.get_postings(&term)?;
+            let mut positions: HashMap<u32, Vec<u32>> = HashMap::new();
+            let mut frequencies = Vec::with_capacity(postings.len());
+            for posting in postings {
+                frequencies.push((posting.0, posting.1));
+                positions.insert(posting.0, posting.6);
+            }
+            if seen_terms.insert(term.clone()) {
+                term_positions.push((query_weight, positions));
+            }
+            frequencies
+        } else if prunable {
+            Vec::new()
+        } else {
+            term_postings(index, &term)?
+        };
+        ranked_features.push(RankedFeature { label: term, statistics, query_weight, weight, postings });
+    }
+
+    for feature in features {
+        let matches = feature_matches(feature, index, kgram)?;
+        if matches.is_empty() {
+            continue;
+        }
+        let label = feature_label(feature);
+        let statistics = global_term_statistics(options, &label).unwrap_or_else(|| TermStatistics {
+            document_frequency: matches.len() as u32,
+            collection_frequency: matches.iter().map(|&(_, match_count)| match_count as u64).sum(),
+        });
+        let query_weight = scorer.query_weight(&collection, &statistics);
+        ranked_features.push(RankedFeature {
+            label,
+            statistics,
+            query_weight,
+            weight: 1.0,
+            postings: matches,
+        });
+    }
 
-        if kgram.is_enabled() {
-            for entry in and_entries {
-                if entry.contains("*") {
-                    let mut results = get_wildcards(&entry, kgram);
-                    new_and_entries.append(&mut results);
-                } else {
-                    println!("NOT WILDCARD: {}", entry);
-                    new_and_entries.push(entry); 
+    let k = options.offset.saturating_add(options.limit);
+    let mut ranked = None;
+    if prunable {
+        // One document more than the page needs shows whether documents remain after it
+        ranked = rank_with_pruning(&ranked_features, options, k.saturating_add(1), index, &collection, &mut documents);
+    }
+    let pruned = ranked.is_some();
+    let (ranked, matching) = match ranked {
+        Some(mut ranked) => {
+            let largest_list = ranked_features
+                .iter()
+                .filter(|feature| feature.query_weight > 0.0)
+                .map(|feature| index.get_document_frequency(&feature.label) as usize)
+                .max()
+                .unwrap_or(0);
+            let matching = largest_list.max(ranked.len());
+            ranked.truncate(k);
+            (ranked, matching)
+        },
+        None => {
+            if prunable {
+                for feature in ranked_features.iter_mut() {
+                    feature.postings = term_postings(index, &feature.label)?;
                 }
             }
-        } else {
-            for entry in and_entries {
-                new_and_entries.push(entry);
-            } 
-        }
-
-        println!("Full Query: {:?}", new_and_entries);
-
-        let mut accumulators : BinaryHeap<DocumentAccumulator> = BinaryHeap::new(); 
-
-        let mut doc_accs : HashMap <u32, f64> = HashMap::new();
-
-        let number_of_docs = id_file.len();
-        // println!("Number of docs: {}" , number_of_docs);
-        for entry in new_and_entries {
-            let normalized_tokens = document_parser::normalize_token(entry.to_string());
-            let stemmed_tokens = document_parser::stem_terms(normalized_tokens);
-            for stemmed_token in stemmed_tokens {
-                let wqt = get_wqt(scheme, number_of_docs as u32, &stemmed_token, index);
-                let postings = index.get_postings_no_positions(&stemmed_token).expect("Failed to get postings");
-                for posting in postings {
-                    let doc_id = posting.0;
-                    let term_doc_frequency = posting.1;
-                    let wdt = get_wdt(scheme, doc_id, &stemmed_token, term_doc_frequency, index);
-                    let accumulator : f64 = wqt * wdt;
-                    if doc_accs.contains_key(&doc_id) {
-                        *doc_accs.get_mut(&doc_id).unwrap() += accumulator;
-                    } else {
-                        doc_accs.insert(doc_id, accumulator); 
+            let mut doc_accs: HashMap<u32, f64> = HashMap::new();
+            let mut matching: HashSet<u32> = HashSet::new();
+            for feature in ranked_features.iter() {
+                for &(doc_id, term_doc_frequency) in feature.postings.iter() {
+                    let document = document_statistics(doc_id, index, &mut documents);
+                    let wdt = scorer.document_weight(&collection, &feature.statistics, &document, term_doc_frequency);
+                    *doc_accs.entry(doc_id).or_insert(0.0) += feature.query_weight * wdt;
+                    if feature.query_weight > 0.0 {
+                        matching.insert(doc_id);
+                    }
+                }
+            }
+
+            let query_length = ranked_features.len() as u32;
+            let mut ranked: Vec<(u32, f64)> = Vec::new();
+            for (doc, acc) in doc_accs {
+                if acc > 0.0 {
+                    let document = document_statistics(doc, index, &mut documents);
+                    let mut new_acc = scorer.document_score(&collection, &document, acc, query_length);
+                    if let Some(boost) = proximity {
+                        new_acc += proximity_score(boost, &collection, &document, &term_positions);
                     }
+                    ranked.push((doc, new_acc));
                 }
             }
+            wand::sort_results(&mut ranked);
+            ranked.truncate(k);
+            (ranked, matching.len())
         }
+    };
 
-        for (doc, acc) in doc_accs {
-            if acc > 0.0 {
-                let ld = get_ld(scheme, doc, index);
-                let new_acc = (acc)/(ld);
-                let new_doc_acc : DocumentAccumulator = DocumentAccumulator::new(doc, new_acc); 
-                accumulators.push(new_doc_acc);
+    let mut page: Vec<RankedDocument> = Vec::new();
+    for (doc_id, score) in ranked.into_iter().skip(options.offset) {
+        let document = document_statistics(doc_id, index, &mut documents);
+        let mut explanation: Vec<ScoreComponent> = Vec::new();
+        // Pruned postings were never all decoded, so the document's own term vector is read instead
+        let document_vector = if pruned { index.get_document_vector(doc_id).unwrap_or_default() } else { Vec::new() };
+        for feature in ranked_features.iter() {
+            let term_frequency = if pruned {
+                index
+                    .get_term_id(&feature.label)
+                    .and_then(|term_id| document_vector.binary_search_by_key(&term_id, |entry| entry.0).ok())
+                    .map(|found| document_vector[found].1)
+            } else {
+                feature
+                    .postings
+                    .binary_search_by_key(&doc_id, |posting| posting.0)
+                    .ok()
+                    .map(|found| feature.postings[found].1)
+            };
+            if let Some(term_frequency) = term_frequency {
+                let wdt = scorer.document_weight(&collection, &feature.statistics, &document, term_frequency);
+                explanation.push(ScoreComponent {
+                    label: feature.label.clone(),
+                    term_frequency,
+                    score: feature.query_weight * wdt,
+                });
+            }
+        }
+        if let Some(boost) = proximity {
+            let boost_score = proximity_score(boost, &collection, &document, &term_positions);
+            if boost_score > 0.0 {
+                explanation.push(ScoreComponent {
+                    label: String::from("proximity"),
+                    term_frequency: 0,
+                    score: boost_score,
+                });
             }
         }
-        println!("Number of docs: {}", accumulators.len());
+        page.push(RankedDocument { doc_id, score, explanation });
+    }
+    Ok((page, matching, pruned))
+}
 
-        let mut counter = 0;
+/*
+ * Reads the document ID and tf_td of every posting of a term
+ */
+fn term_postings(index: &DiskInvertedIndex, term: &str) -> Result<Vec<(u32, u32)>, &'static str> {
+    Ok(index
+        .get_postings_no_positions(term)?
+        .into_iter()
+        .map(|posting| (posting.0, posting.1))
+        .collect())
+}
 
-        while !accumulators.is_empty() && counter != 10 {
-            let doc_acc = accumulators.pop().unwrap();
-            let file_path = id_file.get(&(doc_acc.get_doc_id() as u32)).unwrap().to_string();
-            let file: &Path = file_path.as_ref();
-            let file_name = file.file_name().unwrap().to_str().unwrap();
-            println!("{} - {}", file_name, doc_acc.get_accumulator());
-            counter += 1;
+/*
+ * Ranks the documents containing any of the query terms with WAND or Block-Max WAND
+ *
+ * # Arguments
+ *
+ * *`terms` - The query terms found in the index
+ * *`options` - How documents are scored
+ * *`k` - Number of documents to return
+ * *`index` - The Disk Inverted Index being searched
+ * *`collection` - Statistics of the indexed collection
+ * *`documents` - Cache of document statistics
+ *
+ * # Returns
+ *
+ * The best documents, or None if the scorer cannot bound the scores of a term
+ */
+fn rank_with_pruning(
+    terms: &[RankedFeature],
+    options: &SearchOptions,
+    k: usize,
+    index: &DiskInvertedIndex,
+    collection: &CollectionStatistics,
+    documents: &mut HashMap<u32, DocumentStatistics>,
+) -> Option<Vec<(u32, f64)>> {
+    let scorer = options.scorer;
+    let mut cursors: Vec<TermCursor> = Vec::new();
+    for term in terms {
+        // The scorer bounds a term of weight 1, so the bounds grow with the expansion weight
+        let (term_bounds, block_bounds) = index.get_posting_bounds(&term.label).ok()?;
+        let max_score = term.weight * scorer.max_score(collection, &term.statistics, &term_bounds)?;
+        let mut blocks = Vec::with_capacity(block_bounds.len());
+        for &(last_doc_id, _, ref bounds) in block_bounds.iter() {
+            blocks.push((last_doc_id, term.weight * scorer.max_score(collection, &term.statistics, bounds)?));
         }
+        let postings = PostingsCursor::new(index, &term.label, &block_bounds);
+        cursors.push(TermCursor::new(postings, term.query_weight, term.statistics, max_score, blocks));
     }
 
-    return results;
+    let query_length = cursors.len() as u32;
+    let block_max = options.pruning == Pruning::BlockMaxWand;
+    let mut document_lookup = |doc_id: u32| document_statistics(doc_id, index, documents);
+    Some(wand::top_k(
+        &mut cursors,
+        k,
+        block_max,
+        scorer,
+        collection,
+        query_length,
+        &mut document_lookup,
+    ))
+}
 
+/*
+ * Writes a phrase or proximity clause back as query text, to label its part of a score
+ */
+fn feature_label(feature: &QueryNode) -> String {
+    match *feature {
+        QueryNode::Term(ref token) => token.clone(),
+        QueryNode::Phrase(ref words) => format!("\"{}\"", words.join(" ")),
+        QueryNode::Proximity { ref operands, distance, kind } => {
+            let operands: Vec<String> = operands.iter().map(feature_label).collect();
+            match kind {
+                ProximityKind::Ordered => operands.join(&format!(" ONEAR/{} ", distance)),
+                ProximityKind::Unordered => operands.join(&format!(" NEAR/{} ", distance)),
+                ProximityKind::Window => format!("WITHIN/{}({})", distance, operands.join(" ")),
+            }
+        },
+        _ => String::new(),
+    }
 }
 
+/*
+ * The normalized terms scored by ranked retrieval for a parsed query, once per occurrence, with
+ * wildcards expanded
+ *
+ * # Arguments
+ *
+ * *`query` - The parsed query
+ * *`kgram` - The K Gram Index used to expand wildcards
+ */
+pub fn ranked_terms(query: &QueryNode, kgram: &KGramIndex) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut features: Vec<&QueryNode> = Vec::new();
+    collect_ranked_features(query, kgram, &mut terms, &mut features);
+    terms
+}
 
-fn get_wqt(scheme: &str, number_of_docs: u32, token: &str, index: &DiskInvertedIndex ) -> f64 {
-    if scheme == "default" {
-        return ((1.0 + ((number_of_docs as f64)/index.get_document_frequency(&token) as f64))).ln();
-    } else if scheme == "tfidf" {
-        return (((number_of_docs)/index.get_document_frequency(&token)) as f64).ln();
-    } else if scheme == "okapi" {
-        return (0.1 as f64).max((((((number_of_docs - index.get_document_frequency(&token)) as f64) + 0.5)/((index.get_document_frequency(&token) as f64) + 0.5) as f64) as f64).ln());
-    } else if scheme == "wacky" {
-        return (0.0 as f64).max(((((number_of_docs - index.get_document_frequency(&token)) as f64)/(index.get_document_frequency(&token) as f64)) as f64).ln());
-    } else {
-        return 1.0; 
+/*
+ * Flattens a parsed query into the normalized terms scored by ranked retrieval and the phrase and
+ * proximity clauses scored as additional features. Negated sub-queries are ignored
+ *
+ * # Arguments
+ *
+ * *`query` - The parsed query
+ * *`kgram` - The K Gram Index used to expand wildcards
+ * *`terms` - Receives the normalized terms of the query
+ * *`features` - Receives the phrase and proximity clauses of the query
+ */
+fn collect_ranked_features<'q>(
+    query: &'q QueryNode,
+    kgram: &KGramIndex,
+    terms: &mut Vec<String>,
+    features: &mut Vec<&'q QueryNode>,
+) {
+    match *query {
+        QueryNode::Term(ref token) => {
+            if token.contains('*') {
+                if kgram.is_enabled() {
+                    for expansion in get_wildcards(token, kgram) {
+                        terms.extend(normalize_query_token(&expansion));
+                    }
+                }
+            } else {
+                terms.extend(normalize_query_token(token));
+            }
+        },
+        QueryNode::Phrase(ref words) => {
+            for word in words {
+                terms.extend(normalize_query_token(word));
+            }
+            features.push(query);
+        },
+        QueryNode::Proximity { ref operands, .. } => {
+            for operand in operands {
+                if let QueryNode::Term(ref token) = *operand {
+                    terms.extend(normalize_query_token(token));
+                } else if let QueryNode::Phrase(ref words) = *operand {
+                    for word in words {
+                        terms.extend(normalize_query_token(word));
+                    }
+                }
+            }
+            features.push(query);
+        },
+        QueryNode::And(ref operands) | QueryNode::Or(ref operands) => {
+            for operand in operands {
+                collect_ranked_features(operand, kgram, terms, features);
+            }
+        },
+        QueryNode::Not(_) => {},
     }
 }
 
+/*
+ * Counts, for every matching document, the places where a phrase or proximity clause matches
+ */
+fn feature_matches(feature: &QueryNode, index: &DiskInvertedIndex, kgram: &KGramIndex) -> Result<Vec<(u32, u32)>, &'static str> {
+    match *feature {
+        QueryNode::Phrase(ref words) => Ok(phrase_positions(&phrase_terms(words), index)?
+            .into_iter()
+            .map(|(doc_id, starts)| (doc_id, starts.len() as u32))
+            .collect()),
+        QueryNode::Proximity { ref operands, distance, kind } => {
+            proximity_matches(operands, distance, kind, index, kgram)
+        },
+        _ => Ok(Vec::new()),
+    }
+}
 
-fn get_wdt(scheme: &str, doc_id: u32, token: &str, term_doc_frequency: u32, index: &DiskInvertedIndex) -> f64 {
-    if scheme == "default" {
-        return 1.0 + (term_doc_frequency as f64).ln();
-    } else if scheme == "tfidf" {
-        return term_doc_frequency as f64;
-    } else if scheme == "okapi" {
-        return 2.2 * term_doc_frequency as f64;
-    } else if scheme == "wacky" {
-        let doc_weights = index.get_document_weights(doc_id).unwrap();
-        let tftd_a = doc_weights.4;
+/*
+ * Calculates the term-pair proximity boost of a document
+ *
+ * # Arguments
+ *
+ * *`boost` - The proximity boost parameters
+ * *`collection` - Statistics of the indexed collection
+ * *`document` - The document being scored
+ * *`term_positions` - Query weight and per-document positions of every distinct query term
+ *
+ * # Returns
+ *
+ * The amount added to the document's score
+ */
+fn proximity_score(
+    boost: &ProximityBoost,
+    collection: &CollectionStatistics,
+    document: &DocumentStatistics,
+    term_positions: &[(f64, HashMap<u32, Vec<u32>>)],
+) -> f64 {
+    let terms: Vec<(f64, &[u32])> = term_positions
+        .iter()
+        .filter_map(|(wqt, positions)| positions.get(&document.doc_id).map(|found| (*wqt, found.as_slice())))
+        .collect();
+    if terms.len() < 2 {
+        return 0.0;
+    }
+    boost.score(&terms, document.length as f64, collection.avg_doc_length)
+}
 
-        return (1.0 + (term_doc_frequency as f64).ln())/(1.0 + (tftd_a).ln());
-    } else {
-        return 1.0;
+/*
+ * Gathers the collection wide statistics used by the scorers
+ *
+ * # Arguments
+ *
+ * *`index` - The Disk Inverted Index being searched
+ * *`number_of_docs` - Number of documents in the index
+ *
+ * # Returns
+ *
+ * The statistics of the collection
+ */
+pub fn collection_statistics(index: &DiskInvertedIndex, number_of_docs: u32) -> CollectionStatistics {
+    let avg_doc_length = match index.get_document_weights(0) {
+        Ok(doc_weights) if number_of_docs > 0 => doc_weights.0,
+        _ => 0.0,
+    };
+    CollectionStatistics {
+        number_of_docs,
+        avg_doc_length,
+        total_term_frequency: index.get_total_term_frequency(),
     }
 }
 
-fn get_ld(scheme: &str, doc_id: u32, index:&DiskInvertedIndex) -> f64 {
-    let doc_weights = index.get_document_weights(doc_id).unwrap();
-    let doc_weight = doc_weights.1;
-    let byte_size = doc_weights.3;
-    if scheme == "default" {
-        return doc_weight;
-    } else if scheme == "tfidf" {
-        return doc_weight;
-    } else if scheme == "okapi" {
-        return 1.0;
-    } else {
-        return (byte_size as f64).sqrt();
+/*
+ * The global statistics of a query term or clause, if several indexes are searched together
+ */
+fn global_term_statistics(options: &SearchOptions, label: &str) -> Option<TermStatistics> {
+    options.statistics.and_then(|global| global.terms.get(label).cloned())
+}
+
+/*
+ * Gathers the statistics of several indexes searched together, so a ranked query scores the
+ * documents of every index as if they were one collection
+ *
+ * # Arguments
+ *
+ * *`query` - The parsed query
+ * *`expansion` - Weights of normalized terms of the query, see SearchOptions::expansion
+ * *`indexes` - Every searched Disk Inverted Index, with the K Gram Index expanding its wildcards
+ *
+ * # Returns
+ *
+ * The combined number of documents, average document length and total term frequency, and the
+ * document and collection frequencies of every query term and clause summed over the indexes, or
+ * an error if a postings list cannot be read
+ */
+pub fn global_statistics(
+    query: &QueryNode,
+    expansion: &[TermComponentScore],
+    indexes: &[(&DiskInvertedIndex, &KGramIndex)],
+) -> Result<GlobalStatistics, &'static str> {
+    let mut number_of_docs: u32 = 0;
+    let mut total_doc_length = 0.0;
+    let mut total_term_frequency: u64 = 0;
+    let mut terms: HashMap<String, TermStatistics> = HashMap::new();
+
+    for &(index, kgram) in indexes {
+        let collection = collection_statistics(index, index.get_num_documents().unwrap_or(0));
+        number_of_docs += collection.number_of_docs;
+        total_doc_length += collection.avg_doc_length * collection.number_of_docs as f64;
+        total_term_frequency += collection.total_term_frequency;
+
+        let mut query_terms: Vec<String> = Vec::new();
+        let mut features: Vec<&QueryNode> = Vec::new();
+        collect_ranked_features(query, kgram, &mut query_terms, &mut features);
+        query_terms.extend(expansion.iter().filter(|expansion| expansion.score > 0.0).map(|expansion| expansion.term.clone()));
+        query_terms.sort();
+        query_terms.dedup();
+        for term in query_terms {
+            if !index.contains_term(&term) {
+                continue;
+            }
+            let statistics = terms.entry(term.clone()).or_insert(TermStatistics { document_frequency: 0, collection_frequency: 0 });
+            statistics.document_frequency += index.get_document_frequency(&term);
+            statistics.collection_frequency += index.get_term_frequency(&term) as u64;
+        }
+
+        let mut seen_features: HashSet<String> = HashSet::new();
+        for feature in features {
+            let label = feature_label(feature);
+            if !seen_features.insert(label.clone()) {
+                continue;
+            }
+            let matches = feature_matches(feature, index, kgram)?;
+            let statistics = terms.entry(label).or_insert(TermStatistics { document_frequency: 0, collection_frequency: 0 });
+            statistics.document_frequency += matches.len() as u32;
+            statistics.collection_frequency += matches.iter().map(|&(_, match_count)| match_count as u64).sum::<u64>();
+        }
     }
+
+    let avg_doc_length = if number_of_docs > 0 { total_doc_length / number_of_docs as f64 } else { 0.0 };
+    Ok(GlobalStatistics {
+        collection: CollectionStatistics {
+            number_of_docs,
+            avg_doc_length,
+            total_term_frequency,
+        },
+        terms,
+    })
+}
+
+/*
+ * Reads the statistics of a document, caching them for the rest of the query
+ */
+fn document_statistics(
+    doc_id: u32,
+    index: &DiskInvertedIndex,
+    documents: &mut HashMap<u32, DocumentStatistics>,
+) -> DocumentStatistics {
+    *documents.entry(doc_id).or_insert_with(|| {
+        let doc_weights = index.get_document_weights(doc_id).expect("Failed to get document weights");
+        DocumentStatistics {
+            doc_id,
+            weight: doc_weights.1,
+            length: doc_weights.2,
+            byte_size: doc_weights.3,
+            avg_tftd: doc_weights.4,
+        }
+    })
 }
 
 
 pub fn get_wildcards(entry: &str, kgram: &KGramIndex) -> Vec<String> {
-    println!("WILDCARD: {}", entry);
+    debug!("WILDCARD: {}", entry);
     let mut results: Vec<String> = Vec::new();
     if entry.starts_with("*") {
         let mut batch_one: Vec<String> = Vec::new();
@@ -657,201 +1176,319 @@ pub fn get_wildcards(entry: &str, kgram: &KGramIndex) -> Vec<String> {
     return results;
 }
 /*
- * Function to process a NEAR/ query 
+ * Function to process a phrase literal query
  *
  * # Arguments
- * *`query_literal` - The query literal in the form "a NEAR/? b"
- * *`index` - The Positional Inverted Index that specifies which a is within x terms of b
+ * *`query_literal` - The phrase literal in the form "a b c"
+ * *`index` - The Disk Inverted Index containing the positions of each term
  *
  * # Returns
  *
- * The list of files satisfying the query
- */
-pub fn near_query(query_literal: String, index: &DiskInvertedIndex) -> Vec<u32> {
-    //extract the terms from the literal
-    let literals: Vec<&str> = query_literal.split(' ').collect();
-    let first_term = document_parser::stem_terms(document_parser::normalize_token(literals[0].to_string()))[0].to_string();
-    let mut near = literals[1].clone().to_string();
-    let second_term = document_parser::stem_terms(document_parser::normalize_token(literals[2].to_string()))[0].to_string();
-
-    near = near.replace("NEAR/", "");
-    //extract the maximum distance
-    let max_distance = near.parse::<i32>().unwrap();
-
-    println!("first term: {}", first_term);
-
-    let mut documents: Vec<u32> = Vec::new();
-    //iterate through postings lists until a common document ID is found
-   
-    let first_term_postings = index.get_postings(&first_term).expect("Error getting postings");
-    for first_posting in first_term_postings {
-        let first_doc_id = first_posting.0;
-        let first_positions = first_posting.6;
-        let second_term_postings = index.get_postings(&second_term).expect("Error getting postings");
-        for second_posting in second_term_postings {
-            let second_doc_id = second_posting.0;
-            let second_positions= second_posting.6;
-
-            if first_doc_id == second_doc_id {
-                if is_near(&first_positions, &second_positions, max_distance) {
-                    documents.push(first_doc_id);
-                }
-            }
-             
-        } 
-    }
-
-    documents
+ * The ids of the documents containing the phrase, or an error if a postings list cannot be read
+ */
+pub fn phrase_query(query_literal: String, index: &DiskInvertedIndex) -> Result<Vec<u32>, &'static str> {
+    let words: Vec<&str> = query_literal.split_whitespace().collect();
+    Ok(phrase_positions(&phrase_terms(&words), index)?.into_iter().map(|(doc_id, _)| doc_id).collect())
 }
 
 /*
- * Function to determine if two terms are within a distance of each other
+ * Normalizes the words of a phrase into its terms, each with its position relative to the start
+ * of the phrase. Hyphenated words are positioned like document_parser positions them at index time
  *
  * # Arguments
  *
- * *`first_positions` - The positions of the first term within a document
- * *`second_positions` - The positions of the second term within a document
- * *`max_distance` - The maximum distance allowed between the two terms
+ * *`words` - The words of the phrase, in order
  *
  * # Returns
  *
- * True if the positions of the first term are within distance of the positions of the second term
- * False otherwise
+ * The normalized terms of the phrase and their relative positions, in order
  */
-pub fn is_near(first_positions: &Vec<u32>, second_positions: &Vec<u32>, max_distance: i32) -> bool {
-    let mut i = 0;
-    let mut j = 0;
-    let mut difference: i32;
-    //iterate through the positions
-    while i < first_positions.len() && j < second_positions.len() {
-        difference = (second_positions[j] as i32) - (first_positions[i] as i32);
-        //if the distance is within the max_distance then we return true
-        if difference <= max_distance && difference > 0 {
-            return true;
-        // if the first position comes before the second then we increment the second position vector
-        } else if difference < 0 {
-            j = j + 1;
-        // if the second position comes more than the threshold after the first one, increment the first position vector
-        } else if difference > 0 {
-            i = i + 1;
-        }
+fn phrase_terms<S: AsRef<str>>(words: &[S]) -> Vec<(String, u32)> {
+    let mut terms: Vec<(String, u32)> = Vec::new();
+    let mut position = 0;
+    for word in words {
+        let normalized = document_parser::normalize_token(word.as_ref().to_string());
+        let (term_positions, width) = document_parser::relative_positions(&normalized);
+        let (kept, kept_positions): (Vec<String>, Vec<u32>) = normalized
+            .into_iter()
+            .zip(term_positions)
+            .filter(|(term, _)| !term.trim().is_empty())
+            .unzip();
+        let stemmed = document_parser::stem_terms(kept);
+        terms.extend(stemmed.into_iter().zip(kept_positions.into_iter().map(|term_position| position + term_position)));
+        position += width;
     }
-    false
+    terms
 }
 
-
-pub fn phrase_query(query_literal: String, index: &DiskInvertedIndex) -> Vec<u32> {
-    //extract the terms from the literal
-    let literals: Vec<&str> = query_literal.split(' ').collect();
-    let mut normalized_literals:Vec<String> = Vec::new(); // Also stemmed...
-    //normalize the literals
-    for word in literals.iter() {
-        normalized_literals.push(document_parser::stem_terms(document_parser::normalize_token(word.to_string()))[0].to_string());
+/*
+ * Finds every occurrence of a sequence of normalized terms appearing at given distances from the
+ * first one
+ *
+ * # Arguments
+ *
+ * *`terms` - The normalized terms of the phrase with their positions in the phrase, in order
+ * *`index` - The Disk Inverted Index containing the positions of each term
+ *
+ * # Returns
+ *
+ * For each document containing the phrase (in increasing order), the positions of its first term,
+ * or an error if a postings list cannot be read
+ */
+pub fn phrase_positions(terms: &[(String, u32)], index: &DiskInvertedIndex) -> Result<Vec<(u32, Vec<u32>)>, &'static str> {
+    if terms.is_empty() || terms.iter().any(|(term, _)| !index.contains_term(term)) {
+        return Ok(Vec::new());
     }
+    let mut current: Vec<(u32, Vec<u32>)> = index
+        .get_postings(&terms[0].0)?
+        .into_iter()
+        .map(|posting| (posting.0, posting.6))
+        .collect();
 
-    let mut current_disk_postings = index.get_postings(&normalized_literals[0]).expect("Failed to get postings");
-
-    let mut current_postings: Vec<PositionalPosting> = Vec::new();
-
-    for disk_posting in current_disk_postings {
-        let mut temp_posting = PositionalPosting::new(disk_posting.0);
-        for position in disk_posting.6 {
-            &temp_posting.add_position(position);
+    let first_position = terms[0].1;
+    for &(ref term, term_position) in terms.iter().skip(1) {
+        let next = index.get_postings(term)?;
+        let mut merged: Vec<(u32, Vec<u32>)> = Vec::new();
+        let mut i = 0;
+        let mut j = 0;
+        while i < current.len() && j < next.len() {
+            if current[i].0 == next[j].0 {
+                // Keep the phrase starts where the next term sits at its position in the phrase
+                let starts: Vec<u32> = current[i].1
+                    .iter()
+                    .cloned()
+                    .filter(|start| {
+                        (start + term_position)
+                            .checked_sub(first_position)
+                            .is_some_and(|expected| next[j].6.binary_search(&expected).is_ok())
+                    })
+                    .collect();
+                if !starts.is_empty() {
+                    merged.push((current[i].0, starts));
+                }
+                i += 1;
+                j += 1;
+            } else if current[i].0 < next[j].0 {
+                i += 1;
+            } else {
+                j += 1;
+            }
         }
-        current_postings.push(temp_posting);
+        current = merged;
     }
+    Ok(current)
+}
 
-    println!("{}", &normalized_literals[0]);
+/*
+ * Finds the positions of a single query token in every document. Wildcard tokens take the
+ * positions of all their expansions, and tokens split into several terms (e.g. hyphenated words)
+ * take the positions where their terms occur as they do in the token
+ *
+ * # Returns
+ *
+ * For each matching document (in increasing order), the sorted positions of the token, or an error
+ * if a postings list cannot be read
+ */
+fn term_positions(token: &str, index: &DiskInvertedIndex, kgram: &KGramIndex) -> Result<Vec<(u32, Vec<u32>)>, &'static str> {
+    if token.contains('*') {
+        let mut merged: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for expansion in get_wildcards(token, kgram) {
+            for term in normalize_query_token(&expansion) {
+                if let Ok(postings) = index.get_postings(&term) {
+                    for posting in postings {
+                        merged.entry(posting.0).or_default().extend(posting.6);
+                    }
+                }
+            }
+        }
+        return Ok(merged
+            .into_iter()
+            .map(|(doc_id, mut positions)| {
+                positions.sort();
+                positions.dedup();
+                (doc_id, positions)
+            })
+            .collect());
+    }
+    phrase_positions(&phrase_terms(&[token]), index)
+}
 
+/*
+ * The (first position, last position) of each occurrence of an operand, grouped by document id
+ */
+type DocumentOccurrences = Vec<(u32, Vec<(u32, u32)>)>;
 
-    for ind in 1..normalized_literals.len() {
-        let next_disk_postings = index.get_postings(&normalized_literals[ind]).expect("Failed to get postings");
+/*
+ * Finds the occurrences of a proximity operand in every document
+ *
+ * # Returns
+ *
+ * For each matching document (in increasing order), the (first position, last position) of each
+ * occurrence, ordered by first position, or an error if a postings list cannot be read
+ */
+fn operand_occurrences(operand: &QueryNode, index: &DiskInvertedIndex, kgram: &KGramIndex) -> Result<DocumentOccurrences, &'static str> {
+    Ok(match *operand {
+        QueryNode::Term(ref token) => {
+            term_positions(token, index, kgram)?
+                .into_iter()
+                .map(|(doc_id, positions)| (doc_id, positions.into_iter().map(|pos| (pos, pos)).collect()))
+                .collect()
+        },
+        QueryNode::Phrase(ref words) => {
+            let terms = phrase_terms(words);
+            let length = terms.iter().map(|&(_, position)| position + 1).max().unwrap_or(1);
+            phrase_positions(&terms, index)?
+                .into_iter()
+                .map(|(doc_id, starts)| (doc_id, starts.into_iter().map(|start| (start, start + length - 1)).collect()))
+                .collect()
+        },
+        _ => Vec::new(),
+    })
+}
 
-        println!("{}", &normalized_literals[ind]);
+/*
+ * Function to process a proximity query (NEAR/k, ONEAR/k or WITHIN/k)
+ *
+ * # Arguments
+ *
+ * *`operands` - The terms or phrases that must occur close to each other
+ * *`distance` - The maximum distance k
+ * *`kind` - How the operands must be arranged
+ * *`index` - The Disk Inverted Index containing the positions of each term
+ * *`kgram` - The K Gram Index used to expand wildcards
+ *
+ * # Returns
+ *
+ * For each document satisfying the query (in increasing order), the number of places where the
+ * operands occur close enough to each other, or an error if a postings list cannot be read
+ */
+pub fn proximity_matches(
+    operands: &[QueryNode],
+    distance: u32,
+    kind: ProximityKind,
+    index: &DiskInvertedIndex,
+    kgram: &KGramIndex,
+) -> Result<Vec<(u32, u32)>, &'static str> {
+    let occurrences: Vec<DocumentOccurrences> = operands
+        .iter()
+        .map(|operand| operand_occurrences(operand, index, kgram))
+        .collect::<Result<_, _>>()?;
+    if occurrences.len() < 2 || occurrences.iter().any(|operand| operand.is_empty()) {
+        return Ok(Vec::new());
+    }
 
-        let mut next: Vec<PositionalPosting> = Vec::new();
-        
-        for disk_posting in next_disk_postings{
-            let mut temp_posting = PositionalPosting::new(disk_posting.0);
-            for position in disk_posting.6 {
-                &temp_posting.add_position(position);
+    let mut matches = Vec::new();
+    let mut cursors = vec![0; occurrences.len()];
+    // Document at a time: advance every operand to the largest current document id
+    'documents: loop {
+        let mut target = 0;
+        for (operand, &cursor) in occurrences.iter().zip(cursors.iter()) {
+            if cursor >= operand.len() {
+                break 'documents;
             }
-            next.push(temp_posting);
+            target = target.max(operand[cursor].0);
+        }
+        let mut aligned = true;
+        for (operand, cursor) in occurrences.iter().zip(cursors.iter_mut()) {
+            while *cursor < operand.len() && operand[*cursor].0 < target {
+                *cursor += 1;
+            }
+            if *cursor >= operand.len() {
+                break 'documents;
+            }
+            if operand[*cursor].0 != target {
+                aligned = false;
+            }
+        }
+        if !aligned {
+            continue;
         }
-        let mut i = 0;
-        let mut j = 0;
-
-        // list of postings containing document ids that terms share in common and positions
-        let mut merged:Vec<PositionalPosting> = Vec::new();
-
-        //iterate through postings lists until a common document ID is found
-        
-        while i < current_postings.len() && j < next.len() {
-            if current_postings[i].get_doc_id() == next[j].get_doc_id() {
-                
-                //if the two terms have a common document, retrieve the positions
-                let positions_of_current = current_postings[i].get_positions();
-                let positions_of_next = next[j].get_positions();
-                //return all positions of the second term where the terms are adjacent to each other
-                
-                let merged_positions = adjacent_positions(&positions_of_next, &positions_of_current);
-                //if none exist we can continue
-                if merged_positions.is_empty() {
-                    i = i + 1;
-                    j = j + 1;
-                    continue;
-                }
-                //create new positional posting to push to merged list of postings
-                let mut temp_posting = PositionalPosting::new(current_postings[i].get_doc_id());
-                for pos in merged_positions {
-                    temp_posting.add_position(pos);
-                }
-                merged.push(temp_posting);
-                
-                i = i + 1;
-                j = j + 1;
 
-            } else if current_postings[i].get_doc_id() < next[j].get_doc_id() {
-                i = i + 1;
-            } else if current_postings[i].get_doc_id() > next[j].get_doc_id() {
-                j = j + 1;
-            } 
+        let document: Vec<&[(u32, u32)]> = occurrences
+            .iter()
+            .zip(cursors.iter())
+            .map(|(operand, &cursor)| &operand[cursor].1[..])
+            .collect();
+        let count = match kind {
+            ProximityKind::Ordered => count_ordered(document[0], document[1], distance),
+            ProximityKind::Unordered => count_unordered(document[0], document[1], distance),
+            ProximityKind::Window => count_windows(&document, distance),
+        };
+        if count > 0 {
+            matches.push((target, count));
+        }
+        for cursor in cursors.iter_mut() {
+            *cursor += 1;
         }
-        current_postings = merged;
     }
+    Ok(matches)
+}
 
-    let mut documents:Vec<u32> = Vec::new();
-    
-    for i in current_postings {
-        documents.push(i.get_doc_id());
-    }
+/*
+ * Counts the occurrences of `first` followed by an occurrence of `second` starting at most
+ * `distance` positions after it ends
+ */
+fn count_ordered(first: &[(u32, u32)], second: &[(u32, u32)], distance: u32) -> u32 {
+    first
+        .iter()
+        .filter(|&&(_, end)| {
+            let next = second.partition_point(|&(start, _)| start <= end);
+            next < second.len() && second[next].0 - end <= distance
+        })
+        .count() as u32
+}
 
-    return documents;
+/*
+ * Counts the occurrences of `first` with an occurrence of `second` at most `distance` positions
+ * before or after it
+ */
+fn count_unordered(first: &[(u32, u32)], second: &[(u32, u32)], distance: u32) -> u32 {
+    first
+        .iter()
+        .filter(|&&(start, end)| {
+            let next = second.partition_point(|&(other_start, _)| other_start <= end);
+            let after = next < second.len() && second[next].0 - end <= distance;
+            let before = second
+                .iter()
+                .take_while(|&&(other_start, _)| other_start < start)
+                .any(|&(_, other_end)| other_end < start && start - other_end <= distance);
+            after || before
+        })
+        .count() as u32
 }
 
-pub fn adjacent_positions(term_positions: &Vec<u32>, positions: &Vec<u32>) -> Vec<u32> {
-    let mut i = 0;
-    let mut j = 0;
-    let mut off_by_one_positions: Vec<u32> = Vec::new();
-    //iterate through the positions
-
-    while j < term_positions.len() && i < positions.len() {
-        let difference = (term_positions[j]as i32) - (positions[i] as i32);
-        //if the distance is within the max_distance then we return true
-        if difference == 1 {
-            off_by_one_positions.push(term_positions[j]);
-            i = i + 1;
-            j = j + 1;
-        // if the first position comes before the second then we increment the second position vector
-        } else if difference <= 0 {
-            j = j + 1;
-        // if the second position comes more than the threshold after the first one, increment the first position vector
-        } else if difference > 1 {
-            i = i + 1;
-        }
-    }
-    //println!("{:?}", off_by_one_positions);
-    off_by_one_positions
+/*
+ * Counts the windows spanning at most `distance` positions that contain an occurrence of every
+ * operand. Each window is counted once, by its leftmost occurrence
+ */
+fn count_windows(operands: &[&[(u32, u32)]], distance: u32) -> u32 {
+    let mut events: Vec<(u32, u32, usize)> = Vec::new();
+    for (operand, occurrences) in operands.iter().enumerate() {
+        for &(start, end) in occurrences.iter() {
+            events.push((start, end, operand));
+        }
+    }
+    events.sort();
+
+    let mut count = 0;
+    for (i, &(window_start, _, _)) in events.iter().enumerate() {
+        let mut covered = vec![false; operands.len()];
+        let mut window_end = window_start;
+        for &(start, end, operand) in events[i..].iter() {
+            if start - window_start > distance {
+                break;
+            }
+            covered[operand] = true;
+            window_end = window_end.max(end);
+            if covered.iter().all(|&seen| seen) {
+                if window_end - window_start <= distance {
+                    count += 1;
+                }
+                break;
+            }
+        }
+    }
+    count
 }
 
 pub fn intersection<T: Clone + Ord + PartialOrd >(first: Vec<T>, second: Vec<T>) -> Vec<T> {
@@ -0,0 +1,166 @@
+use classifier::classifier::TermComponentScore;
+use classifier::rocchio_classifier;
+use index::disk_inverted_index::DiskInvertedIndex;
+use index::disk_inverted_index::IndexReader;
+use index::k_gram_index::KGramIndex;
+use parser::query_parser::QueryParser;
+use processor::query_processor;
+use processor::query_processor::SearchOptions;
+use processor::search_results::SearchResults;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/*
+ * Parameters of Rocchio relevance feedback, which moves a query towards the centroid of the
+ * relevant documents and away from the centroid of the non-relevant ones:
+ * q' = alpha * q + beta * relevant centroid - gamma * non-relevant centroid
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Feedback {
+    /*
+     * Weight of the original query
+     */
+    pub alpha: f64,
+
+    /*
+     * Weight of the centroid of the relevant documents
+     */
+    pub beta: f64,
+
+    /*
+     * Weight of the centroid of the non-relevant documents
+     */
+    pub gamma: f64,
+
+    /*
+     * Number of terms added to the query
+     */
+    pub expansion_terms: usize,
+
+    /*
+     * Number of top ranked documents assumed relevant by pseudo-relevance feedback
+     */
+    pub feedback_documents: usize,
+}
+
+impl Default for Feedback {
+    fn default() -> Feedback {
+        Feedback {
+            alpha: 1.0,
+            beta: 0.75,
+            gamma: 0.15,
+            expansion_terms: 10,
+            feedback_documents: 10,
+        }
+    }
+}
+
+/*
+ * Builds the query vector of Rocchio relevance feedback,
+ * q' = alpha * q + beta * relevant centroid - gamma * non-relevant centroid, where q counts the
+ * occurrences of every query term and is normalized to unit length like the document vectors
+ * averaged into the centroids. Negative weights are clipped to 0
+ *
+ * # Arguments
+ *
+ * *`feedback` - The feedback parameters
+ * *`query_terms` - The normalized terms of the query, once per occurrence
+ * *`relevant` - Documents judged relevant
+ * *`non_relevant` - Documents judged non-relevant
+ * *`index` - The Disk Inverted Index holding the documents
+ *
+ * # Returns
+ *
+ * The weight of every query term and of the `expansion_terms` other terms with the largest
+ * positive weights, heaviest first with ties broken by term
+ */
+pub fn rocchio_query(
+    feedback: &Feedback,
+    query_terms: &[String],
+    relevant: &[u32],
+    non_relevant: &[u32],
+    index: &DiskInvertedIndex,
+) -> Vec<TermComponentScore> {
+    let mut query: HashMap<String, f64> = HashMap::new();
+    for term in query_terms {
+        *query.entry(term.clone()).or_insert(0.0) += 1.0;
+    }
+    let query_length = query.values().map(|count| count * count).sum::<f64>().sqrt();
+
+    let mut weights: HashMap<String, f64> = HashMap::new();
+    for (term, count) in query.iter() {
+        *weights.entry(term.clone()).or_insert(0.0) += feedback.alpha * count / query_length;
+    }
+    for component in rocchio_classifier::document_centroid(relevant, index) {
+        *weights.entry(component.term).or_insert(0.0) += feedback.beta * component.score;
+    }
+    for component in rocchio_classifier::document_centroid(non_relevant, index) {
+        *weights.entry(component.term).or_insert(0.0) -= feedback.gamma * component.score;
+    }
+
+    let by_weight = |a: &TermComponentScore, b: &TermComponentScore| {
+        b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal).then_with(|| a.term.cmp(&b.term))
+    };
+    let (mut terms, mut added): (Vec<TermComponentScore>, Vec<TermComponentScore>) = weights
+        .into_iter()
+        .filter(|(term, weight)| query.contains_key(term) || *weight > 0.0)
+        .filter_map(|(term, weight)| TermComponentScore::new(weight.max(0.0), term))
+        .partition(|component| query.contains_key(&component.term));
+    added.sort_by(by_weight);
+    added.truncate(feedback.expansion_terms);
+    terms.extend(added);
+    terms.sort_by(by_weight);
+    terms
+}
+
+/*
+ * Answers a ranked query reweighted and expanded by relevance feedback, see rocchio_query. With
+ * explicit judgments the query moves towards the documents marked relevant and away from those
+ * marked non-relevant; otherwise the top `feedback_documents` documents of the original query are
+ * assumed relevant (pseudo-relevance feedback)
+ *
+ * # Arguments
+ *
+ * *`options` - How the expanded query is answered and which page is returned
+ * *`feedback` - The feedback parameters
+ * *`judgments` - Documents marked relevant and non-relevant, or None for pseudo-relevance feedback
+ * *`input` - The query
+ * *`index` - The Disk Inverted Index being searched
+ * *`kgram` - The K Gram Index used to expand wildcards
+ *
+ * # Returns
+ *
+ * The results of the expanded query and the weights of its terms, or an error if the query cannot
+ * be parsed or its results cannot be read
+ */
+pub fn process_query_with_feedback(
+    options: &SearchOptions,
+    feedback: &Feedback,
+    judgments: Option<(&[u32], &[u32])>,
+    input: &str,
+    index: &DiskInvertedIndex,
+    kgram: &KGramIndex,
+) -> Result<(SearchResults, Vec<TermComponentScore>), &'static str> {
+    let query = QueryParser::new().parse(input)?;
+    let query_terms = query_processor::ranked_terms(&query, kgram);
+    let expansion = match judgments {
+        Some((relevant, non_relevant)) => rocchio_query(feedback, &query_terms, relevant, non_relevant, index),
+        None => {
+            let mut top = SearchOptions::new(true, options.scorer);
+            top.pruning = options.pruning;
+            top.limit = feedback.feedback_documents;
+            let number_of_docs = index.get_num_documents().unwrap_or(0);
+            let (ranked, _, _) = query_processor::rank_query(&query, &top, index, kgram, number_of_docs)?;
+            let relevant: Vec<u32> = ranked.iter().map(|document| document.doc_id).collect();
+            rocchio_query(feedback, &query_terms, &relevant, &[], index)
+        },
+    };
+
+    let expanded = SearchOptions {
+        ranked_retrieval: true,
+        expansion: &expansion,
+        ..*options
+    };
+    let results = query_processor::process_query(&expanded, input, index, kgram)?;
+    Ok((results, expansion))
+}
@@ -0,0 +1,563 @@
+use index::posting_bounds::PostingBounds;
+use std::collections::HashMap;
+
+/*
+ * Statistics describing the whole indexed collection
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct CollectionStatistics {
+    pub number_of_docs: u32,
+    pub avg_doc_length: f64,
+    pub total_term_frequency: u64,
+}
+
+/*
+ * Statistics describing a single term of the query
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct TermStatistics {
+    pub document_frequency: u32,
+    pub collection_frequency: u64,
+}
+
+/*
+ * Statistics of several indexes searched together. They replace the statistics of the index being
+ * searched, so the scores of documents from different indexes are comparable
+ */
+#[derive(Debug, Clone)]
+pub struct GlobalStatistics {
+    /*
+     * Number of documents, average document length and total term frequency of every index combined
+     */
+    pub collection: CollectionStatistics,
+
+    /*
+     * Statistics of every normalized query term, and of every phrase or proximity clause by its
+     * label, summed over the indexes
+     */
+    pub terms: HashMap<String, TermStatistics>,
+}
+
+/*
+ * Statistics describing a single document, as stored in doc_weights.bin
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct DocumentStatistics {
+    pub doc_id: u32,
+    pub weight: f64,
+    pub length: u64,
+    pub byte_size: u64,
+    pub avg_tftd: f64,
+}
+
+impl DocumentStatistics {
+    /*
+     * Number of tokens in the document, which is its stored length
+     */
+    pub fn token_count(&self) -> f64 {
+        self.length as f64
+    }
+}
+
+/*
+ * A weighting scheme for ranked retrieval
+ *
+ * A document's score is the sum over the query terms of query_weight * document_weight, divided
+ * by the document's length_normalizer unless document_score is overridden. Scorers are shared by
+ * queries running on several threads
+ */
+pub trait Scorer: Send + Sync {
+    /*
+     * Name the scorer is registered and selected under
+     */
+    fn name(&self) -> &str;
+
+    /*
+     * Weight of a term in the query (w_q,t)
+     */
+    fn query_weight(&self, collection: &CollectionStatistics, term: &TermStatistics) -> f64;
+
+    /*
+     * Weight of a term in a document (w_d,t)
+     */
+    fn document_weight(
+        &self,
+        collection: &CollectionStatistics,
+        term: &TermStatistics,
+        document: &DocumentStatistics,
+        term_doc_frequency: u32,
+    ) -> f64;
+
+    /*
+     * Length normalizer of a document (L_d)
+     */
+    fn length_normalizer(&self, collection: &CollectionStatistics, document: &DocumentStatistics) -> f64;
+
+    /*
+     * Final score of a document
+     *
+     * # Arguments
+     *
+     * *`collection` - Statistics of the indexed collection
+     * *`document` - Statistics of the document being scored
+     * *`accumulator` - Sum of query_weight * document_weight over the query terms in the document
+     * *`query_length` - Number of query terms found in the index
+     */
+    fn document_score(
+        &self,
+        collection: &CollectionStatistics,
+        document: &DocumentStatistics,
+        accumulator: f64,
+        _query_length: u32,
+    ) -> f64 {
+        accumulator / self.length_normalizer(collection, document)
+    }
+
+    /*
+     * Upper bound of query_weight * document_weight / length_normalizer over a run of postings,
+     * used to skip documents that cannot reach the top results
+     *
+     * # Arguments
+     *
+     * *`collection` - Statistics of the indexed collection
+     * *`term` - Statistics of the query term
+     * *`bounds` - Extremes of the statistics of the postings
+     *
+     * # Returns
+     *
+     * The bound, or None when the scorer cannot bound its scores, in which case every posting is
+     * scored. Scorers overriding document_score must return None
+     */
+    fn max_score(&self, _collection: &CollectionStatistics, _term: &TermStatistics, _bounds: &PostingBounds) -> Option<f64> {
+        None
+    }
+}
+
+/*
+ * Cosine scoring with logarithmic term frequencies and Euclidean document weights
+ */
+pub struct DefaultScorer;
+
+impl Scorer for DefaultScorer {
+    fn name(&self) -> &str {
+        "default"
+    }
+
+    fn query_weight(&self, collection: &CollectionStatistics, term: &TermStatistics) -> f64 {
+        (1.0 + (collection.number_of_docs as f64 / term.document_frequency as f64)).ln()
+    }
+
+    fn document_weight(&self, _: &CollectionStatistics, _: &TermStatistics, _: &DocumentStatistics, term_doc_frequency: u32) -> f64 {
+        1.0 + (term_doc_frequency as f64).ln()
+    }
+
+    fn length_normalizer(&self, _: &CollectionStatistics, document: &DocumentStatistics) -> f64 {
+        document.weight
+    }
+
+    fn max_score(&self, collection: &CollectionStatistics, term: &TermStatistics, bounds: &PostingBounds) -> Option<f64> {
+        let max_weight = 1.0 + (bounds.max_term_frequency as f64).ln();
+        Some(self.query_weight(collection, term) * max_weight / bounds.min_doc_weight)
+    }
+}
+
+/*
+ * Raw term frequencies weighted by inverse document frequency
+ */
+pub struct TfIdfScorer;
+
+impl Scorer for TfIdfScorer {
+    fn name(&self) -> &str {
+        "tfidf"
+    }
+
+    fn query_weight(&self, collection: &CollectionStatistics, term: &TermStatistics) -> f64 {
+        ((collection.number_of_docs / term.document_frequency) as f64).ln()
+    }
+
+    fn document_weight(&self, _: &CollectionStatistics, _: &TermStatistics, _: &DocumentStatistics, term_doc_frequency: u32) -> f64 {
+        term_doc_frequency as f64
+    }
+
+    fn length_normalizer(&self, _: &CollectionStatistics, document: &DocumentStatistics) -> f64 {
+        document.weight
+    }
+
+    fn max_score(&self, collection: &CollectionStatistics, term: &TermStatistics, bounds: &PostingBounds) -> Option<f64> {
+        Some(self.query_weight(collection, term) * bounds.max_term_frequency as f64 / bounds.min_doc_weight)
+    }
+}
+
+/*
+ * Okapi BM25 with document length normalization
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct OkapiScorer {
+    /*
+     * Saturation of the term frequency
+     */
+    pub k1: f64,
+
+    /*
+     * Strength of document length normalization, between 0 (none) and 1 (full)
+     */
+    pub b: f64,
+}
+
+impl Default for OkapiScorer {
+    fn default() -> OkapiScorer {
+        OkapiScorer::new(1.2, 0.75)
+    }
+}
+
+impl OkapiScorer {
+    pub fn new(k1: f64, b: f64) -> OkapiScorer {
+        OkapiScorer { k1, b }
+    }
+}
+
+impl Scorer for OkapiScorer {
+    fn name(&self) -> &str {
+        "okapi"
+    }
+
+    fn query_weight(&self, collection: &CollectionStatistics, term: &TermStatistics) -> f64 {
+        let number_of_docs = collection.number_of_docs as f64;
+        let document_frequency = term.document_frequency as f64;
+        0.1_f64.max(((number_of_docs - document_frequency + 0.5) / (document_frequency + 0.5)).ln())
+    }
+
+    fn document_weight(
+        &self,
+        collection: &CollectionStatistics,
+        _: &TermStatistics,
+        document: &DocumentStatistics,
+        term_doc_frequency: u32,
+    ) -> f64 {
+        bm25_term_frequency(self.k1, self.b, collection, document.length as f64, term_doc_frequency)
+    }
+
+    fn length_normalizer(&self, _: &CollectionStatistics, _: &DocumentStatistics) -> f64 {
+        1.0
+    }
+
+    fn max_score(&self, collection: &CollectionStatistics, term: &TermStatistics, bounds: &PostingBounds) -> Option<f64> {
+        let max_weight = bm25_term_frequency(self.k1, self.b, collection, bounds.min_doc_length as f64, bounds.max_term_frequency);
+        Some(self.query_weight(collection, term) * max_weight)
+    }
+}
+
+/*
+ * BM25+ (Lv & Zhai), which adds delta to every matching term so that long documents are not
+ * scored below documents missing the term
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct Bm25PlusScorer {
+    pub k1: f64,
+    pub b: f64,
+
+    /*
+     * Lower bound of the contribution of a matching term
+     */
+    pub delta: f64,
+}
+
+impl Default for Bm25PlusScorer {
+    fn default() -> Bm25PlusScorer {
+        Bm25PlusScorer::new(1.2, 0.75, 1.0)
+    }
+}
+
+impl Bm25PlusScorer {
+    pub fn new(k1: f64, b: f64, delta: f64) -> Bm25PlusScorer {
+        Bm25PlusScorer { k1, b, delta }
+    }
+}
+
+impl Scorer for Bm25PlusScorer {
+    fn name(&self) -> &str {
+        "bm25plus"
+    }
+
+    fn query_weight(&self, collection: &CollectionStatistics, term: &TermStatistics) -> f64 {
+        ((collection.number_of_docs as f64 + 1.0) / term.document_frequency as f64).ln()
+    }
+
+    fn document_weight(
+        &self,
+        collection: &CollectionStatistics,
+        _: &TermStatistics,
+        document: &DocumentStatistics,
+        term_doc_frequency: u32,
+    ) -> f64 {
+        bm25_term_frequency(self.k1, self.b, collection, document.length as f64, term_doc_frequency) + self.delta
+    }
+
+    fn length_normalizer(&self, _: &CollectionStatistics, _: &DocumentStatistics) -> f64 {
+        1.0
+    }
+
+    fn max_score(&self, collection: &CollectionStatistics, term: &TermStatistics, bounds: &PostingBounds) -> Option<f64> {
+        let max_weight = bm25_term_frequency(self.k1, self.b, collection, bounds.min_doc_length as f64, bounds.max_term_frequency) + self.delta;
+        Some(self.query_weight(collection, term) * max_weight)
+    }
+}
+
+/*
+ * Saturated, length normalized term frequency shared by the BM25 scorers
+ *
+ * # Arguments
+ *
+ * *`k1` - Saturation of the term frequency
+ * *`b` - Strength of document length normalization
+ * *`collection` - Statistics holding the average document length
+ * *`doc_length` - Length of the document
+ * *`term_doc_frequency` - Frequency of the term in the document
+ *
+ * # Returns
+ *
+ * tf * (k1 + 1) / (tf + k1 * (1 - b + b * length / avg_length))
+ */
+fn bm25_term_frequency(
+    k1: f64,
+    b: f64,
+    collection: &CollectionStatistics,
+    doc_length: f64,
+    term_doc_frequency: u32,
+) -> f64 {
+    let length_ratio = if collection.avg_doc_length > 0.0 {
+        doc_length / collection.avg_doc_length
+    } else {
+        1.0
+    };
+    let tf = term_doc_frequency as f64;
+    tf * (k1 + 1.0) / (tf + k1 * (1.0 - b + b * length_ratio))
+}
+
+/*
+ * Term frequencies relative to the document's average term frequency, normalized by byte size
+ */
+pub struct WackyScorer;
+
+impl Scorer for WackyScorer {
+    fn name(&self) -> &str {
+        "wacky"
+    }
+
+    fn query_weight(&self, collection: &CollectionStatistics, term: &TermStatistics) -> f64 {
+        let number_of_docs = collection.number_of_docs as f64;
+        let document_frequency = term.document_frequency as f64;
+        0.0_f64.max(((number_of_docs - document_frequency) / document_frequency).ln())
+    }
+
+    fn document_weight(&self, _: &CollectionStatistics, _: &TermStatistics, document: &DocumentStatistics, term_doc_frequency: u32) -> f64 {
+        (1.0 + (term_doc_frequency as f64).ln()) / (1.0 + document.avg_tftd.ln())
+    }
+
+    fn length_normalizer(&self, _: &CollectionStatistics, document: &DocumentStatistics) -> f64 {
+        (document.byte_size as f64).sqrt()
+    }
+
+    fn max_score(&self, collection: &CollectionStatistics, term: &TermStatistics, bounds: &PostingBounds) -> Option<f64> {
+        let max_weight = (1.0 + (bounds.max_term_frequency as f64).ln()) / (1.0 + bounds.min_avg_tftd.ln());
+        Some(self.query_weight(collection, term) * max_weight / (bounds.min_byte_size as f64).sqrt())
+    }
+}
+
+/*
+ * Query likelihood with Dirichlet prior smoothing
+ *
+ * Ranks documents by log P(q|d), where the document model is smoothed with the collection model
+ * in proportion to mu / (|d| + mu)
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct DirichletScorer {
+    /*
+     * Weight of the collection model, in pseudo-tokens
+     */
+    pub mu: f64,
+}
+
+impl Default for DirichletScorer {
+    fn default() -> DirichletScorer {
+        DirichletScorer::new(2000.0)
+    }
+}
+
+impl DirichletScorer {
+    pub fn new(mu: f64) -> DirichletScorer {
+        DirichletScorer { mu }
+    }
+}
+
+impl Scorer for DirichletScorer {
+    fn name(&self) -> &str {
+        "dirichlet"
+    }
+
+    fn query_weight(&self, _: &CollectionStatistics, _: &TermStatistics) -> f64 {
+        1.0
+    }
+
+    fn document_weight(
+        &self,
+        collection: &CollectionStatistics,
+        term: &TermStatistics,
+        _: &DocumentStatistics,
+        term_doc_frequency: u32,
+    ) -> f64 {
+        let collection_probability = collection_probability(collection, term);
+        (1.0 + term_doc_frequency as f64 / (self.mu * collection_probability)).ln()
+    }
+
+    fn length_normalizer(&self, _: &CollectionStatistics, _: &DocumentStatistics) -> f64 {
+        1.0
+    }
+
+    fn document_score(
+        &self,
+        _: &CollectionStatistics,
+        document: &DocumentStatistics,
+        accumulator: f64,
+        query_length: u32,
+    ) -> f64 {
+        accumulator + query_length as f64 * (self.mu / (document.token_count() + self.mu)).ln()
+    }
+}
+
+/*
+ * Query likelihood with Jelinek-Mercer smoothing
+ *
+ * Ranks documents by log P(q|d), where the document model is interpolated with the collection
+ * model by a fixed lambda
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct JelinekMercerScorer {
+    /*
+     * Weight of the collection model, between 0 and 1
+     */
+    pub lambda: f64,
+}
+
+impl Default for JelinekMercerScorer {
+    fn default() -> JelinekMercerScorer {
+        JelinekMercerScorer::new(0.1)
+    }
+}
+
+impl JelinekMercerScorer {
+    pub fn new(lambda: f64) -> JelinekMercerScorer {
+        JelinekMercerScorer { lambda }
+    }
+}
+
+impl Scorer for JelinekMercerScorer {
+    fn name(&self) -> &str {
+        "jelinek-mercer"
+    }
+
+    fn query_weight(&self, _: &CollectionStatistics, _: &TermStatistics) -> f64 {
+        1.0
+    }
+
+    fn document_weight(
+        &self,
+        collection: &CollectionStatistics,
+        term: &TermStatistics,
+        document: &DocumentStatistics,
+        term_doc_frequency: u32,
+    ) -> f64 {
+        let document_probability = term_doc_frequency as f64 / document.token_count();
+        let collection_probability = collection_probability(collection, term);
+        (1.0 + (1.0 - self.lambda) * document_probability / (self.lambda * collection_probability)).ln()
+    }
+
+    fn length_normalizer(&self, _: &CollectionStatistics, _: &DocumentStatistics) -> f64 {
+        1.0
+    }
+
+    fn max_score(&self, collection: &CollectionStatistics, term: &TermStatistics, bounds: &PostingBounds) -> Option<f64> {
+        let min_token_count = bounds.min_doc_length as f64;
+        let max_document_probability = bounds.max_term_frequency as f64 / min_token_count;
+        let collection_probability = collection_probability(collection, term);
+        Some((1.0 + (1.0 - self.lambda) * max_document_probability / (self.lambda * collection_probability)).ln())
+    }
+}
+
+/*
+ * Probability of a term in the collection model, P(t|C) = cf / total term frequency
+ */
+fn collection_probability(collection: &CollectionStatistics, term: &TermStatistics) -> f64 {
+    term.collection_frequency.max(1) as f64 / collection.total_term_frequency.max(1) as f64
+}
+
+/*
+ * Collection of scorers selectable by name
+ */
+pub struct ScorerRegistry {
+    scorers: HashMap<String, Box<dyn Scorer>>,
+}
+
+impl Default for ScorerRegistry {
+    fn default() -> ScorerRegistry {
+        ScorerRegistry::new()
+    }
+}
+
+impl ScorerRegistry {
+    /*
+     * Constructs a registry holding the built in scorers: default, tfidf, okapi, bm25plus, wacky,
+     * dirichlet and jelinek-mercer
+     */
+    pub fn new() -> ScorerRegistry {
+        let mut registry = ScorerRegistry {
+            scorers: HashMap::new(),
+        };
+        registry.register(Box::new(DefaultScorer));
+        registry.register(Box::new(TfIdfScorer));
+        registry.register(Box::new(OkapiScorer::default()));
+        registry.register(Box::new(Bm25PlusScorer::default()));
+        registry.register(Box::new(WackyScorer));
+        registry.register(Box::new(DirichletScorer::default()));
+        registry.register(Box::new(JelinekMercerScorer::default()));
+        registry
+    }
+
+    /*
+     * Registers a scorer under its name, replacing any scorer already registered under that name
+     *
+     * # Arguments
+     *
+     * *`scorer` - The scorer to register
+     */
+    pub fn register(&mut self, scorer: Box<dyn Scorer>) {
+        self.scorers.insert(scorer.name().to_string(), scorer);
+    }
+
+    /*
+     * Looks up a scorer by name
+     *
+     * # Arguments
+     *
+     * *`name` - Name the scorer was registered under
+     *
+     * # Returns
+     *
+     * The scorer, or an error if no scorer is registered under that name
+     */
+    pub fn get(&self, name: &str) -> Result<&dyn Scorer, &'static str> {
+        match self.scorers.get(name) {
+            Some(scorer) => Ok(scorer.as_ref()),
+            None => Err("Unknown weighting scheme"),
+        }
+    }
+
+    /*
+     * Returns the sorted names of every registered scorer
+     */
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.scorers.keys().map(|name| name.as_str()).collect();
+        names.sort();
+        names
+    }
+}
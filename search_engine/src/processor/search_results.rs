@@ -0,0 +1,188 @@
+use processor::snippet::Snippet;
+use std::time::Duration;
+
+/*
+ * The contribution of one part of a ranked query to a document's score
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScoreComponent {
+    /*
+     * The normalized query term, the text of a phrase or proximity clause, or "proximity" for the
+     * term-pair proximity boost
+     */
+    pub label: String,
+
+    /*
+     * Occurrences of the term or matches of the clause in the document
+     */
+    pub term_frequency: u32,
+
+    /*
+     * Amount the component adds to the document's accumulator, before length normalization
+     */
+    pub score: f64,
+}
+
+/*
+ * A ranked document together with the contributions making up its score
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedDocument {
+    pub doc_id: u32,
+    pub score: f64,
+    pub explanation: Vec<ScoreComponent>,
+}
+
+/*
+ * A document returned by a query
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit {
+    pub doc_id: u32,
+
+    /*
+     * Path of the document's file
+     */
+    pub path: String,
+
+    /*
+     * Title of the document, from the document store
+     */
+    pub title: String,
+
+    /*
+     * Score of the document, or None for boolean retrieval
+     */
+    pub score: Option<f64>,
+
+    /*
+     * Per-term breakdown of the score, empty for boolean retrieval
+     */
+    pub explanation: Vec<ScoreComponent>,
+
+    /*
+     * Passage of the document around the query terms, if snippets were requested
+     */
+    pub snippet: Option<Snippet>,
+}
+
+/*
+ * One page of the documents returned by a query
+ */
+#[derive(Debug, Clone)]
+pub struct SearchResults {
+    /*
+     * The documents of the page, in result order
+     */
+    pub hits: Vec<SearchHit>,
+
+    /*
+     * Number of documents matching the query, across every page
+     */
+    pub total: usize,
+
+    /*
+     * Whether total only counts part of the matching documents, because ranking skipped the rest.
+     * It still exceeds the documents up to the end of the page whenever more remain
+     */
+    pub total_is_lower_bound: bool,
+
+    /*
+     * Number of documents skipped before the page
+     */
+    pub offset: usize,
+
+    /*
+     * Largest number of documents the page may hold
+     */
+    pub limit: usize,
+
+    /*
+     * Time spent answering the query
+     */
+    pub elapsed: Duration,
+}
+
+impl SearchResults {
+    pub fn is_empty(&self) -> bool {
+        self.hits.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hits.len()
+    }
+
+    /*
+     * Whether documents remain after this page
+     */
+    pub fn has_more(&self) -> bool {
+        self.offset + self.hits.len() < self.total
+    }
+}
+
+/*
+ * A document returned by a query run across several indexes, tagged with the index holding it
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FederatedHit {
+    /*
+     * Name of the index the document belongs to. Document IDs are only unique within an index
+     */
+    pub index: String,
+
+    #[serde(flatten)]
+    pub hit: SearchHit,
+}
+
+/*
+ * One page of the documents returned by a query run across several indexes
+ */
+#[derive(Debug, Clone)]
+pub struct FederatedResults {
+    /*
+     * The documents of the page, in result order
+     */
+    pub hits: Vec<FederatedHit>,
+
+    /*
+     * Number of documents matching the query in every index, across every page
+     */
+    pub total: usize,
+
+    /*
+     * Whether total only counts part of the matching documents of some index
+     */
+    pub total_is_lower_bound: bool,
+
+    /*
+     * Number of documents skipped before the page
+     */
+    pub offset: usize,
+
+    /*
+     * Largest number of documents the page may hold
+     */
+    pub limit: usize,
+
+    /*
+     * Time spent answering the query
+     */
+    pub elapsed: Duration,
+}
+
+impl FederatedResults {
+    pub fn is_empty(&self) -> bool {
+        self.hits.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hits.len()
+    }
+
+    /*
+     * Whether documents remain after this page
+     */
+    pub fn has_more(&self) -> bool {
+        self.offset + self.hits.len() < self.total
+    }
+}
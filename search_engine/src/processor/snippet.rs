@@ -0,0 +1,271 @@
+use index::disk_inverted_index::TokenOffsets;
+use parser::document_parser;
+
+/*
+ * How the matched terms of a snippet are marked when it is rendered
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Highlight {
+    /*
+     * Bold yellow ANSI escape codes, for terminals
+     */
+    Ansi,
+
+    /*
+     * <mark></mark> tags around matched terms, with the rest of the text HTML escaped
+     */
+    Markup,
+}
+
+impl Highlight {
+    /*
+     * The text written before and after a matched term
+     */
+    fn tags(&self) -> (&'static str, &'static str) {
+        match *self {
+            Highlight::Ansi => ("\x1b[1;33m", "\x1b[0m"),
+            Highlight::Markup => ("<mark>", "</mark>"),
+        }
+    }
+
+    fn escape(&self, text: &str) -> String {
+        match *self {
+            Highlight::Ansi => text.to_string(),
+            Highlight::Markup => text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;"),
+        }
+    }
+}
+
+/*
+ * A passage of a document around the query terms it contains (keyword in context)
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snippet {
+    /*
+     * The tokens of the passage separated by single spaces
+     */
+    pub text: String,
+
+    /*
+     * Byte ranges of the matched terms within the text, in increasing order
+     */
+    pub highlights: Vec<(usize, usize)>,
+
+    /*
+     * Whether the passage starts after the beginning of the document
+     */
+    pub leading: bool,
+
+    /*
+     * Whether the passage ends before the end of the document
+     */
+    pub trailing: bool,
+}
+
+impl Snippet {
+    /*
+     * Renders the passage with its matched terms highlighted and ellipses marking omitted text
+     *
+     * # Arguments
+     *
+     * *`highlight` - How the matched terms are marked
+     *
+     * # Returns
+     *
+     * The rendered passage
+     */
+    pub fn render(&self, highlight: Highlight) -> String {
+        let (open, close) = highlight.tags();
+        let mut rendered = String::new();
+        if self.leading {
+            rendered.push_str("...");
+        }
+        let mut last = 0;
+        for &(start, end) in self.highlights.iter() {
+            rendered.push_str(&highlight.escape(&self.text[last..start]));
+            rendered.push_str(open);
+            rendered.push_str(&highlight.escape(&self.text[start..end]));
+            rendered.push_str(close);
+            last = end;
+        }
+        rendered.push_str(&highlight.escape(&self.text[last..]));
+        if self.trailing {
+            rendered.push_str("...");
+        }
+        rendered
+    }
+}
+
+/*
+ * Finds the byte range of every whitespace separated token of a document body, with the position
+ * build_index records for its first term. The parts of a hyphenated token take consecutive
+ * positions, so a token may span several positions
+ *
+ * # Arguments
+ *
+ * *`body` - The body of the document
+ *
+ * # Returns
+ *
+ * The first position and the start and end byte offsets of every token
+ */
+pub fn token_offsets(body: &str) -> Vec<(u32, usize, usize)> {
+    let mut offsets: Vec<(u32, usize, usize)> = Vec::new();
+    let mut position = 0;
+    let mut start = None;
+    for (i, c) in body.char_indices().chain(Some((body.len(), ' '))) {
+        if c.is_whitespace() {
+            if let Some(token_start) = start.take() {
+                offsets.push((position, token_start, i));
+                let terms = document_parser::normalize_token(body[token_start..i].to_string());
+                position += document_parser::relative_positions(&terms).1;
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    offsets
+}
+
+/*
+ * Token, query term and character offsets of one occurrence of a query term
+ */
+type Occurrence = (usize, usize, Option<(u32, u32)>);
+
+/*
+ * Where a query term occurs in a document
+ */
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TermOccurrences {
+    /*
+     * The sorted positions of the term
+     */
+    pub positions: Vec<u32>,
+
+    /*
+     * Start and end character offsets of the term at every position, if the index recorded them
+     */
+    pub offsets: Option<TokenOffsets>,
+}
+
+/*
+ * Builds the snippet of a document from the occurrences of the query terms it contains. The
+ * passage chosen is the window holding the most distinct query terms, then the most occurrences,
+ * preferring the earliest such window. A position belongs to the token token_offsets places it in,
+ * so the parts of a hyphenated word share its token. Terms are highlighted at their recorded
+ * character offsets, or as the whole token without surrounding punctuation when no offsets were
+ * recorded
+ *
+ * # Arguments
+ *
+ * *`body` - The body of the document
+ * *`terms` - The occurrences of every distinct query term found in the document
+ * *`length` - Number of tokens in the passage
+ *
+ * # Returns
+ *
+ * The snippet, starting at the beginning of the document if no query term occurs in it
+ */
+pub fn generate(body: &str, terms: &[TermOccurrences], length: usize) -> Snippet {
+    let offsets = token_offsets(body);
+    let length = length.max(1);
+
+    let last_position = offsets.last().map_or(0, |&(position, start, end)| {
+        position + document_parser::relative_positions(&document_parser::normalize_token(body[start..end].to_string())).1
+    });
+
+    let mut matches: Vec<Occurrence> = Vec::new();
+    for (term, occurrences) in terms.iter().enumerate() {
+        for (i, &position) in occurrences.positions.iter().enumerate() {
+            if position < last_position {
+                // The token holding the position is the last one starting at or before it
+                let token = offsets.partition_point(|&(first, _, _)| first <= position) - 1;
+                let characters = occurrences.offsets.as_ref().and_then(|offsets| offsets.get(i).cloned());
+                matches.push((token, term, characters));
+            }
+        }
+    }
+    matches.sort();
+
+    let mut start = 0;
+    let mut best: Option<(usize, usize)> = None;
+    for (i, &(first, _, _)) in matches.iter().enumerate() {
+        let window: Vec<&Occurrence> = matches[i..]
+            .iter()
+            .take_while(|&&(position, _, _)| position < first + length)
+            .collect();
+        let mut window_terms: Vec<usize> = window.iter().map(|&&(_, term, _)| term).collect();
+        window_terms.sort();
+        window_terms.dedup();
+        let score = (window_terms.len(), window.len());
+        if best.map_or(true, |best| score > best) {
+            best = Some(score);
+            // Center the matches inside the passage
+            let last = window.last().unwrap().0;
+            let slack = length - (last - first + 1);
+            start = first.saturating_sub(slack / 2);
+        }
+    }
+    let start = start.min(offsets.len().saturating_sub(length));
+    let end = (start + length).min(offsets.len());
+
+    // Byte offset of every character, to locate recorded character offsets in the body
+    let mut character_bytes: Vec<usize> = body.char_indices().map(|(i, _)| i).collect();
+    character_bytes.push(body.len());
+
+    let mut text = String::new();
+    let mut highlights: Vec<(usize, usize)> = Vec::new();
+    for (position, &(_, token_start, token_end)) in offsets.iter().enumerate().take(end).skip(start) {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        let token = &body[token_start..token_end];
+        let first = matches.partition_point(|&(matched, _, _)| matched < position);
+        for &(_, _, characters) in matches[first..].iter().take_while(|&&(matched, _, _)| matched == position) {
+            let recorded = characters.and_then(|(character_start, character_end)| {
+                let byte_start = *character_bytes.get(character_start as usize)?;
+                let byte_end = *character_bytes.get(character_end as usize)?;
+                if token_start <= byte_start && byte_start < byte_end && byte_end <= token_end {
+                    Some((byte_start - token_start, byte_end - token_start))
+                } else {
+                    None
+                }
+            });
+            let (word_start, word_end) = match recorded {
+                Some(range) => range,
+                None => {
+                    // Leave surrounding punctuation out of the highlight
+                    let trimmed = token.trim_start_matches(|c: char| !c.is_alphanumeric());
+                    let word = trimmed.trim_end_matches(|c: char| !c.is_alphanumeric());
+                    let word_start = token.len() - trimmed.len();
+                    (word_start, word_start + word.len())
+                }
+            };
+            if word_start < word_end {
+                highlights.push((text.len() + word_start, text.len() + word_end));
+            }
+        }
+        text.push_str(token);
+    }
+
+    // Terms of one token may overlap, such as the parts of a hyphenated word and the joined word
+    highlights.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (highlight_start, highlight_end) in highlights {
+        match merged.last_mut() {
+            Some(last) if highlight_start <= last.1 => last.1 = last.1.max(highlight_end),
+            _ => merged.push((highlight_start, highlight_end)),
+        }
+    }
+
+    Snippet {
+        text,
+        highlights: merged,
+        leading: start > 0,
+        trailing: end < offsets.len(),
+    }
+}
@@ -0,0 +1,52 @@
+use index::disk_inverted_index::DiskInvertedIndex;
+use index::disk_inverted_index::IndexReader;
+use index::k_gram_index::KGramIndex;
+use parser::document_parser;
+
+/*
+ * A word of the indexed documents offered to complete a partially typed query term
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Suggestion {
+    pub term: String,
+
+    /*
+     * Number of documents containing the word or another word with the same stem
+     */
+    pub document_frequency: u32,
+}
+
+/*
+ * Finds the words of the indexed documents starting with a prefix. The vocabulary of the index
+ * holds stems, which are not words to offer, so the words are the unstemmed terms kept by the K
+ * Gram Index, found from the gram marking the start of a word
+ *
+ * # Arguments
+ *
+ * *`prefix` - The partially typed term, matched case insensitively
+ * *`index` - The Disk Inverted Index counting the documents of every word
+ * *`kgram` - The K Gram Index holding the words. Indexes built without it have no suggestions
+ * *`limit` - Maximum number of suggestions returned
+ *
+ * # Returns
+ *
+ * The matching words contained in the most documents first, with ties broken by word
+ */
+pub fn suggest(prefix: &str, index: &DiskInvertedIndex, kgram: &KGramIndex, limit: usize) -> Vec<Suggestion> {
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.is_empty() || limit == 0 {
+        return Vec::new();
+    }
+
+    let words: Vec<String> = kgram.get_terms_with_prefix(&prefix).into_iter().cloned().collect();
+    let stems = document_parser::stem_terms(words.clone());
+    let mut suggestions: Vec<Suggestion> = words
+        .into_iter()
+        .zip(stems)
+        .map(|(term, stem)| Suggestion { term, document_frequency: index.get_document_frequency(&stem) })
+        .filter(|suggestion| suggestion.document_frequency > 0)
+        .collect();
+    suggestions.sort_by(|a, b| b.document_frequency.cmp(&a.document_frequency).then_with(|| a.term.cmp(&b.term)));
+    suggestions.truncate(limit);
+    suggestions
+}
@@ -0,0 +1,240 @@
+use index::postings_cursor::PostingsCursor;
+use processor::scorer::CollectionStatistics;
+use processor::scorer::DocumentStatistics;
+use processor::scorer::Scorer;
+use processor::scorer::TermStatistics;
+
+/*
+ * Relative margin added to every score bound so that rounding never lets a bound fall below the
+ * score it is meant to cover
+ */
+const BOUND_SLACK: f64 = 1e-9;
+
+/*
+ * Strategy used to find the top ranked documents
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pruning {
+    /*
+     * Scores every document of every posting list
+     */
+    Exhaustive,
+
+    /*
+     * Skips documents whose summed per-term score bounds cannot beat the current top k (WAND)
+     */
+    Wand,
+
+    /*
+     * WAND refined with per-block score bounds, skipping whole blocks of postings (Block-Max WAND)
+     */
+    BlockMaxWand,
+}
+
+/*
+ * A query term's postings together with the bounds of the scores they can produce
+ */
+pub struct TermCursor {
+    postings: PostingsCursor,
+    query_weight: f64,
+    term: TermStatistics,
+    max_score: f64,
+    blocks: Vec<(u32, f64)>,
+}
+
+impl TermCursor {
+    /*
+     * Constructs a cursor at the first posting of a term
+     *
+     * # Arguments
+     *
+     * *`postings` - Cursor at the first posting of the term
+     * *`query_weight` - Weight of the term in the query
+     * *`term` - Statistics of the term
+     * *`max_score` - Upper bound of the term's score in any document
+     * *`blocks` - Last document ID and upper bound of the term's score for every block of postings
+     */
+    pub fn new(
+        postings: PostingsCursor,
+        query_weight: f64,
+        term: TermStatistics,
+        max_score: f64,
+        blocks: Vec<(u32, f64)>,
+    ) -> TermCursor {
+        TermCursor {
+            postings,
+            query_weight,
+            term,
+            max_score: max_score * (1.0 + BOUND_SLACK),
+            blocks: blocks
+                .into_iter()
+                .map(|(last_doc_id, block_max)| (last_doc_id, block_max * (1.0 + BOUND_SLACK)))
+                .collect(),
+        }
+    }
+
+    /*
+     * Document ID of the current posting, or u32::MAX once every posting has been passed
+     */
+    fn doc_id(&self) -> u32 {
+        self.postings.doc_id()
+    }
+
+    /*
+     * Moves to the first posting whose document ID is at least target. Blocks of postings passed
+     * over are never read
+     */
+    fn advance_to(&mut self, target: u32) {
+        self.postings.advance_to(target);
+    }
+
+    /*
+     * Last document ID and score bound of the block holding the first posting at or after doc_id
+     */
+    fn block_at(&self, doc_id: u32) -> (u32, f64) {
+        let block = self.blocks.partition_point(|&(last_doc_id, _)| last_doc_id < doc_id);
+        self.blocks.get(block).cloned().unwrap_or((u32::MAX, 0.0))
+    }
+}
+
+/*
+ * Finds the k best scoring documents with WAND or Block-Max WAND. The result is identical to
+ * scoring every posting, as long as every cursor's bounds hold for the scorer
+ *
+ * # Arguments
+ *
+ * *`cursors` - One cursor per query term, in query order
+ * *`k` - Number of documents to return
+ * *`block_max` - Whether to also prune with the per-block bounds
+ * *`scorer` - The weighting scheme
+ * *`collection` - Statistics of the indexed collection
+ * *`query_length` - Number of query terms found in the index
+ * *`document_statistics` - Looks up the statistics of a document
+ *
+ * # Returns
+ *
+ * The document ID and score of the top k documents, best first, with ties broken by lower
+ * document ID
+ */
+pub fn top_k(
+    cursors: &mut [TermCursor],
+    k: usize,
+    block_max: bool,
+    scorer: &dyn Scorer,
+    collection: &CollectionStatistics,
+    query_length: u32,
+    document_statistics: &mut dyn FnMut(u32) -> DocumentStatistics,
+) -> Vec<(u32, f64)> {
+    let mut results: Vec<(u32, f64)> = Vec::new();
+    if k == 0 {
+        return results;
+    }
+    let mut order: Vec<usize> = (0..cursors.len()).collect();
+    loop {
+        let threshold = if results.len() < k { 0.0 } else { worst(&results).1 };
+        order.sort_by_key(|&i| cursors[i].doc_id());
+
+        // The pivot is the first document at which the summed bounds could beat the threshold;
+        // every document before it can be skipped
+        let mut upper_bound = 0.0;
+        let mut pivot = None;
+        for (rank, &i) in order.iter().enumerate() {
+            if cursors[i].doc_id() == u32::MAX {
+                break;
+            }
+            upper_bound += cursors[i].max_score;
+            if upper_bound > threshold {
+                pivot = Some(rank);
+                break;
+            }
+        }
+        let mut pivot = match pivot {
+            Some(pivot) => pivot,
+            None => break,
+        };
+        let pivot_doc = cursors[order[pivot]].doc_id();
+        while pivot + 1 < order.len() && cursors[order[pivot + 1]].doc_id() == pivot_doc {
+            pivot += 1;
+        }
+
+        if block_max {
+            // Until the end of the first block, the pivot's cursors cannot score more than their
+            // current blocks allow, and no other cursor has postings there
+            let mut block_bound = 0.0;
+            let mut next = order
+                .get(pivot + 1)
+                .map_or(u32::MAX, |&i| cursors[i].doc_id());
+            for &i in &order[..=pivot] {
+                let (last_doc_id, block_max_score) = cursors[i].block_at(pivot_doc);
+                block_bound += block_max_score;
+                next = next.min(last_doc_id.saturating_add(1));
+            }
+            if block_bound <= threshold {
+                for &i in &order[..=pivot] {
+                    cursors[i].advance_to(next);
+                }
+                continue;
+            }
+        }
+
+        if cursors[order[0]].doc_id() == pivot_doc {
+            let document = document_statistics(pivot_doc);
+            let mut accumulator = 0.0;
+            for cursor in cursors.iter() {
+                if cursor.doc_id() == pivot_doc {
+                    let term_doc_frequency = cursor.postings.term_frequency();
+                    accumulator += cursor.query_weight
+                        * scorer.document_weight(collection, &cursor.term, &document, term_doc_frequency);
+                }
+            }
+            if accumulator > 0.0 {
+                let score = scorer.document_score(collection, &document, accumulator, query_length);
+                insert(&mut results, k, pivot_doc, score);
+            }
+            for &i in &order[..=pivot] {
+                cursors[i].advance_to(pivot_doc + 1);
+            }
+        } else {
+            for &i in &order[..pivot] {
+                cursors[i].advance_to(pivot_doc);
+            }
+        }
+    }
+    sort_results(&mut results);
+    results
+}
+
+/*
+ * Sorts scored documents best first, breaking ties by lower document ID
+ */
+pub fn sort_results(results: &mut [(u32, f64)]) {
+    results.sort_by(|first, second| {
+        second.1.partial_cmp(&first.1).unwrap().then(first.0.cmp(&second.0))
+    });
+}
+
+/*
+ * The lowest ranked of the collected documents
+ */
+fn worst(results: &[(u32, f64)]) -> (u32, f64) {
+    *results
+        .iter()
+        .min_by(|first, second| first.1.partial_cmp(&second.1).unwrap().then(second.0.cmp(&first.0)))
+        .unwrap()
+}
+
+/*
+ * Adds a document to the top k, evicting the lowest ranked document when full. Documents arrive
+ * in increasing ID order, so a document tying with the lowest ranked one loses the tie
+ */
+fn insert(results: &mut Vec<(u32, f64)>, k: usize, doc_id: u32, score: f64) {
+    if results.len() < k {
+        results.push((doc_id, score));
+        return;
+    }
+    let lowest = worst(results);
+    if score > lowest.1 {
+        let position = results.iter().position(|&result| result == lowest).unwrap();
+        results[position] = (doc_id, score);
+    }
+}
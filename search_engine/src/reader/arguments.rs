@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/*
+ * A subcommand given on the command line, such as `query <dir> --mode ranked "q"`
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    /*
+     * Name of the subcommand
+     */
+    pub name: String,
+
+    /*
+     * Arguments that are not options, in order
+     */
+    pub positional: Vec<String>,
+
+    options: HashMap<String, String>,
+    flags: HashSet<String>,
+}
+
+impl Command {
+    /*
+     * The value given to an option, such as "ranked" for `--mode ranked` or `--mode=ranked`
+     */
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(|value| value.as_str())
+    }
+
+    /*
+     * The value given to an option parsed as a number or other type
+     *
+     * # Arguments
+     *
+     * *`name` - Name of the option, without the leading dashes
+     * *`default` - Value used when the option is not given
+     *
+     * # Returns
+     *
+     * The value, or an error if the option cannot be parsed
+     */
+    pub fn parse_option<T: FromStr>(&self, name: &str, default: T) -> Result<T, &'static str> {
+        match self.option(name) {
+            Some(value) => value.parse().map_err(|_| "Invalid option value"),
+            None => Ok(default),
+        }
+    }
+
+    /*
+     * Whether a flag such as `--proximity` was given
+     */
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+}
+
+/*
+ * Parses the arguments of a subcommand. Options take a value as `--name value` or `--name=value`,
+ * flags take none, and everything after `--` is positional
+ *
+ * # Arguments
+ *
+ * *`arguments` - The subcommand followed by its arguments, without the program name
+ * *`value_options` - Names of the options of the subcommand that take a value
+ * *`flags` - Names of the flags of the subcommand
+ *
+ * # Returns
+ *
+ * The parsed subcommand, or an error if no subcommand is given, an option is unknown or an option
+ * is missing its value
+ */
+pub fn parse_command(arguments: &[String], value_options: &[&str], flags: &[&str]) -> Result<Command, &'static str> {
+    let name = arguments.first().ok_or("No command given")?.clone();
+    let mut command = Command {
+        name,
+        positional: Vec::new(),
+        options: HashMap::new(),
+        flags: HashSet::new(),
+    };
+
+    let mut arguments = arguments[1..].iter();
+    while let Some(argument) = arguments.next() {
+        if argument == "--" {
+            command.positional.extend(arguments.by_ref().cloned());
+            break;
+        }
+        let option = match argument.strip_prefix("--") {
+            Some(option) => option,
+            None => {
+                command.positional.push(argument.clone());
+                continue;
+            },
+        };
+        let (option, inline_value) = match option.find('=') {
+            Some(equals) => (&option[..equals], Some(option[equals + 1..].to_string())),
+            None => (option, None),
+        };
+        if value_options.contains(&option) {
+            let value = match inline_value {
+                Some(value) => value,
+                None => arguments.next().ok_or("Option is missing its value")?.clone(),
+            };
+            command.options.insert(option.to_string(), value);
+        } else if flags.contains(&option) && inline_value.is_none() {
+            command.flags.insert(option.to_string());
+        } else {
+            return Err("Unknown option");
+        }
+    }
+    Ok(command)
+}
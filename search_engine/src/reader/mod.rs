@@ -1,2 +1,3 @@
+pub mod arguments;
 pub mod read_file;
 pub mod user_input;
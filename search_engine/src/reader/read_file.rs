@@ -3,6 +3,7 @@ use std::io::BufReader;
 use std::io::Read;
 use std::fs::DirEntry;
 use std::path::Path;
+use std::collections::BTreeMap;
 
 /*
  * Represents a document read from a file
@@ -179,6 +180,42 @@ pub fn read_json_file(file_name: &str) -> Document {
 }
 
 
+/*
+ * Reads the values of a document beyond its title, body and url, to be kept in the document store
+ *
+ * # Arguments
+ *
+ * *`file_name` - The file of the document
+ *
+ * # Returns
+ *
+ * The other top level values of a JSON document by name, strings as they are and any other value
+ * as JSON text. Text files have no other values
+ */
+pub fn read_stored_fields(file_name: &str) -> BTreeMap<String, String> {
+    let mut fields = BTreeMap::new();
+    if Path::new(file_name).extension().map_or(true, |extension| extension != "json") {
+        return fields;
+    }
+    let mut file = File::open(file_name).expect("File not Found");
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).expect("Error reading contents");
+    let value: ::serde_json::Value = ::serde_json::from_str(&contents).expect("Error retrieving JSON document");
+    if let ::serde_json::Value::Object(object) = value {
+        for (name, value) in object {
+            if name == "title" || name == "body" || name == "url" {
+                continue;
+            }
+            let text = match value {
+                ::serde_json::Value::String(text) => text,
+                other => other.to_string(),
+            };
+            fields.insert(name, text);
+        }
+    }
+    fields
+}
+
 pub fn read_n<R>(reader: R, bytes_to_read: u64) -> Vec<u8>
 where 
     R: Read 
@@ -0,0 +1,528 @@
+use classifier::classifier::Classification;
+use classifier::classifier::Classifier;
+use classifier::knn_classifier::KnnClassifier;
+use classifier::knn_classifier::Voting;
+use classifier::knn_classifier::DEFAULT_NEIGHBOURS;
+use classifier::model::ClassifierModel;
+use classifier::training_set::TrainingClass;
+use classifier::training_set::TrainingSet;
+use index::disk_inverted_index::DiskInvertedIndex;
+use index::disk_inverted_index::IndexReader;
+use index::index_set::IndexSet;
+use index::k_gram_index::KGramIndex;
+use processor::federated_search;
+use processor::federated_search::SearchTarget;
+use processor::query_processor;
+use processor::query_processor::SearchOptions;
+use processor::scorer::ScorerRegistry;
+use processor::suggest;
+use serde_json::Map;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::thread;
+use tiny_http;
+use tiny_http::Header;
+
+/*
+ * An index served under a name
+ */
+#[derive(Clone)]
+pub struct ServedIndex {
+    name: String,
+    index: DiskInvertedIndex,
+    kgram: Arc<KGramIndex>,
+}
+
+impl ServedIndex {
+    /*
+     * Constructs a served index
+     *
+     * # Arguments
+     *
+     * *`name` - Name the index is selected by in requests
+     * *`index` - The Disk Inverted Index
+     * *`kgram` - The K Gram Index used to expand wildcards, empty if the index has none
+     */
+    pub fn new(name: &str, index: DiskInvertedIndex, kgram: KGramIndex) -> ServedIndex {
+        ServedIndex {
+            name: name.to_string(),
+            index,
+            kgram: Arc::new(kgram),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+}
+
+/*
+ * Answer to a request: its HTTP status and JSON body
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Response {
+        Response { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Response {
+        Response {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+/*
+ * A classifier trained for an index, method and classes, kept for the next requests. kNN
+ * classifiers are kept apart, as every request chooses its own neighbours and voting
+ */
+#[derive(Clone)]
+enum TrainedClassifier {
+    Knn(Arc<KnnClassifier>),
+    Other(Arc<dyn Classifier + Send + Sync>),
+}
+
+impl TrainedClassifier {
+    fn new(model: ClassifierModel, documents: &DiskInvertedIndex) -> TrainedClassifier {
+        match model {
+            ClassifierModel::Knn(model) => TrainedClassifier::Knn(Arc::new(KnnClassifier::from_model(documents, model))),
+            model => TrainedClassifier::Other(Arc::from(model.classifier(documents))),
+        }
+    }
+
+    fn classify(&self, doc_id: u32, k: u32, voting: Voting) -> Classification {
+        match *self {
+            TrainedClassifier::Knn(ref classifier) => classifier.classify_with(doc_id, k, voting),
+            TrainedClassifier::Other(ref classifier) => classifier.classify(doc_id),
+        }
+    }
+}
+
+/*
+ * Parameters of a request, decoded from its query string
+ */
+type Parameters = HashMap<String, String>;
+
+/*
+ * Answers search, suggestion, document, classification and statistics requests over HTTP with
+ * JSON bodies:
+ *
+ * GET /search?q=QUERY[&index=NAME][&mode=ranked|boolean][&scheme=NAME][&limit=N][&offset=N][&snippet=N]
+ * GET /suggest?q=PREFIX[&index=NAME][&limit=N]
+ * GET /document/ID[?index=NAME]
+ * GET /classify?doc=FILE[&index=NAME][&classes=NAME,NAME...][&method=rocchio|bayes|knn][&k=N][&voting=majority|weighted]
+ * GET /stats[?index=NAME]
+ * POST /reopen?index=NAME
+ *
+ * Requests without an index use the first served index. Documents are classified into the classes
+ * named, by default every other served index, each labelled by its name. A classifier is trained
+ * by the first request for its index, method and classes, and kept for the next ones, which may
+ * ask a kNN classifier for other neighbours and voting. The indexes
+ * are kept in an IndexSet, so reopening one picks up a rebuild while the requests already being
+ * answered finish with the previous files
+ */
+pub struct SearchServer {
+    names: Vec<String>,
+    indexes: IndexSet,
+    scorers: ScorerRegistry,
+    classifiers: Mutex<HashMap<String, TrainedClassifier>>,
+}
+
+impl SearchServer {
+    pub fn new(served: Vec<ServedIndex>) -> SearchServer {
+        let indexes = IndexSet::new();
+        for served in served.iter() {
+            indexes.insert(&served.name, served.index.clone(), served.kgram.clone());
+        }
+        SearchServer {
+            names: served.into_iter().map(|served| served.name).collect(),
+            indexes,
+            scorers: ScorerRegistry::new(),
+            classifiers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /*
+     * Answers HTTP requests with a pool of worker threads until the HTTP server is unblocked once
+     * for every worker
+     *
+     * # Arguments
+     *
+     * *`http` - The listening HTTP server
+     * *`threads` - Number of requests answered at the same time
+     */
+    pub fn serve(&self, http: &tiny_http::Server, threads: usize) {
+        thread::scope(|scope| {
+            for _ in 0..threads.max(1) {
+                scope.spawn(|| {
+                    for request in http.incoming_requests() {
+                        self.respond(request);
+                    }
+                });
+            }
+        });
+    }
+
+    fn respond(&self, request: tiny_http::Request) {
+        let method = request.method().as_str().to_string();
+        let url = request.url().to_string();
+        let response = panic::catch_unwind(AssertUnwindSafe(|| self.handle(&method, &url)))
+            .unwrap_or_else(|_| Response::error(500, "Internal error"));
+        info!("{} {} {}", method, url, response.status);
+
+        let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).expect("Invalid header");
+        let http_response = tiny_http::Response::from_string(response.body.to_string())
+            .with_status_code(response.status)
+            .with_header(content_type);
+        if let Err(error) = request.respond(http_response) {
+            warn!("Failed to answer {}: {}", url, error);
+        }
+    }
+
+    /*
+     * Answers a request
+     *
+     * # Arguments
+     *
+     * *`method` - The HTTP method, POST for /reopen and GET otherwise
+     * *`url` - The path and query string of the request
+     *
+     * # Returns
+     *
+     * The response, with an error status and message if the request cannot be answered
+     */
+    pub fn handle(&self, method: &str, url: &str) -> Response {
+        let (path, parameters) = match parse_url(url) {
+            Ok(parsed) => parsed,
+            Err(error) => return Response::error(400, error),
+        };
+        let result = match (method, path.trim_end_matches('/')) {
+            ("POST", "/reopen") => self.reopen(&parameters),
+            (_, "/reopen") => Err(Response::error(405, "Indexes are reopened by POST requests")),
+            ("GET", "/search") => self.search(&parameters),
+            ("GET", "/suggest") => self.suggest(&parameters),
+            ("GET", "/classify") => self.classify(&parameters),
+            ("GET", "/stats") => self.stats(&parameters),
+            ("GET", route) if route.starts_with("/document/") => self.document(&route["/document/".len()..], &parameters),
+            ("GET", _) => Err(Response::error(404, "Unknown route")),
+            _ => Err(Response::error(405, "Only GET requests are answered")),
+        };
+        match result {
+            Ok(response) => response,
+            Err(response) => response,
+        }
+    }
+
+    /*
+     * The current handles of a served index, looked up in the index set
+     */
+    fn lookup(&self, name: &str) -> Option<ServedIndex> {
+        if !self.names.iter().any(|served| served == name) {
+            return None;
+        }
+        self.indexes.get_with_k_gram_index(name).map(|(index, kgram)| ServedIndex {
+            name: name.to_string(),
+            index,
+            kgram,
+        })
+    }
+
+    fn served_index(&self, parameters: &Parameters) -> Result<ServedIndex, Response> {
+        match parameters.get("index") {
+            Some(name) => self.lookup(name),
+            None => self.names.first().and_then(|name| self.lookup(name)),
+        }
+        .ok_or_else(|| Response::error(404, "Unknown index"))
+    }
+
+    /*
+     * The indexes named by a comma separated 'index' parameter, or every index for '*'
+     */
+    fn served_indexes(&self, parameters: &Parameters) -> Result<Vec<ServedIndex>, Response> {
+        match parameters.get("index").map(|names| names.as_str()) {
+            Some("*") => self
+                .names
+                .iter()
+                .map(|name| self.lookup(name))
+                .collect::<Option<Vec<ServedIndex>>>()
+                .ok_or_else(|| Response::error(404, "Unknown index")),
+            Some(names) if names.contains(',') => names
+                .split(',')
+                .map(|name| self.lookup(name.trim()))
+                .collect::<Option<Vec<ServedIndex>>>()
+                .ok_or_else(|| Response::error(404, "Unknown index")),
+            _ => self.served_index(parameters).map(|served| vec![served]),
+        }
+    }
+
+    fn search(&self, parameters: &Parameters) -> Result<Response, Response> {
+        let served = self.served_indexes(parameters)?;
+        let input = required(parameters, "q")?;
+        let ranked_retrieval = match parameters.get("mode").map(|mode| mode.as_str()).unwrap_or("ranked") {
+            "ranked" => true,
+            "boolean" => false,
+            _ => return Err(Response::error(400, "Unknown mode, use ranked or boolean")),
+        };
+        let scheme = parameters.get("scheme").map(|scheme| scheme.as_str()).unwrap_or("default");
+        let scorer = self.scorers.get(scheme).map_err(|error| Response::error(400, error))?;
+
+        let mut options = SearchOptions::new(ranked_retrieval, scorer);
+        options.limit = parse(parameters, "limit", options.limit)?;
+        options.offset = parse(parameters, "offset", options.offset)?;
+        options.snippet_length = parse(parameters, "snippet", options.snippet_length)?;
+
+        if served.len() > 1 {
+            let targets: Vec<SearchTarget> = served
+                .iter()
+                .map(|served| SearchTarget::new(&served.name, &served.index, &served.kgram))
+                .collect();
+            let results = federated_search::process_query(&options, input, &targets)
+                .map_err(|error| Response::error(400, error))?;
+            return Ok(Response::ok(json!({
+                "index": served.iter().map(|served| served.name.as_str()).collect::<Vec<&str>>(),
+                "query": input,
+                "mode": if ranked_retrieval { "ranked" } else { "boolean" },
+                "scheme": if ranked_retrieval { Some(scheme) } else { None },
+                "total": results.total,
+                "total_is_lower_bound": results.total_is_lower_bound,
+                "offset": results.offset,
+                "limit": results.limit,
+                "elapsed_ms": results.elapsed.as_secs_f64() * 1000.0,
+                "hits": results.hits,
+            })));
+        }
+        let served = &served[0];
+        let results = query_processor::process_query(&options, input, &served.index, &served.kgram)
+            .map_err(|error| Response::error(400, error))?;
+        Ok(Response::ok(json!({
+            "index": served.name,
+            "query": input,
+            "mode": if ranked_retrieval { "ranked" } else { "boolean" },
+            "scheme": if ranked_retrieval { Some(scheme) } else { None },
+            "total": results.total,
+            "total_is_lower_bound": results.total_is_lower_bound,
+            "offset": results.offset,
+            "limit": results.limit,
+            "elapsed_ms": results.elapsed.as_secs_f64() * 1000.0,
+            "hits": results.hits,
+        })))
+    }
+
+    fn suggest(&self, parameters: &Parameters) -> Result<Response, Response> {
+        let served = self.served_index(parameters)?;
+        let prefix = required(parameters, "q")?;
+        let limit = parse(parameters, "limit", 10)?;
+        let suggestions = suggest::suggest(prefix, &served.index, &served.kgram, limit);
+        Ok(Response::ok(json!({
+            "index": served.name,
+            "prefix": prefix,
+            "suggestions": suggestions,
+        })))
+    }
+
+    fn document(&self, doc_id: &str, parameters: &Parameters) -> Result<Response, Response> {
+        let served = self.served_index(parameters)?;
+        let doc_id: u32 = doc_id.parse().map_err(|_| Response::error(400, "Invalid document id"))?;
+        let index = &served.index;
+        let path = index.get_document_path(doc_id).map_err(|error| Response::error(404, error))?;
+        let document = index.get_document(doc_id).map_err(|error| Response::error(500, error))?;
+        Ok(Response::ok(json!({
+            "index": served.name,
+            "doc_id": doc_id,
+            "path": path,
+            "title": document.title,
+            "url": document.url,
+            "body": document.body,
+            "fields": document.fields,
+        })))
+    }
+
+    fn classify(&self, parameters: &Parameters) -> Result<Response, Response> {
+        let served = self.served_index(parameters)?;
+        let file = required(parameters, "doc")?;
+        let method = parameters.get("method").map(|method| method.as_str()).unwrap_or("rocchio");
+        if !["rocchio", "bayes", "bayesian", "knn"].contains(&method) {
+            return Err(Response::error(400, "Unknown method, use rocchio, bayes or knn"));
+        }
+        let k = parse(parameters, "k", DEFAULT_NEIGHBOURS)?;
+        let voting = parse(parameters, "voting", Voting::Majority)?;
+        // The classes are the named indexes, or every other index
+        let classes: Vec<ServedIndex> = match parameters.get("classes") {
+            Some(names) => names
+                .split(',')
+                .map(|name| self.lookup(name.trim()))
+                .collect::<Option<Vec<ServedIndex>>>()
+                .ok_or_else(|| Response::error(404, "Unknown class index"))?,
+            None => self.names.iter().filter(|name| **name != served.name).filter_map(|name| self.lookup(name)).collect(),
+        };
+        if classes.iter().any(|class| class.name == served.name) {
+            return Err(Response::error(400, "Documents of a training index are not classified"));
+        }
+        if classes.len() < 2 {
+            return Err(Response::error(404, "Classification needs at least two other indexes as classes"));
+        }
+        let doc_id = served.index.get_document_id(file).ok_or_else(|| Response::error(404, "Document not in the index"))?;
+        let method = if method == "bayesian" { "bayes" } else { method };
+        let key = format!(
+            "{}/{}/{}",
+            served.name,
+            method,
+            classes.iter().map(|class| class.name.as_str()).collect::<Vec<&str>>().join(",")
+        );
+
+        // The lock is not held while training, so other requests are not kept waiting
+        let cached = self.classifiers.lock().unwrap_or_else(PoisonError::into_inner).get(&key).cloned();
+        let classifier = match cached {
+            Some(classifier) => classifier,
+            None => {
+                let training = TrainingSet::new(classes.iter().map(|class| TrainingClass::new(&class.name, &class.index)).collect());
+                let model = ClassifierModel::fit(method, &training).map_err(|error| Response::error(400, error))?;
+                let classifier = TrainedClassifier::new(model, &served.index);
+                let mut classifiers = self.classifiers.lock().unwrap_or_else(PoisonError::into_inner);
+                classifiers.entry(key).or_insert(classifier).clone()
+            },
+        };
+        let classification = classifier.classify(doc_id, k, voting);
+        let mut scores = Map::new();
+        for score in classification.scores.iter() {
+            scores.insert(score.label.clone(), json!(score.score));
+        }
+        Ok(Response::ok(json!({
+            "index": served.name,
+            "file": file,
+            "doc_id": doc_id,
+            "method": method,
+            "label": classification.label,
+            "scores": scores,
+        })))
+    }
+
+    fn stats(&self, parameters: &Parameters) -> Result<Response, Response> {
+        if parameters.contains_key("index") {
+            let served = self.served_index(parameters)?;
+            return Ok(Response::ok(index_statistics(&served)));
+        }
+        let statistics: Vec<Value> = self.names.iter().filter_map(|name| self.lookup(name)).map(|served| index_statistics(&served)).collect();
+        Ok(Response::ok(json!({ "indexes": statistics })))
+    }
+
+    /*
+     * Number of trained classifiers kept for the next requests
+     */
+    pub fn get_classifier_count(&self) -> usize {
+        self.classifiers.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    /*
+     * Opens a served index again after it was rebuilt, and drops the classifiers trained with the
+     * previous files
+     */
+    fn reopen(&self, parameters: &Parameters) -> Result<Response, Response> {
+        let name = required(parameters, "index")?;
+        if self.lookup(name).is_none() {
+            return Err(Response::error(404, "Unknown index"));
+        }
+        self.indexes.reopen(name).map_err(|error| Response::error(500, error))?;
+        self.classifiers.lock().unwrap_or_else(PoisonError::into_inner).clear();
+        let served = self.lookup(name).ok_or_else(|| Response::error(404, "Unknown index"))?;
+        Ok(Response::ok(index_statistics(&served)))
+    }
+}
+
+fn index_statistics(served: &ServedIndex) -> Value {
+    let index = &served.index;
+    let documents = index.get_num_documents().unwrap_or(0);
+    let average_document_length = if documents > 0 {
+        index.get_document_weights(0).map(|weights| weights.0).unwrap_or(0.0)
+    } else {
+        0.0
+    };
+    json!({
+        "index": served.name,
+        "documents": documents,
+        "terms": index.get_term_count(),
+        "tokens": index.get_total_term_frequency(),
+        "average_document_length": average_document_length,
+    })
+}
+
+fn required<'p>(parameters: &'p Parameters, name: &str) -> Result<&'p str, Response> {
+    match parameters.get(name) {
+        Some(value) if !value.trim().is_empty() => Ok(value),
+        _ => Err(Response::error(400, &format!("Missing parameter {}", name))),
+    }
+}
+
+fn parse<T: FromStr>(parameters: &Parameters, name: &str, default: T) -> Result<T, Response> {
+    match parameters.get(name) {
+        Some(value) => value.parse().map_err(|_| Response::error(400, &format!("Invalid parameter {}", name))),
+        None => Ok(default),
+    }
+}
+
+/*
+ * Splits a request URL into its path and decoded query parameters
+ *
+ * # Arguments
+ *
+ * *`url` - The path and query string, such as "/search?q=alpha%20bravo&limit=5"
+ *
+ * # Returns
+ *
+ * The decoded path and parameters, or an error if an escape sequence is invalid
+ */
+pub fn parse_url(url: &str) -> Result<(String, HashMap<String, String>), &'static str> {
+    let (path, query) = match url.find('?') {
+        Some(question_mark) => (&url[..question_mark], &url[question_mark + 1..]),
+        None => (url, ""),
+    };
+    let mut parameters = HashMap::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (name, value) = match pair.find('=') {
+            Some(equals) => (&pair[..equals], &pair[equals + 1..]),
+            None => (pair, ""),
+        };
+        parameters.insert(percent_decode(name)?, percent_decode(value)?);
+    }
+    Ok((percent_decode(path)?, parameters))
+}
+
+/*
+ * Decodes the %XX escapes of a URL component, reading '+' as a space
+ */
+fn percent_decode(component: &str) -> Result<String, &'static str> {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes.get(i + 1..i + 3).ok_or("Invalid escape in URL")?;
+                let hex = ::std::str::from_utf8(hex).map_err(|_| "Invalid escape in URL")?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| "Invalid escape in URL")?);
+                i += 3;
+            },
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            },
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| "URL is not valid UTF-8")
+}
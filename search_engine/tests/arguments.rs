@@ -0,0 +1,41 @@
+extern crate search_engine;
+
+use search_engine::reader::arguments;
+
+fn strings(arguments: &[&str]) -> Vec<String> {
+    arguments.iter().map(|argument| argument.to_string()).collect()
+}
+
+#[test]
+fn test_parse_command() {
+    let arguments = strings(&["query", "index", "--mode", "boolean", "--limit=5", "--proximity", "alpha", "--", "--bravo"]);
+    let command = arguments::parse_command(&arguments, &["mode", "limit"], &["proximity"]).unwrap();
+    assert_eq!("query", command.name);
+    assert_eq!(strings(&["index", "alpha", "--bravo"]), command.positional);
+    assert_eq!(Some("boolean"), command.option("mode"));
+    assert_eq!(None, command.option("scheme"));
+    assert!(command.flag("proximity"));
+    assert!(!command.flag("feedback"));
+
+    assert_eq!(Ok(5), command.parse_option("limit", 10usize));
+    assert_eq!(Ok(20), command.parse_option("offset", 20usize));
+    assert!(command.parse_option("mode", 0usize).is_err());
+}
+
+#[test]
+fn test_parse_command_errors() {
+    assert_eq!(Err("No command given"), arguments::parse_command(&[], &[], &[]));
+    assert_eq!(
+        Err("Unknown option"),
+        arguments::parse_command(&strings(&["query", "--missing"]), &["mode"], &[])
+    );
+    assert_eq!(
+        Err("Option is missing its value"),
+        arguments::parse_command(&strings(&["query", "--mode"]), &["mode"], &[])
+    );
+    // Flags take no value
+    assert_eq!(
+        Err("Unknown option"),
+        arguments::parse_command(&strings(&["query", "--proximity=yes"]), &[], &["proximity"])
+    );
+}
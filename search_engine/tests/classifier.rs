@@ -0,0 +1,380 @@
+extern crate search_engine;
+extern crate serde_json;
+
+mod common;
+
+use search_engine::classifier::bayesian_classifier::BayesianClassifier;
+use search_engine::classifier::classifier::Classifier;
+use search_engine::classifier::knn_classifier::KnnClassifier;
+use search_engine::classifier::knn_classifier::Voting;
+use search_engine::classifier::model::ClassifierModel;
+use search_engine::classifier::model::MODEL_FORMAT_VERSION;
+use search_engine::classifier::rocchio_classifier::RocchioClassifier;
+use search_engine::classifier::training_set::TrainingSet;
+use search_engine::index::disk_inverted_index::DiskInvertedIndex;
+use std::fs;
+
+#[test]
+fn test_classify_with_scores() {
+    let directory = common::build_class_directories("test_classify", &[
+        ("disputed", &["alpha bravo charlie alpha", "golf hotel india"]),
+        ("hamilton", &["alpha bravo charlie", "alpha charlie kilo"]),
+        ("jay", &["delta echo foxtrot", "delta echo lima"]),
+        ("madison", &["golf hotel india", "golf india mike"]),
+    ]);
+    let disputed_index = DiskInvertedIndex::new(directory.join("disputed"));
+    let training = TrainingSet::from_subdirectories(&directory, Some(&directory.join("disputed"))).unwrap();
+    assert_eq!(vec!["hamilton", "jay", "madison"], training.get_labels());
+
+    let rocchio = RocchioClassifier::new(&disputed_index, &training);
+    let classification = rocchio.classify(0);
+    assert_eq!("hamilton", classification.label);
+    assert_eq!(
+        vec!["hamilton", "jay", "madison"],
+        classification.scores.iter().map(|score| score.label.as_str()).collect::<Vec<&str>>()
+    );
+    // Rocchio scores are distances to the class centroids, so the chosen class is the closest
+    assert!(classification.scores[0].score < classification.scores[1].score);
+    assert!(classification.scores[0].score < classification.scores[2].score);
+    assert_eq!("madison", rocchio.classify(1).label);
+
+    // Bayesian scores are log probabilities, so the chosen class is the most likely
+    let bayesian = BayesianClassifier::new(&disputed_index, &training);
+    let classification = bayesian.classify(1);
+    let best = classification
+        .scores
+        .iter()
+        .fold(&classification.scores[0], |best, score| if score.score > best.score { score } else { best });
+    assert_eq!(best.label, classification.label);
+    assert_eq!("madison", classification.label);
+}
+
+#[test]
+fn test_rocchio_centroids() {
+    let directory = common::build_class_directories("test_rocchio_centroids", &[
+        ("disputed", &["alpha bravo"]),
+        ("first", &["alpha bravo", "alpha charlie"]),
+        ("second", &["delta echo", "delta echo"]),
+    ]);
+    let disputed_index = DiskInvertedIndex::new(directory.join("disputed"));
+    let training = TrainingSet::from_subdirectories(&directory, Some(&directory.join("disputed"))).unwrap();
+    let rocchio = RocchioClassifier::new(&disputed_index, &training);
+    let centroids = rocchio.get_centroids();
+    assert_eq!("first", centroids[0].0);
+
+    // Both documents have two terms of frequency one, so each component is 1/sqrt(2) before averaging
+    let component = |term: &str| centroids[0].1.iter().find(|component| component.term == term).unwrap().score;
+    let expected = 1.0 / 2f64.sqrt();
+    assert!((component("alpha") - expected).abs() < 1e-9);
+    assert!((component("bravo") - expected / 2.0).abs() < 1e-9);
+    assert!((component("charli") - expected / 2.0).abs() < 1e-9);
+    assert_eq!(0.0, component("delta"));
+}
+
+#[test]
+fn test_classify_many_classes() {
+    let tickets = [
+        "invoice charged twice card refund billing",
+        "password reset login locked account",
+        "parcel delayed courier tracking shipping",
+        "server down outage error timeout",
+        "cancel subscription plan downgrade renewal",
+        "billing invoice card payment charged",
+        "login password account two factor",
+        "shipping courier parcel address tracking",
+        "outage timeout server unavailable error",
+        "subscription renewal cancel plan annual",
+    ];
+    let tickets: Vec<String> = tickets.iter().map(|ticket| ticket.to_string()).collect();
+    let (training_directory, _, _) = common::build_generated_index("test_classify_many_training", &tickets);
+    let labels = training_directory.join("labels.txt");
+    fs::write(
+        &labels,
+        "# file label\n\
+         doc00000.txt billing\ndoc00001.txt login\ndoc00002.txt shipping\ndoc00003.txt outage\ndoc00004.txt subscription\n\n\
+         doc00005.txt billing\ndoc00006.txt login\ndoc00007.txt shipping\ndoc00008.txt outage\ndoc00009.txt subscription\n",
+    )
+    .unwrap();
+    let training_index = DiskInvertedIndex::new(&training_directory);
+    let training = TrainingSet::from_label_file(&training_index, &labels).unwrap();
+    assert_eq!(vec!["billing", "login", "outage", "shipping", "subscription"], training.get_labels());
+    assert_eq!(10, training.get_num_documents());
+    let billing = &training.get_classes()[0];
+    assert!(!billing.is_whole_index());
+    assert_eq!(vec![0, 5], billing.get_doc_ids());
+    assert_eq!(2, billing.get_document_frequency("invoic"));
+    assert_eq!(0, billing.get_document_frequency("password"));
+    assert_eq!(2, billing.get_term_frequency("card"));
+
+    let new_tickets: Vec<String> = [
+        "charged twice on my card need refund",
+        "locked out cannot login password",
+        "tracking says parcel delayed",
+        "error timeout server down again",
+        "please cancel my subscription renewal",
+    ]
+    .iter()
+    .map(|ticket| ticket.to_string())
+    .collect();
+    let (directory, _, _) = common::build_generated_index("test_classify_many_tickets", &new_tickets);
+    let documents_index = DiskInvertedIndex::new(&directory);
+    let expected = ["billing", "login", "shipping", "outage", "subscription"];
+
+    let rocchio = RocchioClassifier::new(&documents_index, &training);
+    let bayesian = BayesianClassifier::new(&documents_index, &training);
+    for (doc_id, label) in expected.iter().enumerate() {
+        let classification = rocchio.classify(doc_id as u32);
+        assert_eq!(5, classification.scores.len());
+        assert_eq!(*label, classification.label);
+        assert_eq!(*label, bayesian.classify(doc_id as u32).label);
+    }
+
+    // A labelled document of the classified index is not its own nearest neighbour
+    let knn = KnnClassifier::new(&training_index, &training, 1, Voting::Weighted);
+    let path = training_directory.join("knn.json");
+    ClassifierModel::fit("knn", &training).unwrap().save(&path).unwrap();
+    let loaded = ClassifierModel::load(&path).unwrap().classifier(&training_index);
+    for doc_id in 0..10 {
+        let classification = knn.classify(doc_id);
+        assert_eq!(expected[doc_id as usize % 5], classification.label);
+        assert!(classification.scores.iter().all(|score| score.score < 1.0 - 1e-9));
+        assert_eq!(classification.label, loaded.classify(doc_id).label);
+    }
+    // Documents of another index sharing its doc ids are still compared with every training document
+    let other = KnnClassifier::new(&documents_index, &training, 1, Voting::Weighted);
+    for (doc_id, label) in expected.iter().enumerate() {
+        assert_eq!(*label, other.classify(doc_id as u32).label);
+    }
+}
+
+#[test]
+fn test_training_set_errors() {
+    let directory = common::build_class_directories("test_training_set_errors", &[("only", &["alpha bravo"])]);
+    fs::create_dir_all(directory.join("unindexed")).unwrap();
+    // A single indexed subdirectory is not enough, and unindexed ones are skipped
+    assert!(TrainingSet::from_subdirectories(&directory, None).is_err());
+    assert!(TrainingSet::from_subdirectories(&directory.join("missing"), None).is_err());
+
+    let index = DiskInvertedIndex::new(directory.join("only"));
+    let labels = directory.join("labels.txt");
+    fs::write(&labels, "doc00000.txt billing\n").unwrap();
+    assert!(TrainingSet::from_label_file(&index, &labels).is_err());
+    fs::write(&labels, "doc00000.txt billing\nmissing.txt login\n").unwrap();
+    assert!(TrainingSet::from_label_file(&index, &labels).is_err());
+    fs::write(&labels, "doc00000.txt\n").unwrap();
+    assert!(TrainingSet::from_label_file(&index, &labels).is_err());
+    assert!(TrainingSet::from_label_file(&index, &directory.join("missing.txt")).is_err());
+}
+
+/*
+ * The words of the training classes, making up the dimensions of the dense vectors. The fixtures
+ * only use words that are their own stem or share a stem with no other word
+ */
+fn dense_vocab<'w>(classes: &[(&str, &[&'w str])]) -> Vec<&'w str> {
+    let mut vocab: Vec<&str> = classes.iter().flat_map(|&(_, documents)| documents.iter().flat_map(|document| document.split_whitespace())).collect();
+    vocab.sort();
+    vocab.dedup();
+    vocab
+}
+
+/*
+ * The (1 + ln(tf)) vector of a document normalized over all its words, with one component per word
+ * of vocab
+ */
+fn dense_vector(document: &str, vocab: &[&str]) -> Vec<f64> {
+    let words: Vec<&str> = document.split_whitespace().collect();
+    let weight = |word: &str| 1.0 + (words.iter().filter(|&&other| other == word).count() as f64).ln();
+    let mut distinct = words.clone();
+    distinct.sort();
+    distinct.dedup();
+    let length = distinct.iter().map(|&word| weight(word).powi(2)).sum::<f64>().sqrt();
+    vocab.iter().map(|&term| if words.contains(&term) { weight(term) / length } else { 0.0 }).collect()
+}
+
+/*
+ * Euclidean distance from a document to the centroid of every class, over every dimension
+ */
+fn dense_distances(classes: &[(&str, &[&str])], document: &str) -> Vec<f64> {
+    let vocab = dense_vocab(classes);
+    let vector = dense_vector(document, &vocab);
+    classes
+        .iter()
+        .map(|&(_, documents)| {
+            let vectors: Vec<Vec<f64>> = documents.iter().map(|document| dense_vector(document, &vocab)).collect();
+            (0..vocab.len())
+                .map(|term_id| {
+                    let centroid = vectors.iter().map(|vector| vector[term_id]).sum::<f64>() / vectors.len() as f64;
+                    (vector[term_id] - centroid).powi(2)
+                })
+                .sum::<f64>()
+                .sqrt()
+        })
+        .collect()
+}
+
+/*
+ * log2 P(c) + the sum of log2 P(t|c) over the distinct words of a document, with add-one smoothing
+ * over every word of the vocabulary
+ */
+fn dense_log_probabilities(classes: &[(&str, &[&str])], document: &str) -> Vec<f64> {
+    let vocab = dense_vocab(classes);
+    let total_documents: usize = classes.iter().map(|&(_, documents)| documents.len()).sum();
+    let mut words: Vec<&str> = document.split_whitespace().filter(|word| vocab.contains(word)).collect();
+    words.sort();
+    words.dedup();
+    classes
+        .iter()
+        .map(|&(_, documents)| {
+            let term_frequency = |term: &str| documents.iter().flat_map(|document| document.split_whitespace()).filter(|&word| word == term).count() as f64;
+            let denominator = vocab.iter().map(|&term| term_frequency(term)).sum::<f64>() + vocab.len() as f64;
+            let prior = (documents.len() as f64 / total_documents as f64).log2();
+            prior + words.iter().map(|&word| ((term_frequency(word) + 1.0) / denominator).log2()).sum::<f64>()
+        })
+        .collect()
+}
+
+#[test]
+fn test_classifier_model() {
+    let classes: [(&str, &[&str]); 3] = [
+        ("hamilton", &["alpha bravo charlie", "alpha charlie kilo"]),
+        ("jay", &["delta echo foxtrot", "delta echo lima"]),
+        ("madison", &["golf hotel india", "golf india mike"]),
+    ];
+    // zulu is outside the vocabulary of the classes, but still counts in the length of its document
+    let disputed = ["alpha bravo charlie alpha", "golf hotel india", "delta echo alpha zulu"];
+    let labels = ["hamilton", "madison", "jay"];
+    let mut fixture: Vec<(&str, &[&str])> = vec![("disputed", &disputed)];
+    fixture.extend(classes.iter().cloned());
+    let directory = common::build_class_directories("test_classifier_model", &fixture);
+    let disputed_index = DiskInvertedIndex::new(directory.join("disputed"));
+    let training = TrainingSet::from_subdirectories(&directory, Some(&directory.join("disputed"))).unwrap();
+
+    for method in ["rocchio", "bayes"].iter() {
+        let model = ClassifierModel::fit(method, &training).unwrap();
+        assert_eq!(*method, model.get_method());
+        assert_eq!(vec!["hamilton", "jay", "madison"], model.get_labels());
+        if let ClassifierModel::Bayes(ref model) = model {
+            // Every word is discriminating, so the dense sums run over the whole vocabulary
+            assert_eq!(dense_vocab(&classes).len(), model.get_features().len());
+        }
+
+        let path = directory.join(format!("{}.json", method));
+        model.save(&path).unwrap();
+        let loaded = ClassifierModel::load(&path).unwrap();
+        assert_eq!(*method, loaded.get_method());
+
+        // The trained and the saved model score like the dense computation: Rocchio sums
+        // |c|² + d² - 2dc over the terms of the document only, and Bayes sums precomputed log
+        // probabilities of the terms of the document only
+        for classifier in [model.classifier(&disputed_index), loaded.classifier(&disputed_index)].iter() {
+            for (doc_id, (document, label)) in disputed.iter().zip(labels.iter()).enumerate() {
+                let classification = classifier.classify(doc_id as u32);
+                assert_eq!(*label, classification.label);
+                let expected = match *method {
+                    "rocchio" => dense_distances(&classes, document),
+                    _ => dense_log_probabilities(&classes, document),
+                };
+                assert_eq!(expected.len(), classification.scores.len());
+                for (((class, _), expected), score) in classes.iter().zip(expected.iter()).zip(classification.scores.iter()) {
+                    assert_eq!(*class, score.label);
+                    assert!((expected - score.score).abs() < 1e-9, "{} {}: expected {}, found {}", method, document, expected, score.score);
+                }
+            }
+        }
+    }
+
+    let model = BayesianClassifier::fit(&training, 3);
+    assert_eq!(3, model.get_features().len());
+    let features: Vec<&str> = model.get_features().iter().map(|feature| feature.term.as_str()).collect();
+    let selected = BayesianClassifier::build_discriminating_vocab_set(&training, 3);
+    assert_eq!(features, selected.iter().map(|feature| feature.term.as_str()).collect::<Vec<&str>>());
+
+    assert!(ClassifierModel::fit("missing", &training).is_err());
+    assert!(ClassifierModel::load(&directory.join("missing.json")).is_err());
+    fs::write(directory.join("invalid.json"), "{\"method\": \"missing\", \"version\": 1}").unwrap();
+    assert!(ClassifierModel::load(&directory.join("invalid.json")).is_err());
+}
+
+#[test]
+fn test_classifier_model_version() {
+    let directory = common::build_class_directories("test_classifier_model_version", &[
+        ("hamilton", &["alpha bravo charlie"]),
+        ("jay", &["delta echo foxtrot"]),
+    ]);
+    let training = TrainingSet::from_subdirectories(&directory, None).unwrap();
+    let path = directory.join("rocchio.json");
+    ClassifierModel::fit("rocchio", &training).unwrap().save(&path).unwrap();
+
+    let mut saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(MODEL_FORMAT_VERSION, saved["version"].as_u64().unwrap());
+
+    // Models saved before the format was versioned, or in a newer format, are rejected
+    saved["version"] = serde_json::json!(MODEL_FORMAT_VERSION + 1);
+    fs::write(&path, saved.to_string()).unwrap();
+    assert!(ClassifierModel::load(&path).is_err());
+    saved.as_object_mut().unwrap().remove("version");
+    fs::write(&path, saved.to_string()).unwrap();
+    assert!(ClassifierModel::load(&path).is_err());
+
+    saved["version"] = serde_json::json!(MODEL_FORMAT_VERSION);
+    fs::write(&path, saved.to_string()).unwrap();
+    assert_eq!(vec!["hamilton", "jay"], ClassifierModel::load(&path).unwrap().get_labels());
+}
+
+#[test]
+fn test_knn_classifier() {
+    let directory = common::build_class_directories("test_knn_classifier", &[
+        ("disputed", &["alpha bravo charlie delta", "kilo lima mike"]),
+        ("first", &["alpha echo foxtrot golf", "bravo hotel india juliet"]),
+        ("second", &["alpha bravo charlie delta"]),
+        ("third", &["kilo lima mike november", "oscar papa quebec romeo"]),
+    ]);
+    let disputed_index = DiskInvertedIndex::new(directory.join("disputed"));
+    let training = TrainingSet::from_subdirectories(&directory, Some(&directory.join("disputed"))).unwrap();
+
+    // Two distant neighbours outvote one identical document, unless votes are weighted by similarity
+    let majority = KnnClassifier::new(&disputed_index, &training, 3, Voting::Majority);
+    let classification = majority.classify(0);
+    assert_eq!("first", classification.label);
+    assert_eq!(vec![2.0, 1.0, 0.0], classification.scores.iter().map(|score| score.score).collect::<Vec<f64>>());
+    let weighted = KnnClassifier::new(&disputed_index, &training, 3, Voting::Weighted);
+    let classification = weighted.classify(0);
+    assert_eq!("second", classification.label);
+    assert!((classification.scores[1].score - 1.0).abs() < 1e-9);
+    assert!(classification.scores[0].score < 1.0);
+    assert_eq!("second", KnnClassifier::new(&disputed_index, &training, 1, Voting::Majority).classify(0).label);
+    // Training documents sharing no term with the document do not vote
+    let classification = majority.classify(1);
+    assert_eq!("third", classification.label);
+    assert_eq!(vec![0.0, 0.0, 1.0], classification.scores.iter().map(|score| score.score).collect::<Vec<f64>>());
+
+    // A classifier classifies with any other neighbours and voting as if it was trained with them
+    for &(k, voting) in [(1, Voting::Majority), (2, Voting::Weighted), (3, Voting::Weighted)].iter() {
+        let trained = KnnClassifier::new(&disputed_index, &training, k, voting);
+        for doc_id in 0..2 {
+            assert_eq!(trained.classify(doc_id), majority.classify_with(doc_id, k, voting));
+        }
+    }
+
+    // Ties go to the class of the nearest neighbour
+    let tied = KnnClassifier::new(&disputed_index, &training, 2, Voting::Majority);
+    let classification = tied.classify(0);
+    assert_eq!(classification.scores[0].score, classification.scores[1].score);
+    assert_eq!("second", classification.label);
+
+    let mut model = ClassifierModel::fit("knn", &training).unwrap();
+    assert_eq!("knn", model.get_method());
+    assert_eq!(vec!["first", "second", "third"], model.get_labels());
+    if let ClassifierModel::Knn(ref mut model) = model {
+        assert_eq!(5, model.get_k());
+        model.set_neighbours(3, Voting::Weighted);
+    }
+    let path = directory.join("knn.json");
+    model.save(&path).unwrap();
+    let loaded = ClassifierModel::load(&path).unwrap().classifier(&disputed_index);
+    assert_eq!(weighted.classify(0), loaded.classify(0));
+    assert_eq!(training.get_vocab(), loaded.get_all_vocab());
+
+    assert_eq!(Ok(Voting::Weighted), "weighted".parse::<Voting>());
+    assert!("closest".parse::<Voting>().is_err());
+}
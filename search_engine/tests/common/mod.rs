@@ -0,0 +1,127 @@
+#![allow(dead_code)]
+
+use search_engine::index::k_gram_index::KGramIndex;
+use search_engine::index::positional_inverted_index::PositionalInvertedIndex;
+use search_engine::parser::document_parser;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/*
+ * Copies the sample documents in assets/documents into a fresh temporary directory so that
+ * tests running in parallel never share index files
+ *
+ * # Arguments
+ *
+ * *`name` - Unique name of the test directory
+ *
+ * # Returns
+ *
+ * The path of the temporary directory
+ */
+pub fn copy_documents(name: &str) -> PathBuf {
+    let source = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets").join("documents");
+    let directory = env::temp_dir().join(format!("search_engine_{}_{}", name, std::process::id()));
+    if directory.exists() {
+        fs::remove_dir_all(&directory).expect("Failed to clear test directory");
+    }
+    fs::create_dir_all(&directory).expect("Failed to create test directory");
+    for entry in fs::read_dir(source).expect("Failed to read assets/documents") {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|extension| extension == "txt") {
+            fs::copy(&path, directory.join(path.file_name().unwrap())).unwrap();
+        }
+    }
+    directory
+}
+
+/*
+ * Builds the on-disk index for the sample documents in a fresh temporary directory
+ *
+ * # Returns
+ *
+ * The index directory, the K Gram Index and the document id to file map
+ */
+pub fn build_documents_index(name: &str) -> (PathBuf, KGramIndex, HashMap<u32, String>) {
+    let directory = copy_documents(name);
+    let mut index = PositionalInvertedIndex::new();
+    let mut k_gram_index = KGramIndex::new();
+    let id_file = document_parser::build_index(
+        directory.to_str().unwrap().to_string(),
+        &mut index,
+        &mut k_gram_index,
+    );
+    (directory, k_gram_index, id_file)
+}
+
+/*
+ * Builds the on-disk index for generated documents in a fresh temporary directory
+ *
+ * # Arguments
+ *
+ * *`name` - Unique name of the test directory
+ * *`documents` - Body of every document, in document id order
+ *
+ * # Returns
+ *
+ * The index directory, the K Gram Index and the document id to file map
+ */
+pub fn build_generated_index(name: &str, documents: &[String]) -> (PathBuf, KGramIndex, HashMap<u32, String>) {
+    let directory = env::temp_dir().join(format!("search_engine_{}_{}", name, std::process::id()));
+    build_index_at(directory, documents)
+}
+
+/*
+ * Builds the on-disk index for generated documents in a directory, replacing its contents
+ *
+ * # Arguments
+ *
+ * *`directory` - The directory the documents and index are written to
+ * *`documents` - Body of every document, in document id order
+ *
+ * # Returns
+ *
+ * The index directory, the K Gram Index and the document id to file map
+ */
+pub fn build_index_at(directory: PathBuf, documents: &[String]) -> (PathBuf, KGramIndex, HashMap<u32, String>) {
+    if directory.exists() {
+        fs::remove_dir_all(&directory).expect("Failed to clear test directory");
+    }
+    fs::create_dir_all(&directory).expect("Failed to create test directory");
+    for (i, body) in documents.iter().enumerate() {
+        fs::write(directory.join(format!("doc{:05}.txt", i)), body).unwrap();
+    }
+    let mut index = PositionalInvertedIndex::new();
+    let mut k_gram_index = KGramIndex::new();
+    let id_file = document_parser::build_index(
+        directory.to_str().unwrap().to_string(),
+        &mut index,
+        &mut k_gram_index,
+    );
+    (directory, k_gram_index, id_file)
+}
+
+/*
+ * Builds one index per class in the subdirectories of a fresh temporary directory
+ *
+ * # Arguments
+ *
+ * *`name` - Unique name of the test directory
+ * *`classes` - Name of every subdirectory and the bodies of its documents
+ *
+ * # Returns
+ *
+ * The directory holding the subdirectories
+ */
+pub fn build_class_directories(name: &str, classes: &[(&str, &[&str])]) -> PathBuf {
+    let directory = env::temp_dir().join(format!("search_engine_{}_{}", name, std::process::id()));
+    if directory.exists() {
+        fs::remove_dir_all(&directory).expect("Failed to clear test directory");
+    }
+    for &(class, documents) in classes {
+        let documents: Vec<String> = documents.iter().map(|document| document.to_string()).collect();
+        build_index_at(directory.join(class), &documents);
+    }
+    directory
+}
@@ -0,0 +1,238 @@
+extern crate search_engine;
+
+mod common;
+
+use search_engine::index::disk_inverted_index::DiskInvertedIndex;
+use search_engine::index::disk_inverted_index::IndexReader;
+use search_engine::index::k_gram_index::KGramIndex;
+use search_engine::index::positional_inverted_index::PositionalInvertedIndex;
+use search_engine::parser::document_parser;
+use search_engine::processor::query_processor;
+use search_engine::processor::query_processor::SearchOptions;
+use search_engine::processor::scorer::OkapiScorer;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+#[test]
+fn test_collection_frequencies() {
+    let (directory, _, _) = common::build_documents_index("test_collection_frequencies");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    let november = document_parser::stem_terms(document_parser::normalize_token("november".to_string()));
+    assert_eq!(5, index.get_term_frequency(&november[0]));
+    assert_eq!(3, index.get_term_frequency("bravo"));
+    assert_eq!(0, index.get_term_frequency("nonexistent"));
+    assert_eq!(64, index.get_total_term_frequency());
+
+    let vocab = index.get_vocab();
+    assert_eq!(vocab.len() as u32, index.get_term_count());
+    assert!(vocab.contains("bravo"));
+    assert_eq!(2, index.get_document_frequency("bravo"));
+    assert_eq!(0, index.get_document_frequency("nonexistent"));
+}
+
+#[test]
+fn test_document_statistics() {
+    let (directory, _, _) = common::build_documents_index("test_document_statistics");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    assert_eq!(Ok(5), index.get_num_documents());
+
+    // doc1.txt repeats november and hotel, leaving 10 distinct terms out of 13 tokens
+    assert_eq!(10, index.get_unique_term_count(0));
+    assert_eq!(0, index.get_unique_term_count(5));
+
+    // Document lengths count tokens, so they average to the total term frequency over the documents
+    let weights = index.get_document_weights(0).unwrap();
+    assert_eq!(13, weights.2);
+    assert!((weights.0 - 64.0 / 5.0).abs() < 1e-9);
+
+    let terms = index.get_terms_for_document(0);
+    assert_eq!(10, terms.len());
+    assert!(terms.contains("hotel"));
+    assert!(!terms.contains("zulu"));
+}
+
+#[test]
+fn test_document_vector() {
+    let (directory, _, _) = common::build_documents_index("test_document_vector");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    // doc2.txt: bravo papa lima sierra bravo november yankee foxtrot charlie tango kilo sierra alpha mike zulu
+    let document_vector = index.get_document_vector(1).unwrap();
+    assert_eq!(13, document_vector.len());
+    assert!(document_vector.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+    let frequencies: Vec<(String, u32)> = document_vector
+        .iter()
+        .map(|&(term_id, term_frequency)| (index.get_term(term_id).unwrap(), term_frequency))
+        .collect();
+    assert_eq!((String::from("alpha"), 1), frequencies[0]);
+    assert!(frequencies.contains(&(String::from("bravo"), 2)));
+    assert!(frequencies.contains(&(String::from("sierra"), 2)));
+    assert_eq!((String::from("zulu"), 1), frequencies[12]);
+
+    assert!(index.get_document_vector(5).is_err());
+    assert!(index.get_term(index.get_term_count()).is_err());
+}
+
+#[test]
+fn test_postings_offsets() {
+    let documents = vec![
+        String::from("Café state-of-the-art, (Don't) stop.\nthe state"),
+        String::from("art state"),
+    ];
+    let (directory, _, _) = common::build_generated_index("test_postings_offsets", &documents);
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    // Hyphenated parts take a position each and keep their own characters
+    let state = index.get_postings_with_offsets("state").unwrap();
+    assert_eq!(2, state.len());
+    assert_eq!((0, 2, vec![1, 8]), (state[0].0 .0, state[0].0 .1, state[0].0 .6.clone()));
+    assert_eq!(Some(vec![(5, 10), (41, 46)]), state[0].1);
+    assert_eq!(Some(vec![(4, 9)]), state[1].1);
+
+    let the = index.get_postings_with_offsets("the").unwrap();
+    assert_eq!(Some(vec![(14, 17), (37, 40)]), the[0].1);
+    let joined = index.get_postings_with_offsets("stateoftheart").unwrap();
+    assert_eq!(Some(vec![(5, 21)]), joined[0].1);
+    assert_eq!(vec![1], joined[0].0 .6);
+    let dont = index.get_postings_with_offsets("dont").unwrap();
+    assert_eq!(Some(vec![(24, 29)]), dont[0].1);
+    assert!(index.get_postings_with_offsets("missing").is_err());
+
+    // Phrases match the whole hyphenated word, its parts, and the words around it
+    for phrase in ["state-of-the-art", "café state-of-the-art", "of the art", "the art don't", "café state", "stateoftheart"].iter() {
+        assert_eq!(vec![0], query_processor::phrase_query(phrase.to_string(), &index).unwrap(), "{}", phrase);
+    }
+    assert!(query_processor::phrase_query(String::from("state art"), &index).unwrap().is_empty());
+
+    // Offsets do not change what the other readers see
+    let postings = index.get_postings("state").unwrap();
+    assert_eq!(vec![1, 8], postings[0].6);
+    assert_eq!(2, postings[0].1);
+    let postings = index.get_postings_no_positions("state").unwrap();
+    assert_eq!(vec![(0, 2), (1, 1)], postings.iter().map(|posting| (posting.0, posting.1)).collect::<Vec<(u32, u32)>>());
+}
+
+#[test]
+fn test_document_store() {
+    // Enough text to fill several compressed blocks
+    let documents: Vec<String> = (0..300)
+        .map(|i| format!("document {} {}", i, "lorem ipsum dolor sit amet ".repeat(i % 13 + 1)))
+        .collect();
+    let (directory, _, docid_file) = common::build_generated_index("test_document_store", &documents);
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    for (doc_id, body) in documents.iter().enumerate() {
+        let document = index.get_document(doc_id as u32).unwrap();
+        let path = &docid_file[&(doc_id as u32)];
+        assert_eq!(*body, document.body);
+        assert_eq!(*path, document.title);
+        assert_eq!("", document.url);
+        assert_eq!(Some(path), document.fields.get("path"));
+    }
+    assert!(index.get_document(300).is_err());
+}
+
+#[test]
+fn test_document_store_json_fields() {
+    let directory = env::temp_dir().join(format!("search_engine_test_document_store_json_fields_{}", std::process::id()));
+    if directory.exists() {
+        fs::remove_dir_all(&directory).unwrap();
+    }
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(
+        directory.join("article.json"),
+        r#"{"title": "Parks", "body": "Yosemite  valley", "url": "https://example.com/parks", "author": "Muir", "year": 1890}"#,
+    ).unwrap();
+    let mut positional_index = PositionalInvertedIndex::new();
+    let mut k_gram_index = KGramIndex::new();
+    document_parser::build_index(directory.to_str().unwrap().to_string(), &mut positional_index, &mut k_gram_index);
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    let document = index.get_document(0).unwrap();
+    assert_eq!("Parks", document.title);
+    assert_eq!("Yosemite valley ", document.body);
+    assert_eq!("https://example.com/parks", document.url);
+    assert_eq!(Some(&String::from("Muir")), document.fields.get("author"));
+    assert_eq!(Some(&String::from("1890")), document.fields.get("year"));
+    assert!(document.fields.get("title").is_none());
+}
+
+#[test]
+fn test_doc_table() {
+    let (directory, _, docid_file) = common::build_documents_index("test_doc_table");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    assert_eq!(5, docid_file.len());
+    for (&doc_id, path) in docid_file.iter() {
+        let file_name = Path::new(path).file_name().unwrap().to_str().unwrap();
+        assert_eq!(*path, index.get_document_path(doc_id).unwrap());
+        assert_eq!(Some(doc_id), index.get_document_id(path));
+        assert_eq!(Some(doc_id), index.get_document_id(file_name));
+    }
+    assert_eq!(Some(2), index.get_document_id("doc3.txt"));
+    assert_eq!(None, index.get_document_id("doc6.txt"));
+    assert_eq!(None, index.get_document_id("/elsewhere/doc3.txt"));
+    assert!(index.get_document_path(5).is_err());
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_concurrent_reads() {
+    assert_send_sync::<DiskInvertedIndex>();
+
+    let words = ["alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliet"];
+    let documents: Vec<String> = (0..200)
+        .map(|i| (0..20 + i % 13).map(|j| words[(i * 7 + j * j) % words.len()]).collect::<Vec<&str>>().join(" "))
+        .collect();
+    let (directory, k_gram_index, _) = common::build_generated_index("test_concurrent_reads", &documents);
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+    let scorer = OkapiScorer::default();
+    let options = SearchOptions::new(true, &scorer);
+
+    // Everything read by a single thread, compared against what many threads read at once
+    let terms: Vec<String> = words
+        .iter()
+        .map(|word| document_parser::stem_terms(document_parser::normalize_token(word.to_string())).remove(0))
+        .collect();
+    let postings: Vec<_> = terms.iter().map(|term| index.get_postings_with_offsets(term).unwrap()).collect();
+    let weights: Vec<_> = (0..200).map(|doc_id| index.get_document_weights(doc_id).unwrap()).collect();
+    let paths: Vec<_> = (0..200).map(|doc_id| index.get_document_path(doc_id).unwrap()).collect();
+    let hits: Vec<u32> = query_processor::process_query(&options, "alpha golf", &index, &k_gram_index)
+        .unwrap()
+        .hits
+        .iter()
+        .map(|hit| hit.doc_id)
+        .collect();
+
+    thread::scope(|scope| {
+        for thread_number in 0..8 {
+            let (index, k_gram_index, options) = (&index, &k_gram_index, &options);
+            let (terms, postings, weights, paths) = (&terms, &postings, &weights, &paths);
+            let (hits, documents) = (&hits, &documents);
+            scope.spawn(move || {
+                for round in 0..25 {
+                    let term = (thread_number + round) % terms.len();
+                    assert_eq!(postings[term], index.get_postings_with_offsets(&terms[term]).unwrap());
+                    for doc_id in (thread_number * 5 + round..200).step_by(37) {
+                        assert_eq!(weights[doc_id], index.get_document_weights(doc_id as u32).unwrap());
+                        assert_eq!(paths[doc_id], index.get_document_path(doc_id as u32).unwrap());
+                        assert_eq!(documents[doc_id], index.get_document(doc_id as u32).unwrap().body);
+                    }
+                    let found: Vec<u32> = query_processor::process_query(options, "alpha golf", index, k_gram_index)
+                        .unwrap()
+                        .hits
+                        .iter()
+                        .map(|hit| hit.doc_id)
+                        .collect();
+                    assert_eq!(*hits, found);
+                }
+            });
+        }
+    });
+}
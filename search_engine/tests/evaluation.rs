@@ -0,0 +1,133 @@
+extern crate search_engine;
+
+mod common;
+
+use search_engine::index::disk_inverted_index::DiskInvertedIndex;
+use search_engine::processor::evaluation;
+use search_engine::processor::evaluation::Topic;
+use search_engine::processor::scorer::ScorerRegistry;
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn assert_close(expected: f64, actual: f64) {
+    assert!((expected - actual).abs() < 1e-9, "expected {}, found {}", expected, actual);
+}
+
+#[test]
+fn test_parse_topics() {
+    let lines = evaluation::parse_topics("# comment\n401 grand canyon\n\n402\tyosemite  trail \n").unwrap();
+    assert_eq!(
+        vec![
+            Topic { id: String::from("401"), query: String::from("grand canyon") },
+            Topic { id: String::from("402"), query: String::from("yosemite  trail") },
+        ],
+        lines
+    );
+    assert!(evaluation::parse_topics("403\n").is_err());
+
+    let trec = "<top>\n<num> Number: 301\n<title> Topic: international crime\n<desc> Description:\nText\n</top>\n\
+                <top>\n<num> 302 <title> poliomyelitis\n</top>\n";
+    let topics = evaluation::parse_topics(trec).unwrap();
+    assert_eq!(
+        vec![
+            Topic { id: String::from("301"), query: String::from("international crime") },
+            Topic { id: String::from("302"), query: String::from("poliomyelitis") },
+        ],
+        topics
+    );
+}
+
+#[test]
+fn test_parse_qrels() {
+    let qrels = evaluation::parse_qrels("401 0 doc1.txt 1\n401 0 doc2.txt 0\n402 0 doc3.txt 2\n402 0 doc4.txt -1\n").unwrap();
+    assert_eq!(Some(&1), qrels["401"].get("doc1.txt"));
+    assert_eq!(Some(&0), qrels["401"].get("doc2.txt"));
+    assert_eq!(Some(&2), qrels["402"].get("doc3.txt"));
+    assert_eq!(Some(&0), qrels["402"].get("doc4.txt"));
+    assert!(evaluation::parse_qrels("401 doc1.txt 1\n").is_err());
+    assert!(evaluation::parse_qrels("401 0 doc1.txt yes\n").is_err());
+}
+
+#[test]
+fn test_evaluate_ranking() {
+    let ranking: Vec<String> = ["a", "b", "c", "d", "e"].iter().map(|name| name.to_string()).collect();
+    let mut judgments: HashMap<String, u32> = HashMap::new();
+    judgments.insert(String::from("a"), 0);
+    judgments.insert(String::from("b"), 2);
+    judgments.insert(String::from("d"), 1);
+    // Relevant but never retrieved
+    judgments.insert(String::from("f"), 1);
+
+    let result = evaluation::evaluate_ranking("q", &ranking, &judgments, 3, Duration::from_millis(4));
+    assert_eq!(3, result.relevant);
+    assert_eq!(2, result.relevant_retrieved);
+    assert_close((1.0 / 2.0 + 2.0 / 4.0) / 3.0, result.average_precision);
+    assert_close(1.0 / 3.0, result.precision_at_k);
+    assert_close(1.0 / 3.0, result.r_precision);
+    assert_close(0.5, result.reciprocal_rank);
+    let dcg = 3.0 / 3f64.log2();
+    let ideal_dcg = 3.0 + 1.0 / 3f64.log2() + 1.0 / 4f64.log2();
+    assert_close(dcg / ideal_dcg, result.ndcg_at_k);
+
+    let empty = evaluation::evaluate_ranking("q", &[], &judgments, 3, Duration::from_millis(0));
+    assert_eq!(0.0, empty.average_precision);
+    assert_eq!(0.0, empty.reciprocal_rank);
+    assert_eq!(0.0, empty.ndcg_at_k);
+}
+
+#[test]
+fn test_compare_schemes() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_compare_schemes");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    let topics = evaluation::parse_topics("1 alpha bravo\n2 kilo\n3 unjudged\n").unwrap();
+    let qrels = evaluation::parse_qrels(
+        "1 0 doc1.txt 1\n1 0 doc2.txt 1\n1 0 doc5.txt 1\n1 0 doc3.txt 0\n2 0 doc4.txt 0\n",
+    ).unwrap();
+    let scorers = ScorerRegistry::new();
+    let evaluations =
+        evaluation::compare_schemes(&scorers, &["default", "okapi"], &topics, &qrels, &index, &k_gram_index, 2).unwrap();
+
+    assert_eq!(vec!["default", "okapi"], evaluations.iter().map(|evaluation| evaluation.scheme.as_str()).collect::<Vec<&str>>());
+    for evaluation in evaluations.iter() {
+        // Only query 1 has a relevant document, and every document matching it is relevant
+        assert_eq!(1, evaluation.queries.len());
+        assert_eq!("1", evaluation.queries[0].query_id);
+        assert_eq!(3, evaluation.queries[0].relevant_retrieved);
+        assert_close(1.0, evaluation.mean_average_precision());
+        assert_close(1.0, evaluation.mean_precision_at_k());
+        assert_close(1.0, evaluation.mean_r_precision());
+        assert_close(1.0, evaluation.mean_ndcg_at_k());
+        assert_close(1.0, evaluation.mean_reciprocal_rank());
+    }
+
+    assert!(evaluations.iter().all(|evaluation| evaluation.get_failed_queries().is_empty()));
+
+    assert!(evaluation::compare_schemes(&scorers, &["missing"], &topics, &qrels, &index, &k_gram_index, 2).is_err());
+}
+
+#[test]
+fn test_evaluate_unparsable_query() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_evaluate_unparsable_query");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    let topics = evaluation::parse_topics("1 \"alpha bravo\n2 alpha bravo\n").unwrap();
+    let qrels = evaluation::parse_qrels("1 0 doc1.txt 1\n2 0 doc1.txt 1\n2 0 doc2.txt 1\n2 0 doc5.txt 1\n").unwrap();
+    let scorers = ScorerRegistry::new();
+    let evaluations = evaluation::compare_schemes(&scorers, &["default"], &topics, &qrels, &index, &k_gram_index, 2).unwrap();
+
+    // The unterminated phrase fails on its own and the next query is still evaluated
+    let evaluation = &evaluations[0];
+    assert_eq!(2, evaluation.queries.len());
+    let failed = evaluation.get_failed_queries();
+    assert_eq!(1, failed.len());
+    assert_eq!("1", failed[0].query_id);
+    assert!(failed[0].error.is_some());
+    assert_eq!(1, failed[0].relevant);
+    assert_eq!(0, failed[0].relevant_retrieved);
+    assert_eq!(0.0, failed[0].average_precision);
+
+    assert_eq!(None, evaluation.queries[1].error);
+    assert_close(1.0, evaluation.queries[1].average_precision);
+    assert_close(0.5, evaluation.mean_average_precision());
+}
@@ -0,0 +1,131 @@
+extern crate search_engine;
+extern crate serde_json;
+
+mod common;
+
+use search_engine::index::disk_inverted_index::DiskInvertedIndex;
+use search_engine::processor::federated_search;
+use search_engine::processor::federated_search::SearchTarget;
+use search_engine::processor::query_processor;
+use search_engine::processor::query_processor::SearchOptions;
+use search_engine::processor::scorer::DefaultScorer;
+use search_engine::processor::scorer::OkapiScorer;
+use search_engine::processor::scorer::Scorer;
+
+/*
+ * Documents whose terms are spread unevenly, so the statistics of either half differ from those of
+ * the whole collection
+ */
+fn generated_documents() -> Vec<String> {
+    let words = ["alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel"];
+    (0..60)
+        .map(|i| {
+            // The second half never uses the first three words
+            let first_word = if i < 30 { 0 } else { 3 };
+            (0..8 + i % 11)
+                .map(|j| words[first_word + (i * 3 + j * j) % (words.len() - first_word)])
+                .collect::<Vec<&str>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+#[test]
+fn test_federated_ranked_matches_single_index() {
+    let documents = generated_documents();
+    let (first_directory, first_kgram, _) = common::build_generated_index("test_federated_first", &documents[..30]);
+    let (second_directory, second_kgram, _) = common::build_generated_index("test_federated_second", &documents[30..]);
+    let (whole_directory, whole_kgram, _) = common::build_generated_index("test_federated_whole", &documents);
+    let first = DiskInvertedIndex::new(&first_directory);
+    let second = DiskInvertedIndex::new(&second_directory);
+    let whole = DiskInvertedIndex::new(&whole_directory);
+    let targets = vec![
+        SearchTarget::new("first", &first, &first_kgram),
+        SearchTarget::new("second", &second, &second_kgram),
+    ];
+
+    let default_scorer = DefaultScorer;
+    let okapi_scorer = OkapiScorer::default();
+    let scorers: [&dyn Scorer; 2] = [&default_scorer, &okapi_scorer];
+    for scorer in scorers.iter() {
+        for input in ["alpha golf", "bravo \"charlie delta\"", "echo NEAR/2 hotel"].iter() {
+            let mut options = SearchOptions::new(true, *scorer);
+            options.limit = 15;
+            options.offset = 3;
+            let federated = federated_search::process_query(&options, input, &targets).unwrap();
+            let single = query_processor::process_query(&options, input, &whole, &whole_kgram).unwrap();
+
+            // The first half keeps its document ids in the whole index, the second is shifted by 30
+            assert_eq!(single.total_is_lower_bound, federated.total_is_lower_bound);
+            if single.total_is_lower_bound {
+                // Pruned totals only count part of the matches, but enough to tell whether more remain
+                assert_eq!(single.has_more(), federated.has_more());
+            } else {
+                assert_eq!(single.total, federated.total);
+            }
+            assert_eq!(single.len(), federated.len());
+            for (merged, expected) in federated.hits.iter().zip(single.hits.iter()) {
+                let doc_id = if merged.index == "first" { merged.hit.doc_id } else { merged.hit.doc_id + 30 };
+                assert_eq!(expected.doc_id, doc_id);
+                assert!((expected.score.unwrap() - merged.hit.score.unwrap()).abs() < 1e-9);
+                assert_eq!(expected.explanation.len(), merged.hit.explanation.len());
+            }
+        }
+    }
+
+    // Scored on its own, the first half disagrees with the merged scores
+    let mut options = SearchOptions::new(true, &okapi_scorer);
+    options.limit = 60;
+    let alone = query_processor::process_query(&options, "alpha golf", &first, &first_kgram).unwrap();
+    let federated = federated_search::process_query(&options, "alpha golf", &targets).unwrap();
+    let merged = federated.hits.iter().find(|merged| merged.index == "first").unwrap();
+    let alone_score = alone.hits.iter().find(|hit| hit.doc_id == merged.hit.doc_id).unwrap().score.unwrap();
+    assert!((alone_score - merged.hit.score.unwrap()).abs() > 1e-6);
+}
+
+#[test]
+fn test_federated_boolean() {
+    let documents = generated_documents();
+    let (first_directory, first_kgram, _) = common::build_generated_index("test_federated_boolean_first", &documents[..30]);
+    let (second_directory, second_kgram, _) = common::build_generated_index("test_federated_boolean_second", &documents[30..]);
+    let first = DiskInvertedIndex::new(&first_directory);
+    let second = DiskInvertedIndex::new(&second_directory);
+    let targets = vec![
+        SearchTarget::new("first", &first, &first_kgram),
+        SearchTarget::new("second", &second, &second_kgram),
+    ];
+
+    let scorer = DefaultScorer;
+    let mut options = SearchOptions::new(false, &scorer);
+    options.limit = usize::MAX;
+    options.snippet_length = 0;
+    let first_matches = query_processor::process_query(&options, "golf -alpha", &first, &first_kgram).unwrap();
+    let second_matches = query_processor::process_query(&options, "golf -alpha", &second, &second_kgram).unwrap();
+    let federated = federated_search::process_query(&options, "golf -alpha", &targets).unwrap();
+
+    // Results are listed index by index, each tagged with its index
+    assert_eq!(first_matches.total + second_matches.total, federated.total);
+    let expected: Vec<(&str, u32)> = first_matches
+        .hits
+        .iter()
+        .map(|hit| ("first", hit.doc_id))
+        .chain(second_matches.hits.iter().map(|hit| ("second", hit.doc_id)))
+        .collect();
+    let merged: Vec<(&str, u32)> = federated.hits.iter().map(|merged| (merged.index.as_str(), merged.hit.doc_id)).collect();
+    assert_eq!(expected, merged);
+    assert!(federated.hits.iter().all(|merged| merged.hit.score.is_none()));
+    let serialized = serde_json::to_value(&federated.hits[0]).unwrap();
+    assert_eq!("first", serialized["index"]);
+    assert!(serialized["doc_id"].is_number());
+
+    // Pages are cut from the merged results
+    options.offset = first_matches.total - 1;
+    options.limit = 2;
+    let page = federated_search::process_query(&options, "golf -alpha", &targets).unwrap();
+    let page: Vec<(&str, u32)> = page.hits.iter().map(|merged| (merged.index.as_str(), merged.hit.doc_id)).collect();
+    assert_eq!(expected[first_matches.total - 1..first_matches.total + 1].to_vec(), page);
+
+    assert!(federated_search::process_query(&options, "(golf", &targets).is_err());
+    assert_eq!(0, federated_search::process_query(&options, "golf", &[]).unwrap().total);
+}
+
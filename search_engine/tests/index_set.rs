@@ -0,0 +1,99 @@
+extern crate search_engine;
+extern crate serde_json;
+
+mod common;
+
+use search_engine::index::disk_inverted_index::DiskInvertedIndex;
+use search_engine::index::disk_inverted_index::IndexReader;
+use search_engine::index::index_set::IndexSet;
+use search_engine::index::k_gram_index::KGramIndex;
+use std::fs;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn test_open_index() {
+    let (directory, _, _) = common::build_documents_index("test_open_index");
+    let index = DiskInvertedIndex::open(&directory).unwrap();
+    assert_eq!(directory.as_path(), index.get_directory());
+    assert_eq!(directory.display().to_string(), index.get_path());
+    assert_eq!(Ok(5), index.get_num_documents());
+
+    assert!(DiskInvertedIndex::open(directory.join("missing")).is_err());
+    assert!(DiskInvertedIndex::open(common::copy_documents("test_open_index_unindexed")).is_err());
+}
+
+#[test]
+fn test_open_outdated_index() {
+    let (directory, _, _) = common::build_documents_index("test_open_outdated_index");
+    let statistics = fs::read(directory.join("statistics.bin")).unwrap();
+
+    // Indexes from before the format was versioned start statistics.bin with their document count
+    fs::write(directory.join("statistics.bin"), &statistics[8..]).unwrap();
+    assert!(DiskInvertedIndex::open(&directory).is_err());
+
+    let mut newer = statistics.clone();
+    newer[7] += 1;
+    fs::write(directory.join("statistics.bin"), &newer).unwrap();
+    assert!(DiskInvertedIndex::open(&directory).is_err());
+
+    fs::write(directory.join("statistics.bin"), &statistics).unwrap();
+    assert_eq!(Ok(5), DiskInvertedIndex::open(&directory).unwrap().get_num_documents());
+}
+
+#[test]
+fn test_index_set() {
+    let (directory, _, _) = common::build_documents_index("test_index_set");
+    let (other, _, _) = common::build_generated_index("test_index_set_other", &["alpha".to_string()]);
+    let indexes = IndexSet::new();
+    assert!(indexes.is_empty());
+
+    let index = indexes.open("documents", &directory).unwrap();
+    indexes.open("other", &other).unwrap();
+    assert_eq!(vec!["documents", "other"], indexes.names());
+    assert_eq!(Ok(5), indexes.get("documents").unwrap().get_num_documents());
+    assert!(indexes.get("missing").is_none());
+    assert!(indexes.open("missing", other.join("missing")).is_err());
+    assert_eq!(2, indexes.len());
+
+    // Opening the same directory again returns the cached index without reading its files
+    fs::remove_file(directory.join("statistics.bin")).unwrap();
+    assert_eq!(Ok(5), indexes.open("documents", &directory).unwrap().get_num_documents());
+    assert!(indexes.reopen("documents").is_err());
+    assert!(indexes.get("documents").is_some());
+
+    // Opening another directory under a name replaces its index
+    indexes.open("documents", &other).unwrap();
+    assert_eq!(Ok(1), indexes.get("documents").unwrap().get_num_documents());
+    assert_eq!(Ok(1), indexes.reopen("other").unwrap().get_num_documents());
+
+    // Handles stay usable after their index is closed, from any thread
+    assert!(indexes.close("documents"));
+    assert!(!indexes.close("documents"));
+    assert!(indexes.reopen("documents").is_err());
+    assert_eq!(vec!["other"], indexes.names());
+    let count = thread::spawn(move || index.get_document_frequency("alpha")).join().unwrap();
+    assert_eq!(3, count);
+}
+
+#[test]
+fn test_index_set_k_gram_index() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_index_set_k_gram_index");
+    let indexes = IndexSet::new();
+
+    // Indexes opened from a directory without kgram.bin have an empty K Gram Index
+    indexes.open("documents", &directory).unwrap();
+    let (index, kgram) = indexes.get_with_k_gram_index("documents").unwrap();
+    assert_eq!(Ok(5), index.get_num_documents());
+    assert!(kgram.get_k_grams().is_empty());
+
+    fs::write(directory.join("kgram.bin"), serde_json::to_string(&k_gram_index).unwrap()).unwrap();
+    indexes.reopen("documents").unwrap();
+    let (_, kgram) = indexes.get_with_k_gram_index("documents").unwrap();
+    assert_eq!(k_gram_index.get_k_grams(), kgram.get_k_grams());
+
+    indexes.insert("inserted", index, Arc::new(KGramIndex::new()));
+    assert_eq!(vec!["documents", "inserted"], indexes.names());
+    assert_eq!(Ok(5), indexes.get("inserted").unwrap().get_num_documents());
+    assert!(indexes.get_with_k_gram_index("missing").is_none());
+}
@@ -1,9 +1,10 @@
 extern crate search_engine;
 
+mod common;
+
 use search_engine::index::positional_inverted_index::PositionalInvertedIndex;
 use search_engine::index::k_gram_index::KGramIndex;
 use search_engine::parser::document_parser;
-use search_engine::paths::search_engine_paths;
 
 #[test]
 fn add_term() {
@@ -54,8 +55,7 @@ fn add_term() {
 
 #[test]
 fn read_documents() {
-    let mut index_path = search_engine_paths::initialize_path();
-    index_path.push("documents");
+    let index_path = common::copy_documents("read_documents");
     let directory = index_path.to_str().expect("Invalid directory");
     let mut positional_inverted_index = PositionalInvertedIndex::new();
     let mut k_gram_index = KGramIndex::new();
@@ -67,9 +67,9 @@ fn read_documents() {
 
     let alpha_postings_list = positional_inverted_index.get_postings("alpha");
 
-    let alpha_test_case_1: Vec<u32> = vec![5]; // Positions for doc id 0
-    let alpha_test_case_2: Vec<u32> = vec![8]; // Positions for doc id 3
-    let alpha_test_case_3: Vec<u32> = vec![12]; // Positions for doc id 4
+    let alpha_test_case_1: Vec<u32> = vec![8]; // Positions for doc id 0
+    let alpha_test_case_2: Vec<u32> = vec![12]; // Positions for doc id 1
+    let alpha_test_case_3: Vec<u32> = vec![5]; // Positions for doc id 4
     {
         for posting in alpha_postings_list.iter() {
             println!("{} - {:?}", posting.get_doc_id(), posting.get_positions());
@@ -78,7 +78,7 @@ fn read_documents() {
                     assert_eq!(position, &alpha_test_case_1[i]);
                 }
             }
-            if posting.get_doc_id() == 3 {
+            if posting.get_doc_id() == 1 {
                 for (i, position) in posting.get_positions().iter().enumerate() {
                     assert_eq!(position, &alpha_test_case_2[i]);
                 }
@@ -90,12 +90,12 @@ fn read_documents() {
             }
         }
     }
-    let results = document_parser::normalize_token("november".to_string());
+    let results = document_parser::stem_terms(document_parser::normalize_token("november".to_string()));
     let november_term = results.get(0).expect("Improper term");
     let november_postings_list = positional_inverted_index.get_postings(november_term);
-    let november_test_case_1: Vec<u32> = vec![0, 8];
-    let november_test_case_2: Vec<u32> = vec![2, 3];
-    let november_test_case_3: Vec<u32> = vec![5];
+    let november_test_case_1: Vec<u32> = vec![2, 3];
+    let november_test_case_2: Vec<u32> = vec![5];
+    let november_test_case_3: Vec<u32> = vec![0, 8];
 
 
     for posting in november_postings_list.iter() {
@@ -105,7 +105,7 @@ fn read_documents() {
                 assert_eq!(position, &november_test_case_1[i]);
             }
         }
-        if posting.get_doc_id() == 3 {
+        if posting.get_doc_id() == 1 {
             for (i, position) in posting.get_positions().iter().enumerate() {
                 assert_eq!(position, &november_test_case_2[i]);
             }
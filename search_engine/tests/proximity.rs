@@ -0,0 +1,26 @@
+extern crate search_engine;
+
+use search_engine::processor::proximity::ProximityBoost;
+
+#[test]
+fn test_proximity_boost() {
+    let boost = ProximityBoost::new();
+    let first: Vec<u32> = vec![0, 20];
+    let adjacent: Vec<u32> = vec![1];
+    let distant: Vec<u32> = vec![4];
+    let outside: Vec<u32> = vec![10];
+
+    let close = boost.score(&[(1.0, &first), (1.0, &adjacent)], 10.0, 10.0);
+    let far = boost.score(&[(1.0, &first), (1.0, &distant)], 10.0, 10.0);
+    assert!(close > far);
+    assert!(far > 0.0);
+
+    // Pairs farther apart than the window and lone terms add nothing
+    assert_eq!(boost.score(&[(1.0, &first), (1.0, &outside)], 10.0, 10.0), 0.0);
+    assert_eq!(boost.score(&[(1.0, &first)], 10.0, 10.0), 0.0);
+
+    // The pair is weighted by its smaller query weight and longer documents are boosted less
+    let weighted = boost.score(&[(0.5, &first), (2.0, &adjacent)], 10.0, 10.0);
+    assert!((weighted - close / 2.0).abs() < 1e-9);
+    assert!(boost.score(&[(1.0, &first), (1.0, &adjacent)], 20.0, 10.0) < close);
+}
@@ -1,6 +1,8 @@
 extern crate search_engine;
 
 use search_engine::parser::query_parser::QueryParser;
+use search_engine::parser::query_parser::QueryNode;
+use search_engine::parser::query_parser::ProximityKind;
 
 #[test]
 fn test_parser() {
@@ -59,3 +61,101 @@ fn test_parser() {
         }
     }*/
 }
+
+#[test]
+fn test_parse_precedence() {
+    let parser = QueryParser::new();
+    let term = |word: &str| QueryNode::Term(String::from(word));
+
+    assert_eq!(
+        parser.parse("a OR b c").unwrap(),
+        QueryNode::Or(vec![term("a"), QueryNode::And(vec![term("b"), term("c")])])
+    );
+    assert_eq!(
+        parser.parse("(a + b) AND NOT c").unwrap(),
+        QueryNode::And(vec![
+            QueryNode::Or(vec![term("a"), term("b")]),
+            QueryNode::Not(Box::new(term("c"))),
+        ])
+    );
+    assert_eq!(
+        parser.parse("-(a (b OR -\"c d\"))").unwrap(),
+        QueryNode::Not(Box::new(QueryNode::And(vec![
+            term("a"),
+            QueryNode::Or(vec![
+                term("b"),
+                QueryNode::Not(Box::new(QueryNode::Phrase(vec![String::from("c"), String::from("d")]))),
+            ]),
+        ])))
+    );
+    assert_eq!(
+        parser.parse("a NEAR/2 b c").unwrap(),
+        QueryNode::And(vec![
+            QueryNode::Proximity {
+                operands: vec![term("a"), term("b")],
+                distance: 2,
+                kind: ProximityKind::Unordered,
+            },
+            term("c"),
+        ])
+    );
+}
+
+#[test]
+fn test_parse_proximity() {
+    let parser = QueryParser::new();
+    let term = |word: &str| QueryNode::Term(String::from(word));
+
+    assert_eq!(
+        parser.parse("\"a b\" ONEAR/3 c").unwrap(),
+        QueryNode::Proximity {
+            operands: vec![QueryNode::Phrase(vec![String::from("a"), String::from("b")]), term("c")],
+            distance: 3,
+            kind: ProximityKind::Ordered,
+        }
+    );
+    assert_eq!(
+        parser.parse("NOT WITHIN/5(a b \"c d\")").unwrap(),
+        QueryNode::Not(Box::new(QueryNode::Proximity {
+            operands: vec![term("a"), term("b"), QueryNode::Phrase(vec![String::from("c"), String::from("d")])],
+            distance: 5,
+            kind: ProximityKind::Window,
+        }))
+    );
+
+    // A window operator following a term is ANDed with it
+    let window = QueryNode::Proximity {
+        operands: vec![term("b"), term("c")],
+        distance: 3,
+        kind: ProximityKind::Window,
+    };
+    assert_eq!(parser.parse("a WITHIN/3(b c)").unwrap(), QueryNode::And(vec![term("a"), window.clone()]));
+    assert_eq!(
+        parser.parse("a NEAR/2 d WITHIN/3(b c)").unwrap(),
+        QueryNode::And(vec![
+            QueryNode::Proximity {
+                operands: vec![term("a"), term("d")],
+                distance: 2,
+                kind: ProximityKind::Unordered,
+            },
+            window,
+        ])
+    );
+
+    assert!(parser.parse("(a OR b) NEAR/2 c").is_err());
+    assert!(parser.parse("a NEAR/2 b NEAR/2 c").is_err());
+    assert!(parser.parse("WITHIN/3(a)").is_err());
+    assert!(parser.parse("WITHIN/3 a b").is_err());
+    assert!(parser.parse("a WITHIN/3 b").is_err());
+}
+
+#[test]
+fn test_parse_errors() {
+    let parser = QueryParser::new();
+    assert!(parser.parse("").is_err());
+    assert!(parser.parse("(a OR b").is_err());
+    assert!(parser.parse("a OR b)").is_err());
+    assert!(parser.parse("a OR").is_err());
+    assert!(parser.parse("\"a b").is_err());
+    assert!(parser.parse("a NEAR/x b").is_err());
+}
@@ -1,20 +1,33 @@
 extern crate search_engine;
 
-use search_engine::index::positional_inverted_index::PositionalInvertedIndex;
-use search_engine::index::k_gram_index::KGramIndex;
-use search_engine::parser::document_parser;
-use search_engine::paths::search_engine_paths;
+mod common;
+
+use search_engine::index::disk_inverted_index::DiskInvertedIndex;
 use search_engine::processor::query_processor;
+use search_engine::processor::query_processor::SearchOptions;
+use search_engine::processor::scorer::DefaultScorer;
+use search_engine::processor::search_results::SearchResults;
+use std::path::Path;
+
+fn file_names(results: &SearchResults) -> Vec<String> {
+    results
+        .hits
+        .iter()
+        .map(|hit| Path::new(&hit.path).file_name().unwrap().to_str().unwrap().to_string())
+        .collect()
+}
+
+fn assert_results(results: &SearchResults, expected: &[&str]) {
+    let names = file_names(results);
+    assert_eq!(expected.to_vec(), names, "Unexpected results");
+    assert_eq!(expected.len(), results.total);
+}
 
 #[test]
 fn test_queries() {
-    let mut index_path = search_engine_paths::initialize_path();
-    search_engine_paths::change_directory(&mut index_path, "documents");
-    let directory = index_path.to_str().expect("Invalid directory");
-    let mut index = PositionalInvertedIndex::new();
-    let mut k_gram_index = KGramIndex::new();
-    let docid_file =
-        document_parser::build_index(directory.to_string(), &mut index, &mut k_gram_index);
+    let (directory, k_gram_index, _) = common::build_documents_index("test_queries");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+    let boolean = SearchOptions::new(false, &DefaultScorer);
 
     let test_query_1 = "alpha"; // Tests simple query
     let test_query_2 = "alpha bravo"; // Tests query with AND operator
@@ -22,48 +35,134 @@ fn test_queries() {
     let test_query_4 = "alpha + mike"; // Tests query with OR operator
     let test_query_5 = "kilo NEAR/3 mike"; // Tests query with NEAR operator
 
-    let result_query_1 = query_processor::process_query(&test_query_1, &index, &docid_file);
-    let result_key_1 = vec!["doc1.txt", "doc2.txt", "doc5.txt"];
-    let result_key_1_size = result_key_1.len();
+    let result_query_1 = query_processor::process_query(&boolean, test_query_1, &index, &k_gram_index).unwrap();
+    assert_results(&result_query_1, &["doc1.txt", "doc2.txt", "doc5.txt"]);
 
-    let result_query_2 = query_processor::process_query(&test_query_2, &index, &docid_file);
-    let result_key_2 = vec!["doc1.txt", "doc2.txt"];
-    let result_key_2_size = result_key_2.len();
+    let result_query_2 = query_processor::process_query(&boolean, test_query_2, &index, &k_gram_index).unwrap();
+    assert_results(&result_query_2, &["doc1.txt", "doc2.txt"]);
 
-    let result_query_3 = query_processor::process_query(&test_query_3, &index, &docid_file);
+    let result_query_3 = query_processor::process_query(&boolean, test_query_3, &index, &k_gram_index).unwrap();
+    assert!(result_query_3.is_empty());
 
-    let result_query_4 = query_processor::process_query(&test_query_4, &index, &docid_file);
-    let result_key_4 = vec!["doc1.txt", "doc2.txt", "doc3.txt", "doc4.txt", "doc5.txt"];
-    let result_key_4_size = result_key_4.len();
+    let result_query_4 = query_processor::process_query(&boolean, test_query_4, &index, &k_gram_index).unwrap();
+    assert_results(&result_query_4, &["doc1.txt", "doc2.txt", "doc3.txt", "doc4.txt", "doc5.txt"]);
 
-    let result_query_5 = query_processor::process_query(&test_query_5, &index, &docid_file);
-    let result_key_5 = vec!["doc2.txt"];
-    let result_key_5_size = result_key_5.len();
-    
+    let result_query_5 = query_processor::process_query(&boolean, test_query_5, &index, &k_gram_index).unwrap();
+    assert_results(&result_query_5, &["doc2.txt"]);
+}
 
-    for result in result_key_1 {
-        assert!(result_query_1.contains(result));
-    }
+#[test]
+fn test_boolean_operators() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_boolean_operators");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+    let boolean = SearchOptions::new(false, &DefaultScorer);
+
+    let query = |input: &str| query_processor::process_query(&boolean, input, &index, &k_gram_index).unwrap();
+
+    assert_results(&query("(alpha OR kilo) AND NOT november"), &["doc3.txt"]);
+    assert_results(&query("alpha AND NOT (bravo OR mike)"), &["doc5.txt"]);
+    assert_results(&query("alpha -(bravo + (mike kilo))"), &["doc5.txt"]);
+
+    // AND binds tighter than OR
+    assert_results(&query("alpha OR kilo mike"), &["doc1.txt", "doc2.txt", "doc3.txt", "doc5.txt"]);
+    assert_results(&query("(alpha OR kilo) mike"), &["doc1.txt", "doc2.txt", "doc3.txt"]);
+
+    // Pure negations are evaluated against every document
+    assert_results(&query("NOT alpha"), &["doc3.txt", "doc4.txt"]);
+    assert_results(&query("-alpha -kilo"), &["doc4.txt"]);
+    assert_results(&query("juliet OR NOT victor"), &["doc1.txt", "doc2.txt", "doc3.txt", "doc4.txt", "doc5.txt"]);
+    assert_results(&query("NOT NOT alpha"), &["doc1.txt", "doc2.txt", "doc5.txt"]);
+
+    // Unknown terms match nothing rather than being skipped
+    assert!(query("alpha zzzz").is_empty());
+    assert!(query_processor::process_query(&boolean, "(alpha", &index, &k_gram_index).is_err());
+}
 
-    assert_eq!(result_key_1_size, result_query_1.len());
+#[test]
+fn test_proximity_operators() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_proximity_operators");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+    let boolean = SearchOptions::new(false, &DefaultScorer);
+
+    let query = |input: &str| query_processor::process_query(&boolean, input, &index, &k_gram_index).unwrap();
+
+    // NEAR/k is unordered, ONEAR/k requires the second operand to follow the first
+    assert_results(&query("mike NEAR/3 kilo"), &["doc2.txt"]);
+    assert_results(&query("mike NEAR/4 kilo"), &["doc2.txt", "doc3.txt"]);
+    assert_results(&query("kilo ONEAR/3 mike"), &["doc2.txt"]);
+    assert!(query("mike ONEAR/3 kilo").is_empty());
+
+    // Distances to a phrase are measured from its last term
+    assert_results(&query("\"alpha bravo\" ONEAR/2 lima"), &["doc1.txt"]);
+    assert!(query("\"alpha bravo\" ONEAR/1 lima").is_empty());
+
+    // WITHIN/k requires every operand inside a window spanning at most k positions
+    assert_results(&query("WITHIN/2(lima kilo victor)"), &["doc3.txt"]);
+    assert!(query("WITHIN/1(lima kilo victor)").is_empty());
+    assert_results(&query("WITHIN/4(victor juliet yankee)"), &["doc5.txt"]);
+    assert_results(&query("WITHIN/3(tango \"november kilo\" delta)"), &["doc5.txt"]);
+
+    // Proximity clauses combine with the boolean operators
+    assert_results(&query("mike NEAR/4 kilo -zulu"), &["doc3.txt"]);
+    assert_results(&query("NOT (mike NEAR/4 kilo)"), &["doc1.txt", "doc4.txt", "doc5.txt"]);
+}
 
-    for result in result_key_2 {
-        assert!(result_query_2.contains(result));
+#[test]
+fn test_ranked_results() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_ranked_results");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    let ranked = SearchOptions::new(true, &DefaultScorer);
+    let results = query_processor::process_query(&ranked, "alpha bravo", &index, &k_gram_index).unwrap();
+    assert_eq!(3, results.total);
+    assert_eq!(3, results.len());
+    assert!(!results.has_more());
+    for pair in results.hits.windows(2) {
+        assert!(pair[0].score.unwrap() >= pair[1].score.unwrap());
     }
 
-    assert_eq!(result_key_2_size, result_query_2.len());
-
-    assert!(result_query_3.is_empty());
-
-    for result in result_key_4 {
-        assert!(result_query_4.contains(result));
+    // Documents containing both terms are explained by both
+    for hit in results.hits.iter() {
+        let labels: Vec<&str> = hit.explanation.iter().map(|component| component.label.as_str()).collect();
+        if hit.path.ends_with("doc5.txt") {
+            assert_eq!(vec!["alpha"], labels);
+        } else {
+            assert_eq!(vec!["alpha", "bravo"], labels);
+        }
+        assert!(hit.explanation.iter().all(|component| component.term_frequency > 0 && component.score > 0.0));
     }
 
-    assert_eq!(result_key_4_size, result_query_4.len());
+    let mut second_page = SearchOptions::new(true, &DefaultScorer);
+    second_page.offset = 1;
+    second_page.limit = 1;
+    let page = query_processor::process_query(&second_page, "alpha bravo", &index, &k_gram_index).unwrap();
+    assert_eq!(3, page.total);
+    assert_eq!(vec![results.hits[1].clone()], page.hits);
+    assert!(page.has_more());
+
+    let mut boolean_page = SearchOptions::new(false, &DefaultScorer);
+    boolean_page.offset = 2;
+    boolean_page.limit = 2;
+    let page = query_processor::process_query(&boolean_page, "alpha + mike", &index, &k_gram_index).unwrap();
+    assert_eq!(vec!["doc3.txt", "doc4.txt"], file_names(&page));
+    assert_eq!(5, page.total);
+    assert!(page.hits.iter().all(|hit| hit.score.is_none()));
+}
 
-    for result in result_key_5 {
-        assert!(result_query_5.contains(result));
+#[test]
+fn test_results_without_corpus() {
+    let (directory, k_gram_index, docid_file) = common::build_documents_index("test_results_without_corpus");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+    for path in docid_file.values() {
+        std::fs::remove_file(path).unwrap();
     }
 
-    assert_eq!(result_key_5_size, result_query_5.len());
+    // Titles and snippets come from the document store
+    let ranked = SearchOptions::new(true, &DefaultScorer);
+    let results = query_processor::process_query(&ranked, "kilo", &index, &k_gram_index).unwrap();
+    assert_eq!(3, results.len());
+    for hit in results.hits.iter() {
+        assert_eq!(hit.path, hit.title);
+        assert!(hit.snippet.as_ref().unwrap().text.contains("kilo"));
+    }
 }
@@ -0,0 +1,134 @@
+extern crate search_engine;
+
+mod common;
+
+use search_engine::classifier::classifier::TermComponentScore;
+use search_engine::classifier::rocchio_classifier;
+use search_engine::index::disk_inverted_index::DiskInvertedIndex;
+use search_engine::index::disk_inverted_index::IndexReader;
+use search_engine::processor::query_processor;
+use search_engine::processor::query_processor::SearchOptions;
+use search_engine::processor::relevance_feedback;
+use search_engine::processor::relevance_feedback::Feedback;
+use search_engine::processor::scorer::DefaultScorer;
+use std::path::Path;
+
+fn file_name(path: &str) -> &str {
+    Path::new(path).file_name().unwrap().to_str().unwrap()
+}
+
+#[test]
+fn test_document_centroid() {
+    let (directory, _, _) = common::build_documents_index("test_document_centroid");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    // Document vectors are normalized to unit length
+    let centroid = rocchio_classifier::document_centroid(&[4], &index);
+    let length: f64 = centroid.iter().map(|component| component.score.powi(2)).sum();
+    assert!((1.0 - length).abs() < 1e-9);
+    assert!(centroid.windows(2).all(|pair| pair[0].term < pair[1].term));
+
+    let doubled = rocchio_classifier::document_centroid(&[4, 4], &index);
+    for (single, double) in centroid.iter().zip(doubled.iter()) {
+        assert_eq!(single.term, double.term);
+        assert!((single.score - double.score).abs() < 1e-9);
+    }
+
+    // Terms of only one of two documents count half
+    let pair = rocchio_classifier::document_centroid(&[3, 4], &index);
+    let golf_single = centroid.iter().find(|component| component.term == "golf").unwrap().score;
+    let golf_pair = pair.iter().find(|component| component.term == "golf").unwrap().score;
+    assert!((golf_single / 2.0 - golf_pair).abs() < 1e-9);
+
+    assert!(rocchio_classifier::document_centroid(&[], &index).is_empty());
+}
+
+#[test]
+fn test_explicit_feedback() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_explicit_feedback");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+    let options = SearchOptions::new(true, &DefaultScorer);
+    let feedback = Feedback::default();
+
+    // doc5.txt is marked relevant, so its terms are added and it ranks first
+    let (results, expansion) = relevance_feedback::process_query_with_feedback(
+        &options, &feedback, Some((&[4], &[])), "alpha", &index, &k_gram_index).unwrap();
+    assert_eq!(feedback.expansion_terms + 1, expansion.len());
+    assert!(expansion.iter().any(|term| term.term == "alpha"));
+    assert!(expansion.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    assert!(expansion.iter().any(|term| term.term == "golf"));
+    assert!(expansion.iter().any(|term| term.term == "juliet"));
+    assert_eq!("doc5.txt", file_name(&results.hits[0].path));
+    // Documents without alpha now match through the expansion terms
+    assert!(results.total > 3);
+
+    // juliet occurs more in the non-relevant doc4.txt than in doc5.txt
+    let heavy_gamma = Feedback { gamma: 1.0, expansion_terms: 20, ..Feedback::default() };
+    let expansion = relevance_feedback::rocchio_query(&heavy_gamma, &[], &[4], &[3], &index);
+    assert!(!expansion.is_empty());
+    assert!(expansion.iter().all(|term| term.score > 0.0));
+    assert!(expansion.iter().any(|term| term.term == "golf"));
+    assert!(expansion.iter().all(|term| term.term != "juliet"));
+}
+
+#[test]
+fn test_pseudo_relevance_feedback() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_pseudo_relevance_feedback");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+    let options = SearchOptions::new(true, &DefaultScorer);
+    let feedback = Feedback { feedback_documents: 1, expansion_terms: 3, ..Feedback::default() };
+
+    let (results, expansion) = relevance_feedback::process_query_with_feedback(
+        &options, &feedback, None, "kilo", &index, &k_gram_index).unwrap();
+    assert_eq!(4, expansion.len());
+    assert!(expansion.iter().all(|term| term.score > 0.0));
+
+    // The added terms come from the top document of the original query
+    let original = query_processor::process_query(&options, "kilo", &index, &k_gram_index).unwrap();
+    let top = index.get_terms_for_document(original.hits[0].doc_id);
+    assert!(expansion.iter().all(|term| top.contains(&term.term)));
+    assert!(results.total >= original.total);
+}
+
+#[test]
+fn test_rocchio_query() {
+    let (directory, _, _) = common::build_documents_index("test_rocchio_query");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+    let feedback = Feedback { alpha: 0.5, beta: 0.75, gamma: 0.25, expansion_terms: usize::MAX, feedback_documents: 10 };
+    let query_terms: Vec<String> = ["alpha", "alpha", "kilo"].iter().map(|term| term.to_string()).collect();
+
+    // alpha * q + beta * relevant centroid - gamma * non-relevant centroid over every term, with q
+    // normalized to unit length and negative weights clipped to 0
+    let relevant = rocchio_classifier::document_centroid(&[0, 4], &index);
+    let non_relevant = rocchio_classifier::document_centroid(&[3], &index);
+    let component = |centroid: &[TermComponentScore], term: &str| {
+        centroid.iter().find(|component| component.term == term).map_or(0.0, |component| component.score)
+    };
+    let mut terms: Vec<String> = relevant.iter().chain(non_relevant.iter()).map(|component| component.term.clone()).collect();
+    terms.extend(query_terms.iter().cloned());
+    terms.sort();
+    terms.dedup();
+
+    let query = relevance_feedback::rocchio_query(&feedback, &query_terms, &[0, 4], &[3], &index);
+    assert!(query.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    for term in terms {
+        let q = match term.as_str() {
+            "alpha" => 2.0 / 5f64.sqrt(),
+            "kilo" => 1.0 / 5f64.sqrt(),
+            _ => 0.0,
+        };
+        let expected = 0.5 * q + 0.75 * component(&relevant, &term) - 0.25 * component(&non_relevant, &term);
+        match query.iter().find(|component| component.term == term) {
+            Some(component) => assert!((expected.max(0.0) - component.score).abs() < 1e-9, "{}", term),
+            // Only terms outside the query with a weight of 0 or less are left out
+            None => assert!(expected <= 0.0 && term != "alpha" && term != "kilo", "{}", term),
+        }
+    }
+
+    // Query terms are kept when expansion_terms leaves no room for other terms
+    let unexpanded = Feedback { expansion_terms: 0, ..feedback };
+    let query = relevance_feedback::rocchio_query(&unexpanded, &query_terms, &[0, 4], &[3], &index);
+    let mut kept: Vec<&str> = query.iter().map(|component| component.term.as_str()).collect();
+    kept.sort();
+    assert_eq!(vec!["alpha", "kilo"], kept);
+}
@@ -0,0 +1,99 @@
+extern crate search_engine;
+
+use search_engine::processor::scorer::Bm25PlusScorer;
+use search_engine::processor::scorer::CollectionStatistics;
+use search_engine::processor::scorer::DirichletScorer;
+use search_engine::processor::scorer::DocumentStatistics;
+use search_engine::processor::scorer::JelinekMercerScorer;
+use search_engine::processor::scorer::OkapiScorer;
+use search_engine::processor::scorer::Scorer;
+use search_engine::processor::scorer::ScorerRegistry;
+use search_engine::processor::scorer::TermStatistics;
+
+struct BinaryScorer;
+
+impl Scorer for BinaryScorer {
+    fn name(&self) -> &str {
+        "binary"
+    }
+
+    fn query_weight(&self, _: &CollectionStatistics, _: &TermStatistics) -> f64 {
+        1.0
+    }
+
+    fn document_weight(&self, _: &CollectionStatistics, _: &TermStatistics, _: &DocumentStatistics, _: u32) -> f64 {
+        1.0
+    }
+
+    fn length_normalizer(&self, _: &CollectionStatistics, _: &DocumentStatistics) -> f64 {
+        1.0
+    }
+}
+
+#[test]
+fn test_scorer_registry() {
+    let mut registry = ScorerRegistry::new();
+    assert_eq!(vec!["bm25plus", "default", "dirichlet", "jelinek-mercer", "okapi", "tfidf", "wacky"], registry.names());
+    assert!(registry.get("binary").is_err());
+
+    registry.register(Box::new(BinaryScorer));
+    assert_eq!("binary", registry.get("binary").unwrap().name());
+    assert_eq!(8, registry.names().len());
+}
+
+#[test]
+fn test_default_scorer() {
+    let registry = ScorerRegistry::new();
+    let scorer = registry.get("default").unwrap();
+    let collection = CollectionStatistics { number_of_docs: 10, avg_doc_length: 4.0, total_term_frequency: 40 };
+    let term = TermStatistics { document_frequency: 5, collection_frequency: 10 };
+    let document = DocumentStatistics { doc_id: 0, weight: 2.0, length: 4, byte_size: 16, avg_tftd: 1.0 };
+
+    assert!((scorer.query_weight(&collection, &term) - 3.0_f64.ln()).abs() < 1e-9);
+    assert!((scorer.document_weight(&collection, &term, &document, 1) - 1.0).abs() < 1e-9);
+    assert!((scorer.length_normalizer(&collection, &document) - 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_bm25_length_normalization() {
+    let collection = CollectionStatistics { number_of_docs: 10, avg_doc_length: 4.0, total_term_frequency: 40 };
+    let average = DocumentStatistics { doc_id: 0, weight: 1.0, length: 4, byte_size: 16, avg_tftd: 1.0 };
+    let long = DocumentStatistics { doc_id: 1, weight: 1.0, length: 8, byte_size: 32, avg_tftd: 1.0 };
+    let term = TermStatistics { document_frequency: 5, collection_frequency: 10 };
+
+    // A document of average length gets tf * (k1 + 1) / (tf + k1)
+    let okapi = OkapiScorer::default();
+    assert!((okapi.document_weight(&collection, &term, &average, 2) - 2.0 * 2.2 / 3.2).abs() < 1e-9);
+    assert!(okapi.document_weight(&collection, &term, &long, 2) < okapi.document_weight(&collection, &term, &average, 2));
+
+    // Without length normalization the document length does not matter
+    let unnormalized = OkapiScorer::new(1.2, 0.0);
+    assert!((unnormalized.document_weight(&collection, &term, &long, 2) - unnormalized.document_weight(&collection, &term, &average, 2)).abs() < 1e-9);
+
+    // BM25+ adds delta to every matching term
+    let plus = Bm25PlusScorer::default();
+    assert!((plus.document_weight(&collection, &term, &long, 2) - okapi.document_weight(&collection, &term, &long, 2) - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_language_model_smoothing() {
+    let collection = CollectionStatistics { number_of_docs: 10, avg_doc_length: 4.0, total_term_frequency: 100 };
+    let term = TermStatistics { document_frequency: 5, collection_frequency: 10 };
+    let short = DocumentStatistics { doc_id: 0, weight: 1.0, length: 10, byte_size: 16, avg_tftd: 2.0 };
+    let long = DocumentStatistics { doc_id: 1, weight: 1.0, length: 20, byte_size: 32, avg_tftd: 2.0 };
+    assert_eq!(10.0, short.token_count());
+
+    // log(1 + tf / (mu * P(t|C))) plus |q| * log(mu / (|d| + mu))
+    let dirichlet = DirichletScorer::new(10.0);
+    let weight = dirichlet.document_weight(&collection, &term, &short, 2);
+    assert!((weight - 3.0_f64.ln()).abs() < 1e-9);
+    let score = dirichlet.document_score(&collection, &short, weight, 1);
+    assert!((score - (3.0_f64.ln() + 0.5_f64.ln())).abs() < 1e-9);
+    assert!(dirichlet.document_score(&collection, &long, weight, 1) < score);
+
+    // log(1 + (1 - lambda) * P(t|d) / (lambda * P(t|C)))
+    let jelinek_mercer = JelinekMercerScorer::new(0.5);
+    let short_weight = jelinek_mercer.document_weight(&collection, &term, &short, 2);
+    assert!((short_weight - 3.0_f64.ln()).abs() < 1e-9);
+    assert!(jelinek_mercer.document_weight(&collection, &term, &long, 2) < short_weight);
+}
@@ -0,0 +1,245 @@
+extern crate search_engine;
+extern crate serde_json;
+extern crate tiny_http;
+
+mod common;
+
+use search_engine::index::disk_inverted_index::DiskInvertedIndex;
+use search_engine::index::k_gram_index::KGramIndex;
+use search_engine::processor::suggest;
+use search_engine::server::search_server;
+use search_engine::server::search_server::SearchServer;
+use search_engine::server::search_server::ServedIndex;
+use std::fs;
+use std::io::prelude::*;
+use std::net::TcpStream;
+use std::thread;
+
+#[test]
+fn test_parse_url() {
+    let (path, parameters) = search_server::parse_url("/search?q=alpha+bravo%2A&limit=5&flag").unwrap();
+    assert_eq!("/search", path);
+    assert_eq!("alpha bravo*", parameters["q"]);
+    assert_eq!("5", parameters["limit"]);
+    assert_eq!("", parameters["flag"]);
+    assert!(search_server::parse_url("/search?q=%2").is_err());
+    assert!(search_server::parse_url("/search?q=%zz").is_err());
+}
+
+#[test]
+fn test_suggest() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_suggest");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    let suggestions = suggest::suggest("Al", &index, &k_gram_index, 10);
+    assert_eq!(vec!["alpha"], suggestions.iter().map(|suggestion| suggestion.term.as_str()).collect::<Vec<&str>>());
+    assert_eq!(3, suggestions[0].document_frequency);
+    assert!(suggestions.windows(2).all(|pair| pair[0].document_frequency >= pair[1].document_frequency));
+    assert!(suggest::suggest("zzz", &index, &k_gram_index, 10).is_empty());
+    assert!(suggest::suggest("", &index, &k_gram_index, 10).is_empty());
+    assert!(suggest::suggest("al", &index, &KGramIndex::new(), 10).is_empty());
+}
+
+#[test]
+fn test_suggest_words() {
+    let documents = ["happy people", "happiness", "it will happen", "happy days"].iter().map(|body| body.to_string()).collect::<Vec<String>>();
+    let (directory, k_gram_index, _) = common::build_generated_index("test_suggest_words", &documents);
+    let server = SearchServer::new(vec![ServedIndex::new("words", DiskInvertedIndex::new(directory.to_str().unwrap()), k_gram_index)]);
+
+    // The vocabulary holds the stems happi and happen, but the words typed are suggested
+    let response = server.handle("GET", "/suggest?q=happ");
+    assert_eq!(200, response.status);
+    let suggestions = response.body["suggestions"].as_array().unwrap();
+    let terms: Vec<&str> = suggestions.iter().map(|suggestion| suggestion["term"].as_str().unwrap()).collect();
+    assert_eq!(vec!["happiness", "happy", "happen"], terms);
+    // happy and happiness share the stem happi, found in three documents
+    assert_eq!(3, suggestions[0]["document_frequency"]);
+    assert_eq!(3, suggestions[1]["document_frequency"]);
+    assert_eq!(1, suggestions[2]["document_frequency"]);
+}
+
+#[test]
+fn test_handle() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_handle");
+    let path = directory.to_str().unwrap().to_string();
+    let server = SearchServer::new(vec![ServedIndex::new("documents", DiskInvertedIndex::new(&path), k_gram_index)]);
+
+    let response = server.handle("GET", "/search?q=alpha&limit=2&snippet=0");
+    assert_eq!(200, response.status);
+    assert_eq!("documents", response.body["index"]);
+    assert_eq!(3, response.body["total"]);
+    assert_eq!(2, response.body["hits"].as_array().unwrap().len());
+    let next = server.handle("GET", "/search?q=alpha&limit=2&offset=2&index=documents");
+    assert_eq!(1, next.body["hits"].as_array().unwrap().len());
+
+    let boolean = server.handle("GET", "/search?q=alpha+kilo&mode=boolean&scheme=okapi");
+    assert_eq!(200, boolean.status);
+    assert!(boolean.body["hits"][0]["score"].is_null());
+
+    let document = server.handle("GET", "/document/4");
+    assert_eq!(200, document.status);
+    assert!(document.body["path"].as_str().unwrap().ends_with("doc5.txt"));
+    assert!(document.body["body"].as_str().unwrap().contains("golf"));
+
+    let stats = server.handle("GET", "/stats?index=documents");
+    assert_eq!(5, stats.body["documents"]);
+    let suggestions = server.handle("GET", "/suggest?q=al");
+    assert_eq!("alpha", suggestions.body["suggestions"][0]["term"]);
+
+    assert_eq!(400, server.handle("GET", "/search").status);
+    assert_eq!(400, server.handle("GET", "/search?q=alpha&scheme=missing").status);
+    assert_eq!(400, server.handle("GET", "/search?q=alpha&limit=many").status);
+    assert_eq!(404, server.handle("GET", "/document/99").status);
+    assert_eq!(404, server.handle("GET", "/search?q=alpha&index=missing").status);
+    assert_eq!(404, server.handle("GET", "/classify?doc=doc1.txt").status);
+    assert_eq!(404, server.handle("GET", "/missing").status);
+    assert_eq!(405, server.handle("POST", "/search?q=alpha").status);
+}
+
+#[test]
+fn test_handle_reopen() {
+    let (directory, k_gram_index, _) = common::build_generated_index("test_handle_reopen", &["alpha".to_string()]);
+    let server = SearchServer::new(vec![ServedIndex::new("words", DiskInvertedIndex::new(directory.to_str().unwrap()), k_gram_index)]);
+    assert_eq!(1, server.handle("GET", "/search?q=alpha").body["total"]);
+
+    // Requests keep reading the files opened first until the index is reopened
+    let documents = ["alpha bravo", "bravo", "brave alpha"].iter().map(|body| body.to_string()).collect::<Vec<String>>();
+    let (_, k_gram_index, _) = common::build_index_at(directory.clone(), &documents);
+    fs::write(directory.join("kgram.bin"), serde_json::to_string(&k_gram_index).unwrap()).unwrap();
+    assert_eq!(1, server.handle("GET", "/search?q=alpha").body["total"]);
+
+    let reopened = server.handle("POST", "/reopen?index=words");
+    assert_eq!(200, reopened.status);
+    assert_eq!(3, reopened.body["documents"]);
+    assert_eq!(2, server.handle("GET", "/search?q=alpha").body["total"]);
+    assert_eq!(3, server.handle("GET", "/stats").body["indexes"][0]["documents"]);
+    // The K Gram Index is read again with the index
+    assert_eq!(2, server.handle("GET", "/suggest?q=bra").body["suggestions"].as_array().unwrap().len());
+
+    assert_eq!(405, server.handle("GET", "/reopen?index=words").status);
+    assert_eq!(400, server.handle("POST", "/reopen").status);
+    assert_eq!(404, server.handle("POST", "/reopen?index=missing").status);
+    fs::remove_file(directory.join("statistics.bin")).unwrap();
+    assert_eq!(500, server.handle("POST", "/reopen?index=words").status);
+    assert_eq!(2, server.handle("GET", "/search?q=alpha").body["total"]);
+}
+
+#[test]
+fn test_serve() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_serve");
+    let path = directory.to_str().unwrap().to_string();
+    let server = SearchServer::new(vec![ServedIndex::new("documents", DiskInvertedIndex::new(&path), k_gram_index)]);
+    let http = tiny_http::Server::http("127.0.0.1:0").unwrap();
+    let address = http.server_addr().to_ip().unwrap();
+    let threads = 4;
+
+    thread::scope(|scope| {
+        scope.spawn(|| server.serve(&http, threads));
+
+        // Clients sharing the index at the same time all get the full results
+        let clients: Vec<_> = (0..8)
+            .map(|_| {
+                scope.spawn(move || {
+                    let mut stream = TcpStream::connect(address).unwrap();
+                    write!(stream, "GET /search?q=alpha HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+                    let mut response = String::new();
+                    stream.read_to_string(&mut response).unwrap();
+                    response
+                })
+            })
+            .collect();
+        for client in clients {
+            let response = client.join().unwrap();
+            assert!(response.starts_with("HTTP/1.1 200"));
+            assert!(response.contains("application/json"));
+            assert!(response.contains("\"total\":3"));
+        }
+        for _ in 0..threads {
+            http.unblock();
+        }
+    });
+}
+
+#[test]
+fn test_handle_federated() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_handle_federated");
+    let (copy, copy_k_gram_index, _) = common::build_documents_index("test_handle_federated_copy");
+    let server = SearchServer::new(vec![
+        ServedIndex::new("documents", DiskInvertedIndex::new(&directory), k_gram_index),
+        ServedIndex::new("copy", DiskInvertedIndex::new(&copy), copy_k_gram_index),
+    ]);
+
+    let response = server.handle("GET", "/search?q=alpha&index=documents,copy&snippet=0");
+    assert_eq!(200, response.status);
+    assert_eq!(6, response.body["total"]);
+    assert_eq!("documents", response.body["index"][0]);
+    let hits = response.body["hits"].as_array().unwrap();
+    assert_eq!(6, hits.len());
+    // Both copies of a document score the same, the first index listed first
+    assert_eq!("documents", hits[0]["index"]);
+    assert_eq!("copy", hits[1]["index"]);
+    assert_eq!(hits[0]["score"], hits[1]["score"]);
+    assert_eq!(hits[0]["doc_id"], hits[1]["doc_id"]);
+
+    let boolean = server.handle("GET", "/search?q=alpha&index=*&mode=boolean&limit=4");
+    assert_eq!(6, boolean.body["total"]);
+    assert_eq!(4, boolean.body["hits"].as_array().unwrap().len());
+    assert_eq!("copy", boolean.body["hits"][3]["index"]);
+    assert_eq!(404, server.handle("GET", "/search?q=alpha&index=documents,missing").status);
+}
+
+#[test]
+fn test_handle_classify() {
+    let directory = std::env::temp_dir().join(format!("search_engine_test_handle_classify_{}", std::process::id()));
+    let classes: [(&str, &[&str]); 4] = [
+        ("unlabelled", &["alpha bravo charlie", "golf hotel india"]),
+        ("first", &["alpha bravo kilo", "alpha charlie lima"]),
+        ("second", &["delta echo foxtrot", "delta echo mike"]),
+        ("third", &["golf hotel india", "golf india november"]),
+    ];
+    let served: Vec<ServedIndex> = classes
+        .iter()
+        .map(|&(name, documents)| {
+            let documents: Vec<String> = documents.iter().map(|document| document.to_string()).collect();
+            let (path, k_gram_index, _) = common::build_index_at(directory.join(name), &documents);
+            ServedIndex::new(name, DiskInvertedIndex::new(&path), k_gram_index)
+        })
+        .collect();
+    let server = SearchServer::new(served);
+
+    // Every other index is a class by default
+    let response = server.handle("GET", "/classify?doc=doc00000.txt");
+    assert_eq!(200, response.status);
+    assert_eq!("first", response.body["label"]);
+    assert_eq!(3, response.body["scores"].as_object().unwrap().len());
+    let response = server.handle("GET", "/classify?doc=doc00001.txt&method=bayes");
+    assert_eq!("third", response.body["label"]);
+    // The trained classifier is kept for the next requests
+    assert_eq!(response, server.handle("GET", "/classify?doc=doc00001.txt&method=bayesian"));
+
+    assert_eq!(2, server.get_classifier_count());
+
+    let response = server.handle("GET", "/classify?doc=doc00000.txt&method=knn&k=1&voting=weighted");
+    assert_eq!("knn", response.body["method"]);
+    assert_eq!("first", response.body["label"]);
+    assert_eq!(400, server.handle("GET", "/classify?doc=doc00000.txt&method=knn&voting=closest").status);
+    // One kNN classifier answers for every number of neighbours and voting
+    let response = server.handle("GET", "/classify?doc=doc00000.txt&method=knn&k=1");
+    assert_eq!(1.0, response.body["scores"]["first"]);
+    let response = server.handle("GET", "/classify?doc=doc00000.txt&method=knn&k=5");
+    assert_eq!(2.0, response.body["scores"]["first"]);
+    let response = server.handle("GET", "/classify?doc=doc00000.txt&method=knn&k=5&voting=weighted");
+    let weighted = response.body["scores"]["first"].as_f64().unwrap();
+    assert!(weighted > 0.0 && weighted < 2.0);
+    assert_eq!(3, server.get_classifier_count());
+
+    let response = server.handle("GET", "/classify?doc=doc00001.txt&classes=first,second");
+    assert_eq!(200, response.status);
+    assert_eq!(2, response.body["scores"].as_object().unwrap().len());
+    assert!(response.body["scores"]["third"].is_null());
+
+    assert_eq!(404, server.handle("GET", "/classify?doc=doc00000.txt&classes=first").status);
+    assert_eq!(404, server.handle("GET", "/classify?doc=doc00000.txt&classes=first,missing").status);
+    assert_eq!(400, server.handle("GET", "/classify?doc=doc00000.txt&classes=first,unlabelled").status);
+    assert_eq!(404, server.handle("GET", "/classify?doc=missing.txt").status);
+}
@@ -0,0 +1,108 @@
+extern crate search_engine;
+
+mod common;
+
+use search_engine::index::disk_inverted_index::DiskInvertedIndex;
+use search_engine::processor::query_processor;
+use search_engine::processor::query_processor::SearchOptions;
+use search_engine::processor::scorer::DefaultScorer;
+use search_engine::processor::snippet;
+use search_engine::processor::snippet::Highlight;
+use search_engine::processor::snippet::TermOccurrences;
+
+fn at(positions: Vec<u32>) -> TermOccurrences {
+    TermOccurrences { positions, offsets: None }
+}
+
+#[test]
+fn test_snippet_window() {
+    let body = "The quick, brown\nfox jumps over   the lazy dog.";
+    assert_eq!(
+        vec![(0, 0, 3), (1, 4, 10), (2, 11, 16), (3, 17, 20), (4, 21, 26), (5, 27, 31), (6, 34, 37), (7, 38, 42), (8, 43, 47)],
+        snippet::token_offsets(body)
+    );
+
+    // The passage is centered on the window holding the most distinct terms
+    let generated = snippet::generate(body, &[at(vec![2]), at(vec![3, 8])], 4);
+    assert_eq!("quick, brown fox jumps", generated.text);
+    assert_eq!("...quick, <mark>brown</mark> <mark>fox</mark> jumps...", generated.render(Highlight::Markup));
+    assert_eq!("...quick, \x1b[1;33mbrown\x1b[0m \x1b[1;33mfox\x1b[0m jumps...", generated.render(Highlight::Ansi));
+
+    // Punctuation around a matched token is not highlighted
+    let generated = snippet::generate(body, &[at(vec![1])], 3);
+    assert_eq!("The <mark>quick</mark>, brown...", generated.render(Highlight::Markup));
+    let generated = snippet::generate(body, &[at(vec![8])], 3);
+    assert_eq!("...the lazy <mark>dog</mark>.", generated.render(Highlight::Markup));
+
+    // Without matches the passage starts at the beginning of the document
+    let generated = snippet::generate(body, &[], 2);
+    assert_eq!("The quick,...", generated.render(Highlight::Markup));
+    assert!(generated.highlights.is_empty());
+
+    let generated = snippet::generate("a<b alpha & omega", &[at(vec![1])], 4);
+    assert_eq!("a&lt;b <mark>alpha</mark> &amp; omega", generated.render(Highlight::Markup));
+}
+
+#[test]
+fn test_snippet_offsets() {
+    let body = "Café state-of-the-art, (Don't) stop.";
+    let the = TermOccurrences { positions: vec![3], offsets: Some(vec![(14, 17)]) };
+    let generated = snippet::generate(body, &[the], 3);
+    assert_eq!("Café state-of-<mark>the</mark>-art, (Don't)...", generated.render(Highlight::Markup));
+
+    // The parts of a hyphenated word overlap the joined word
+    let state = TermOccurrences { positions: vec![1], offsets: Some(vec![(5, 10)]) };
+    let joined = TermOccurrences { positions: vec![1], offsets: Some(vec![(5, 21)]) };
+    let generated = snippet::generate(body, &[state, joined], 2);
+    assert_eq!("...<mark>state-of-the-art</mark>, (Don't)...", generated.render(Highlight::Markup));
+
+    // Offsets outside the token fall back to highlighting the token
+    let stale = TermOccurrences { positions: vec![5], offsets: Some(vec![(0, 4)]) };
+    let generated = snippet::generate(body, &[stale], 4);
+    assert_eq!("Café state-of-the-art, (<mark>Don't</mark>) stop.", generated.render(Highlight::Markup));
+}
+
+#[test]
+fn test_snippet_after_hyphenated_words() {
+    // The parts of a hyphenated word take a position each, shifting the positions after it
+    let body = "A well-known state-of-the-art design wins.";
+    assert_eq!(vec![(0, 0, 1), (1, 2, 12), (3, 13, 29), (7, 30, 36), (8, 37, 42)], snippet::token_offsets(body));
+
+    let generated = snippet::generate(body, &[at(vec![7])], 2);
+    assert_eq!("...<mark>design</mark> wins.", generated.render(Highlight::Markup));
+    let generated = snippet::generate(body, &[at(vec![4]), at(vec![8])], 3);
+    assert_eq!("...<mark>state-of-the-art</mark> design <mark>wins</mark>.", generated.render(Highlight::Markup));
+    let known = TermOccurrences { positions: vec![2], offsets: Some(vec![(7, 12)]) };
+    let generated = snippet::generate(body, &[known, at(vec![7])], 5);
+    assert_eq!("A well-<mark>known</mark> state-of-the-art <mark>design</mark> wins.", generated.render(Highlight::Markup));
+
+    // Positions past the last token are ignored
+    let generated = snippet::generate(body, &[at(vec![9])], 2);
+    assert!(generated.highlights.is_empty());
+}
+
+#[test]
+fn test_result_snippets() {
+    let (directory, k_gram_index, _) = common::build_documents_index("test_result_snippets");
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+
+    let mut options = SearchOptions::new(false, &DefaultScorer);
+    options.snippet_length = 5;
+    let results = query_processor::process_query(&options, "alpha bravo", &index, &k_gram_index).unwrap();
+    let snippets: Vec<String> = results
+        .hits
+        .iter()
+        .map(|hit| hit.snippet.as_ref().unwrap().render(Highlight::Markup))
+        .collect();
+    assert_eq!(
+        vec![
+            "...hotel <mark>alpha</mark> <mark>bravo</mark> mike lima...",
+            "<mark>bravo</mark> papa lima sierra <mark>bravo</mark>...",
+        ],
+        snippets
+    );
+
+    options.snippet_length = 0;
+    let results = query_processor::process_query(&options, "alpha bravo", &index, &k_gram_index).unwrap();
+    assert!(results.hits.iter().all(|hit| hit.snippet.is_none()));
+}
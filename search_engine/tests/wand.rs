@@ -0,0 +1,154 @@
+extern crate search_engine;
+
+mod common;
+
+use search_engine::classifier::classifier::TermComponentScore;
+use search_engine::index::disk_inverted_index::DiskInvertedIndex;
+use search_engine::parser::query_parser::QueryParser;
+use search_engine::processor::query_processor;
+use search_engine::processor::query_processor::SearchOptions;
+use search_engine::processor::scorer::ScorerRegistry;
+use search_engine::processor::wand::Pruning;
+
+const WORDS: [&str; 12] = [
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot",
+    "golf", "hotel", "india", "juliet", "kilo", "lima",
+];
+
+/*
+ * Generates documents of varying length whose words follow a skewed distribution, so that posting
+ * lists span several blocks and score bounds differ between blocks
+ */
+fn generate_documents(count: usize) -> Vec<String> {
+    let mut state: u64 = 42;
+    let mut next = move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+    (0..count)
+        .map(|_| {
+            let length = 3 + next() % 60;
+            (0..length)
+                .map(|_| {
+                    let first = next() % WORDS.len();
+                    WORDS[next() % (first + 1)]
+                })
+                .collect::<Vec<&str>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+#[test]
+fn test_pruning_matches_exhaustive_ranking() {
+    let (directory, k_gram_index, docid_file) = common::build_generated_index("test_pruning", &generate_documents(400));
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+    let scorers = ScorerRegistry::new();
+    let parser = QueryParser::new();
+
+    let queries = ["alpha bravo", "kilo lima india", "alpha charlie golf lima", "juliet", "hotel hotel echo"];
+    for name in scorers.names() {
+        for input in queries.iter() {
+            let query = parser.parse(input).unwrap();
+            let rank = |pruning: Pruning, limit: usize| {
+                let mut options = SearchOptions::new(true, scorers.get(name).unwrap());
+                options.pruning = pruning;
+                options.limit = limit;
+                query_processor::rank_query(&query, &options, &index, &k_gram_index, docid_file.len() as u32).unwrap()
+            };
+
+            for &limit in [1, 10, 50].iter() {
+                let exhaustive = rank(Pruning::Exhaustive, limit);
+                for &pruning in [Pruning::Wand, Pruning::BlockMaxWand].iter() {
+                    let pruned = rank(pruning, limit);
+                    assert!(!exhaustive.2);
+                    if pruned.2 {
+                        // Pruning only counts part of the matches, but enough to show whether more remain
+                        assert!(pruned.1 <= exhaustive.1);
+                        assert_eq!(exhaustive.1 > limit, pruned.1 > limit);
+                    } else {
+                        assert_eq!(exhaustive.1, pruned.1);
+                    }
+                    let pruned_ids: Vec<u32> = pruned.0.iter().map(|result| result.doc_id).collect();
+                    let exhaustive_ids: Vec<u32> = exhaustive.0.iter().map(|result| result.doc_id).collect();
+                    assert_eq!(exhaustive_ids, pruned_ids, "{:?} with {} for '{}'", pruning, name, input);
+                    for (expected, actual) in exhaustive.0.iter().zip(pruned.0.iter()) {
+                        assert!((expected.score - actual.score).abs() < 1e-9);
+                        assert_eq!(expected.explanation, actual.explanation);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_pruning_skips_postings() {
+    // Rare terms set a threshold no document holding only the common terms can reach
+    let documents: Vec<String> = generate_documents(2000)
+        .into_iter()
+        .enumerate()
+        .map(|(i, document)| match i {
+            i if i % 200 == 0 => document + " zulu",
+            i if i % 300 == 0 => document + " yankee",
+            _ => document,
+        })
+        .collect();
+    let (directory, k_gram_index, docid_file) = common::build_generated_index("test_pruning_skips", &documents);
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+    let scorers = ScorerRegistry::new();
+    let query = QueryParser::new().parse("alpha bravo charlie delta echo foxtrot golf hotel zulu yankee").unwrap();
+
+    let mut decoded = Vec::new();
+    for &pruning in [Pruning::Exhaustive, Pruning::Wand, Pruning::BlockMaxWand].iter() {
+        let mut options = SearchOptions::new(true, scorers.get("okapi").unwrap());
+        options.pruning = pruning;
+        let before = index.get_decoded_postings();
+        let (ranked, _, lower_bound) = query_processor::rank_query(&query, &options, &index, &k_gram_index, docid_file.len() as u32).unwrap();
+        assert_eq!(10, ranked.len());
+        assert_eq!(pruning != Pruning::Exhaustive, lower_bound);
+        decoded.push(index.get_decoded_postings() - before);
+    }
+    // Blocks the bounds rule out are skipped without being read
+    assert!(decoded[1] < decoded[0], "WAND decoded {} of {} postings", decoded[1], decoded[0]);
+    assert!(decoded[2] < decoded[0], "Block-Max WAND decoded {} of {} postings", decoded[2], decoded[0]);
+}
+
+#[test]
+fn test_pruning_with_expansion_weights() {
+    // zulu is rare, so with a heavy expansion weight its documents must outrank the alpha ones
+    let documents: Vec<String> = generate_documents(300)
+        .into_iter()
+        .enumerate()
+        .map(|(i, document)| if i % 50 == 7 { document + " zulu" } else { document })
+        .collect();
+    let (directory, k_gram_index, docid_file) = common::build_generated_index("test_pruning_expansion", &documents);
+    let index = DiskInvertedIndex::new(directory.to_str().unwrap());
+    let scorers = ScorerRegistry::new();
+    let query = QueryParser::new().parse("alpha").unwrap();
+
+    for &weight in [0.3, 1.75, 50.0].iter() {
+        let expansion = vec![
+            TermComponentScore::new(weight, String::from("zulu")).unwrap(),
+            TermComponentScore::new(weight / 2.0, String::from("kilo")).unwrap(),
+        ];
+        for name in scorers.names() {
+            let rank = |pruning: Pruning| {
+                let mut options = SearchOptions::new(true, scorers.get(name).unwrap());
+                options.pruning = pruning;
+                options.expansion = &expansion;
+                query_processor::rank_query(&query, &options, &index, &k_gram_index, docid_file.len() as u32).unwrap().0
+            };
+            let exhaustive = rank(Pruning::Exhaustive);
+            for &pruning in [Pruning::Wand, Pruning::BlockMaxWand].iter() {
+                let pruned = rank(pruning);
+                let pruned_ids: Vec<u32> = pruned.iter().map(|result| result.doc_id).collect();
+                let exhaustive_ids: Vec<u32> = exhaustive.iter().map(|result| result.doc_id).collect();
+                assert_eq!(exhaustive_ids, pruned_ids, "{:?} with {} for zulu weighing {}", pruning, name, weight);
+                for (expected, actual) in exhaustive.iter().zip(pruned.iter()) {
+                    assert!((expected.score - actual.score).abs() < 1e-9);
+                }
+            }
+        }
+    }
+}